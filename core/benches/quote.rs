@@ -0,0 +1,92 @@
+//! Compares quoting through [`QuoteContext`] with quoting directly from [`State`].
+//!
+//! Run with `cargo bench -p sanctum-marinade-liquid-staking-core`
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use sanctum_marinade_liquid_staking_core::{
+    DepositSolQuoteArgs, DepositStakeQuoteArgs, QuoteContext, StakeAccountLamports, StakeRecord,
+    State, WithdrawStakeQuoteArgs, STATE_PUBKEY,
+};
+
+use crate::common::{mainnet_state, KeyedUiAccount};
+
+#[allow(dead_code, unused_imports)]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+const AMOUNT: u64 = 1_000_000_000_000;
+
+fn mainnet_state_and_msol_leg_balance() -> (State, u64) {
+    let state = mainnet_state();
+    let msol_leg = KeyedUiAccount::from_test_fixtures_file("marinade-liq_pool_msol_leg");
+    let msol_leg_balance = state
+        .msol_leg_balance(&STATE_PUBKEY, &msol_leg.account_data())
+        .unwrap();
+    (state, msol_leg_balance)
+}
+
+fn bench_quotes(c: &mut Criterion) {
+    let (state, msol_leg_balance) = mainnet_state_and_msol_leg_balance();
+    let ctx = QuoteContext::new(&state, msol_leg_balance).unwrap();
+    let sol_args = DepositSolQuoteArgs { msol_leg_balance };
+    let stake_args = DepositStakeQuoteArgs { msol_leg_balance };
+    let withdraw_args = WithdrawStakeQuoteArgs {
+        stake_record: StakeRecord::new([1; 32], AMOUNT * 10, 0, false),
+    };
+    let stake_account_lamports = StakeAccountLamports {
+        staked: AMOUNT,
+        unstaked: 0,
+    };
+
+    let mut group = c.benchmark_group("lamports_to_pool_tokens");
+    group.bench_function("State", |b| {
+        b.iter(|| black_box(&state).lamports_to_pool_tokens(black_box(AMOUNT)))
+    });
+    group.bench_function("QuoteContext", |b| {
+        b.iter(|| black_box(&ctx).lamports_to_pool_tokens(black_box(AMOUNT)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("quote_deposit_sol");
+    group.bench_function("State", |b| {
+        b.iter(|| black_box(&state).quote_deposit_sol(black_box(AMOUNT), sol_args))
+    });
+    group.bench_function("QuoteContext", |b| {
+        b.iter(|| black_box(&ctx).quote_deposit_sol(black_box(AMOUNT)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("quote_deposit_stake");
+    group.bench_function("State", |b| {
+        b.iter(|| {
+            black_box(&state).quote_deposit_stake(black_box(stake_account_lamports), stake_args)
+        })
+    });
+    group.bench_function("QuoteContext", |b| {
+        b.iter(|| black_box(&ctx).quote_deposit_stake(black_box(stake_account_lamports)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("quote_withdraw_stake");
+    group.bench_function("State", |b| {
+        b.iter(|| black_box(&state).quote_withdraw_stake(black_box(AMOUNT), withdraw_args))
+    });
+    group.bench_function("QuoteContext", |b| {
+        b.iter(|| black_box(&ctx).quote_withdraw_stake(black_box(AMOUNT), withdraw_args))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("quote_deposit_sol_exact_out");
+    group.bench_function("State", |b| {
+        b.iter(|| black_box(&state).quote_deposit_sol_exact_out(black_box(AMOUNT), sol_args))
+    });
+    group.bench_function("QuoteContext", |b| {
+        b.iter(|| black_box(&ctx).quote_deposit_sol_exact_out(black_box(AMOUNT)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_quotes);
+criterion_main!(benches);
@@ -0,0 +1,185 @@
+//! Port of the stake program's effective, activating and deactivating stake calculation
+//!
+//! https://github.com/solana-program/stake/blob/master/interface/src/state.rs
+
+use crate::{Delegation, StakeHistory, DEFAULT_WARMUP_COOLDOWN_RATE};
+
+/// Warmup and cooldown rate after the `reduce_stake_warmup_cooldown` feature
+pub const NEW_WARMUP_COOLDOWN_RATE: f64 = 0.09;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StakeActivationStatus {
+    pub effective: u64,
+    pub activating: u64,
+    pub deactivating: u64,
+}
+
+impl StakeActivationStatus {
+    /// Stake that is fully active, with nothing warming up or cooling down.
+    ///
+    /// Only such stake can be deposited into Marinade.
+    #[inline]
+    pub const fn is_fully_active(&self) -> bool {
+        self.effective > 0 && self.activating == 0 && self.deactivating == 0
+    }
+
+    #[inline]
+    const fn with_deactivating(deactivating: u64) -> Self {
+        Self {
+            effective: deactivating,
+            activating: 0,
+            deactivating,
+        }
+    }
+}
+
+#[inline]
+pub const fn warmup_cooldown_rate(
+    current_epoch: u64,
+    new_rate_activation_epoch: Option<u64>,
+) -> f64 {
+    let new_rate_activation_epoch = match new_rate_activation_epoch {
+        Some(e) => e,
+        None => u64::MAX,
+    };
+    if current_epoch < new_rate_activation_epoch {
+        DEFAULT_WARMUP_COOLDOWN_RATE
+    } else {
+        NEW_WARMUP_COOLDOWN_RATE
+    }
+}
+
+/// Effective, activating and deactivating stake of `delegation` at `current_epoch`.
+///
+/// `new_rate_activation_epoch` is the epoch the `reduce_stake_warmup_cooldown` feature
+/// was activated at, `None` if it has not been activated.
+/// It only affects stake that was warming up or cooling down across that epoch,
+/// so `Some(0)` may be passed for delegations made after it.
+pub fn activation_status(
+    delegation: &Delegation,
+    current_epoch: u64,
+    history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> StakeActivationStatus {
+    let (effective_stake, activating_stake) = stake_and_activating(
+        delegation,
+        current_epoch,
+        history,
+        new_rate_activation_epoch,
+    );
+
+    if current_epoch < delegation.deactivation_epoch {
+        return StakeActivationStatus {
+            effective: effective_stake,
+            activating: activating_stake,
+            deactivating: 0,
+        };
+    }
+    if current_epoch == delegation.deactivation_epoch {
+        // can only deactivate what's activated
+        return StakeActivationStatus::with_deactivating(effective_stake);
+    }
+
+    // no history or dropped out of history, assume fully deactivated
+    let Some(mut prev_cluster_stake) = history.get(delegation.deactivation_epoch) else {
+        return StakeActivationStatus::default();
+    };
+    let mut prev_epoch = delegation.deactivation_epoch;
+    let mut current_effective_stake = effective_stake;
+    loop {
+        let epoch = prev_epoch.saturating_add(1);
+        // no deactivating stake at prev epoch means fully undelegated by now
+        if prev_cluster_stake.deactivating() == 0 {
+            break;
+        }
+
+        // portion of the cluster's deactivating stake this delegation is entitled to
+        let weight = current_effective_stake as f64 / prev_cluster_stake.deactivating() as f64;
+        let newly_not_effective_cluster_stake = prev_cluster_stake.effective() as f64
+            * warmup_cooldown_rate(epoch, new_rate_activation_epoch);
+        let newly_not_effective_stake =
+            ((weight * newly_not_effective_cluster_stake) as u64).max(1);
+
+        current_effective_stake = current_effective_stake.saturating_sub(newly_not_effective_stake);
+        if current_effective_stake == 0 || epoch >= current_epoch {
+            break;
+        }
+        match history.get(epoch) {
+            Some(entry) => {
+                prev_epoch = epoch;
+                prev_cluster_stake = entry;
+            }
+            None => break,
+        }
+    }
+
+    StakeActivationStatus::with_deactivating(current_effective_stake)
+}
+
+/// Returns (effective, activating) stake
+fn stake_and_activating(
+    delegation: &Delegation,
+    current_epoch: u64,
+    history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> (u64, u64) {
+    let delegated_stake = delegation.stake;
+
+    if delegation.activation_epoch == u64::MAX {
+        // bootstrap stake is fully effective immediately
+        return (delegated_stake, 0);
+    }
+    if delegation.activation_epoch == delegation.deactivation_epoch {
+        // activated but instantly deactivated, no stake at all regardless of current_epoch
+        return (0, 0);
+    }
+    if current_epoch == delegation.activation_epoch {
+        return (0, delegated_stake);
+    }
+    if current_epoch < delegation.activation_epoch {
+        return (0, 0);
+    }
+
+    // no history or dropped out of history, assume fully effective
+    let Some(mut prev_cluster_stake) = history.get(delegation.activation_epoch) else {
+        return (delegated_stake, 0);
+    };
+    let mut prev_epoch = delegation.activation_epoch;
+    let mut current_effective_stake = 0;
+    loop {
+        let epoch = prev_epoch.saturating_add(1);
+        // no activating stake at prev epoch means fully effective by now
+        if prev_cluster_stake.activating() == 0 {
+            break;
+        }
+
+        // portion of the cluster's activating stake this delegation is entitled to
+        let remaining_activating_stake = delegated_stake - current_effective_stake;
+        let weight = remaining_activating_stake as f64 / prev_cluster_stake.activating() as f64;
+        let newly_effective_cluster_stake = prev_cluster_stake.effective() as f64
+            * warmup_cooldown_rate(epoch, new_rate_activation_epoch);
+        let newly_effective_stake = ((weight * newly_effective_cluster_stake) as u64).max(1);
+
+        current_effective_stake = current_effective_stake.saturating_add(newly_effective_stake);
+        if current_effective_stake >= delegated_stake {
+            current_effective_stake = delegated_stake;
+            break;
+        }
+        if epoch >= current_epoch || epoch >= delegation.deactivation_epoch {
+            break;
+        }
+        match history.get(epoch) {
+            Some(entry) => {
+                prev_epoch = epoch;
+                prev_cluster_stake = entry;
+            }
+            None => break,
+        }
+    }
+
+    (
+        current_effective_stake,
+        delegated_stake - current_effective_stake,
+    )
+}
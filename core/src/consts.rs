@@ -20,6 +20,9 @@ pub const SYSTEM_PROGRAM: [u8; 32] = bs58::decode_pubkey("1111111111111111111111
 pub const TOKEN_PROGRAM: [u8; 32] =
     bs58::decode_pubkey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
+pub const COMPUTE_BUDGET_PROGRAM: [u8; 32] =
+    bs58::decode_pubkey("ComputeBudget111111111111111111111111111111");
+
 pub const ASSOCIATED_TOKEN_PROGRAM: [u8; 32] =
     bs58::decode_pubkey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
 
@@ -29,3 +32,9 @@ pub const MARINADE_STAKING_PROGRAM: [u8; 32] =
 
 pub const MSOL_MINT_ADDR: [u8; 32] =
     bs58::decode_pubkey("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So");
+
+pub const LP_MINT_ADDR: [u8; 32] =
+    bs58::decode_pubkey("LPmSozJJ8Jh69ut2WP3XmVohTjL4ipR18yiCzxrUmVj");
+
+/// Max serialized transaction size
+pub const PACKET_DATA_SIZE: usize = 1232;
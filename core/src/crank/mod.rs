@@ -0,0 +1,9 @@
+mod scan;
+
+#[cfg(feature = "alloc")]
+mod plan;
+
+pub use scan::*;
+
+#[cfg(feature = "alloc")]
+pub use plan::*;
@@ -0,0 +1,113 @@
+use core::ops::Range;
+
+use alloc::vec::Vec;
+
+use crate::{
+    message::legacy_batches, DynInstruction, StakeRecordUpdate, StakeRecordUpdateKind,
+    UpdateActiveIx, UpdateActiveIxData, UpdateActiveIxKeysOwned, UpdateDeactivatedIx,
+    UpdateDeactivatedIxData, UpdateDeactivatedIxKeysOwned,
+};
+
+/// Stands in for a fee payer that is not in any of the crank instructions
+/// when sizing their transactions
+const SIZING_FEE_PAYER: [u8; 32] = [u8::MAX; 32];
+
+/// Crank instructions that bring stake records up to date for the current epoch.
+///
+/// The transactions must be executed in order,
+/// all of `update_active_transactions` before `update_deactivated_transactions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrankPlan {
+    /// In ascending stake index order
+    pub update_active: Vec<UpdateActiveIx>,
+
+    /// Ranges of `update_active` to be sent in the same transaction
+    pub update_active_transactions: Vec<Range<usize>>,
+
+    /// In descending stake index order.
+    ///
+    /// `update_deactivated` removes the record by moving the last record in the list into its place,
+    /// so going from the back keeps the stake indices of the remaining instructions valid.
+    pub update_deactivated: Vec<UpdateDeactivatedIx>,
+
+    /// Ranges of `update_deactivated` to be sent in the same transaction
+    pub update_deactivated_transactions: Vec<Range<usize>>,
+
+    /// Stake indices of records that are still cooling down and cannot be updated yet
+    pub cooling_down: Vec<u32>,
+}
+
+impl CrankPlan {
+    /// `updates` are typically [`crate::stale_stake_records`]
+    /// classified with [`crate::stake_record_update_kind`].
+    ///
+    /// `update_active_keys` and `update_deactivated_keys` must have all keys set
+    /// except `stake_account`, which is set for each instruction.
+    ///
+    /// Transactions are sized assuming a fee payer that is not in any of the instructions.
+    pub fn new<I>(
+        updates: I,
+        update_active_keys: &UpdateActiveIxKeysOwned,
+        update_deactivated_keys: &UpdateDeactivatedIxKeysOwned,
+    ) -> Self
+    where
+        I: IntoIterator<Item = StakeRecordUpdate>,
+    {
+        let mut active = Vec::new();
+        let mut deactivated = Vec::new();
+        let mut cooling_down = Vec::new();
+        for update in updates {
+            match update.kind {
+                StakeRecordUpdateKind::Active { validator_index } => {
+                    active.push((update.stake_index, validator_index, update.stake_record))
+                }
+                StakeRecordUpdateKind::Deactivated => {
+                    deactivated.push((update.stake_index, update.stake_record))
+                }
+                StakeRecordUpdateKind::CoolingDown => cooling_down.push(update.stake_index),
+            }
+        }
+        active.sort_by_key(|(stake_index, ..)| *stake_index);
+        deactivated.sort_by_key(|(stake_index, _)| core::cmp::Reverse(*stake_index));
+        cooling_down.sort_unstable();
+
+        let update_active: Vec<_> = active
+            .into_iter()
+            .map(|(stake_index, validator_index, stake_record)| {
+                update_active_keys
+                    .clone()
+                    .with_stake_account(*stake_record.stake_account())
+                    .to_ix(&UpdateActiveIxData::new(stake_index, validator_index))
+            })
+            .collect();
+        let update_deactivated: Vec<_> = deactivated
+            .into_iter()
+            .map(|(stake_index, stake_record)| {
+                update_deactivated_keys
+                    .clone()
+                    .with_stake_account(*stake_record.stake_account())
+                    .to_ix(&UpdateDeactivatedIxData::new(stake_index))
+            })
+            .collect();
+
+        Self {
+            update_active_transactions: legacy_batches(
+                &update_active
+                    .iter()
+                    .map(|&ix| ix.into())
+                    .collect::<Vec<_>>(),
+                &SIZING_FEE_PAYER,
+            ),
+            update_active,
+            update_deactivated_transactions: legacy_batches(
+                &update_deactivated
+                    .iter()
+                    .map(|&ix| ix.into())
+                    .collect::<Vec<DynInstruction>>(),
+                &SIZING_FEE_PAYER,
+            ),
+            update_deactivated,
+            cooling_down,
+        }
+    }
+}
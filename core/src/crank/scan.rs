@@ -0,0 +1,85 @@
+use crate::{
+    activation_status, MarinadeError, StakeHistory, StakeList, StakeRecord, StakeStateV2,
+    ValidatorList,
+};
+
+/// The crank instruction that updates a stake record for the current epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StakeRecordUpdateKind {
+    /// `update_active`, with the `validator_index` of the stake account's voter
+    Active { validator_index: u32 },
+
+    /// `update_deactivated`, which withdraws the fully deactivated stake account
+    /// (normal or emergency unstake) to the reserve and removes its record from the list
+    Deactivated,
+
+    /// The stake account is still cooling down and
+    /// cannot be updated until it is fully deactivated
+    CoolingDown,
+}
+
+/// A stake record that is not yet updated for the current epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakeRecordUpdate {
+    pub stake_index: u32,
+    pub stake_record: StakeRecord,
+    pub kind: StakeRecordUpdateKind,
+}
+
+/// Records in `stake_list` with their `stake_index`
+/// whose `last_update_epoch` is before `current_epoch`
+#[inline]
+pub fn stale_stake_records<'a>(
+    stake_list: &'a StakeList<'a>,
+    current_epoch: u64,
+) -> impl Iterator<Item = (u32, StakeRecord)> + 'a {
+    stake_list
+        .0
+        .iter()
+        .zip(0..)
+        .filter(move |(stake_record, _)| stake_record.last_update_epoch() < current_epoch)
+        .map(|(stake_record, stake_index)| (stake_index, *stake_record))
+}
+
+/// Classifies the update of a stake record from the state of its stake account.
+///
+/// See [`crate::activation_status`] for `new_rate_activation_epoch`.
+///
+/// Errors if the stake account is not delegated or
+/// the voter of an active stake account is not in `validator_list`.
+pub fn stake_record_update_kind(
+    stake_state: &StakeStateV2,
+    validator_list: &ValidatorList,
+    current_epoch: u64,
+    stake_history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> Result<StakeRecordUpdateKind, MarinadeError> {
+    let delegation = stake_state
+        .delegation()
+        .ok_or(MarinadeError::RequiredDelegatedStake)?;
+
+    if !delegation.is_deactivated() {
+        return validator_list
+            .0
+            .iter()
+            .position(|v| v.validator_account() == &delegation.voter_pubkey)
+            .and_then(|i| u32::try_from(i).ok())
+            .map(|validator_index| StakeRecordUpdateKind::Active { validator_index })
+            .ok_or(MarinadeError::WrongValidatorAccountOrIndex);
+    }
+
+    let status = activation_status(
+        delegation,
+        current_epoch,
+        stake_history,
+        new_rate_activation_epoch,
+    );
+    Ok(
+        if status.effective == 0 && status.activating == 0 && status.deactivating == 0 {
+            StakeRecordUpdateKind::Deactivated
+        } else {
+            StakeRecordUpdateKind::CoolingDown
+        },
+    )
+}
@@ -1,18 +1,231 @@
 use core::{error::Error, fmt::Display};
 
+/// Code of the first custom error of the on-chain program
+pub const MARINADE_ERROR_CODE_OFFSET: u32 = 6000;
+
+/// Errors of the on-chain program, in the same order and with the same codes,
+/// followed by SDK-only errors that have no code
+#[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum MarinadeError {
-    DepositAmountIsTooLow,
-    ProgramIsPaused,
-    StakingIsCapped,
+    WrongReserveOwner = MARINADE_ERROR_CODE_OFFSET, // 6000
+    NonEmptyReserveData,
+    InvalidInitialReserveLamports,
+    ZeroValidatorChunkSize,
+    TooBigValidatorChunkSize,
+    ZeroCreditChunkSize,
+    TooBigCreditChunkSize,
+    TooLowCreditFee,
+    InvalidMintAuthority,
+    MintHasInitialSupply,
+    InvalidOwnerFeeState, // 6010
+    InvalidProgramId,
+    UnexpectedAccount,
     CalculationFailure,
+    StakeAccountWithLockup,
+    MinStakeIsTooLow,
+    LpMaxFeeIsTooHigh,
+    BasisPointsOverflow,
+    LpFeesAreWrongWayRound,
+    LiquidityTargetTooLow,
+    TicketNotDue, // 6020
+    TicketNotReady,
+    WrongBeneficiary,
+    StakeAccountNotUpdatedYet,
+    StakeNotDelegated,
+    StakeAccountIsEmergencyUnstaking,
+    InsufficientLiquidity,
+    NotUsed6027,
+    InvalidAdminAuthority,
+    InvalidValidatorManager,
+    InvalidStakeListDiscriminator, // 6030
+    InvalidValidatorListDiscriminator,
+    TreasuryCutIsTooHigh,
+    RewardsFeeIsTooHigh,
+    StakingIsCapped,
+    LiquidityIsCapped,
+    UpdateWindowIsTooLow,
+    MinWithdrawIsTooHigh,
+    WithdrawAmountIsTooLow,
+    DepositAmountIsTooLow,
+    NotEnoughUserFunds, // 6040
+    WrongTokenOwnerOrDelegate,
+    TooEarlyForStakeDelta,
+    RequiredDelegatedStake,
+    RequiredActiveStake,
+    RequiredDeactivatingStake,
+    DepositingNotActivatedStake,
     TooLowDelegationInDepositingStake,
+    WrongStakeBalance,
+    WrongValidatorAccountOrIndex,
+    WrongStakeAccountOrIndex, // 6050
+    UnstakingOnPositiveDelta,
+    StakingOnNegativeDelta,
+    MovingStakeIsCapped,
+    StakeMustBeUninitialized,
+    DestinationStakeMustBeDelegated,
+    DestinationStakeMustNotBeDeactivating,
+    DestinationStakeMustBeUpdated,
+    InvalidDestinationStakeDelegation,
+    SourceStakeMustBeDelegated,
+    SourceStakeMustNotBeDeactivating, // 6060
+    SourceStakeMustBeUpdated,
+    InvalidSourceStakeDelegation,
+    InvalidDelayedUnstakeTicket,
+    ReusingDelayedUnstakeTicket,
+    EmergencyUnstakingFromNonZeroScoredValidator,
+    WrongValidatorDuplicationFlag,
+    RedepositingMarinadeStake,
+    RemovingValidatorWithBalance,
+    RedelegateOverTarget,
+    SourceAndDestValidatorsAreTheSame, // 6070
+    UnregisteredMsolMinted,
+    UnregisteredLPMinted,
+    ListIndexOutOfBounds,
+    ListOverflow,
+    AlreadyPaused,
+    NotPaused,
+    ProgramIsPaused,
+    InvalidPauseAuthority,
+    SelectedStakeAccountHasNotEnoughFunds,
+    BasisPointCentsOverflow, // 6080
     WithdrawStakeAccountIsNotEnabled,
-    StakeAccountIsEmergencyUnstaking,
+    WithdrawStakeAccountFeeIsTooHigh,
+    DelayedUnstakeFeeIsTooHigh,
     WithdrawStakeLamportsIsTooLow,
-    SelectedStakeAccountHasNotEnoughFunds,
     StakeAccountRemainderTooLow,
-    WrongValidatorAccountOrIndex,
+    ShrinkingListWithDeletingContents,
+
+    /// SDK-only, the stake account's withdrawer is not the deposit's stake authority
+    WrongStakeAuthority,
+
+    /// SDK-only, the account data is not an initialized SPL token account
+    InvalidTokenAccount,
+
+    /// SDK-only, the token account's mint is not the expected one
+    WrongTokenMint,
+
+    /// SDK-only, the token account's owner is not the expected one
+    WrongTokenOwner,
+
+    /// SDK-only, a transaction message references more than 256 accounts
+    AccountIndexOverflow,
+
+    /// SDK-only, an account is past the 256th address of its address lookup table
+    AddressTableIndexOverflow,
+}
+
+const PROGRAM_ERRORS: [MarinadeError; 87] = [
+    MarinadeError::WrongReserveOwner,
+    MarinadeError::NonEmptyReserveData,
+    MarinadeError::InvalidInitialReserveLamports,
+    MarinadeError::ZeroValidatorChunkSize,
+    MarinadeError::TooBigValidatorChunkSize,
+    MarinadeError::ZeroCreditChunkSize,
+    MarinadeError::TooBigCreditChunkSize,
+    MarinadeError::TooLowCreditFee,
+    MarinadeError::InvalidMintAuthority,
+    MarinadeError::MintHasInitialSupply,
+    MarinadeError::InvalidOwnerFeeState,
+    MarinadeError::InvalidProgramId,
+    MarinadeError::UnexpectedAccount,
+    MarinadeError::CalculationFailure,
+    MarinadeError::StakeAccountWithLockup,
+    MarinadeError::MinStakeIsTooLow,
+    MarinadeError::LpMaxFeeIsTooHigh,
+    MarinadeError::BasisPointsOverflow,
+    MarinadeError::LpFeesAreWrongWayRound,
+    MarinadeError::LiquidityTargetTooLow,
+    MarinadeError::TicketNotDue,
+    MarinadeError::TicketNotReady,
+    MarinadeError::WrongBeneficiary,
+    MarinadeError::StakeAccountNotUpdatedYet,
+    MarinadeError::StakeNotDelegated,
+    MarinadeError::StakeAccountIsEmergencyUnstaking,
+    MarinadeError::InsufficientLiquidity,
+    MarinadeError::NotUsed6027,
+    MarinadeError::InvalidAdminAuthority,
+    MarinadeError::InvalidValidatorManager,
+    MarinadeError::InvalidStakeListDiscriminator,
+    MarinadeError::InvalidValidatorListDiscriminator,
+    MarinadeError::TreasuryCutIsTooHigh,
+    MarinadeError::RewardsFeeIsTooHigh,
+    MarinadeError::StakingIsCapped,
+    MarinadeError::LiquidityIsCapped,
+    MarinadeError::UpdateWindowIsTooLow,
+    MarinadeError::MinWithdrawIsTooHigh,
+    MarinadeError::WithdrawAmountIsTooLow,
+    MarinadeError::DepositAmountIsTooLow,
+    MarinadeError::NotEnoughUserFunds,
+    MarinadeError::WrongTokenOwnerOrDelegate,
+    MarinadeError::TooEarlyForStakeDelta,
+    MarinadeError::RequiredDelegatedStake,
+    MarinadeError::RequiredActiveStake,
+    MarinadeError::RequiredDeactivatingStake,
+    MarinadeError::DepositingNotActivatedStake,
+    MarinadeError::TooLowDelegationInDepositingStake,
+    MarinadeError::WrongStakeBalance,
+    MarinadeError::WrongValidatorAccountOrIndex,
+    MarinadeError::WrongStakeAccountOrIndex,
+    MarinadeError::UnstakingOnPositiveDelta,
+    MarinadeError::StakingOnNegativeDelta,
+    MarinadeError::MovingStakeIsCapped,
+    MarinadeError::StakeMustBeUninitialized,
+    MarinadeError::DestinationStakeMustBeDelegated,
+    MarinadeError::DestinationStakeMustNotBeDeactivating,
+    MarinadeError::DestinationStakeMustBeUpdated,
+    MarinadeError::InvalidDestinationStakeDelegation,
+    MarinadeError::SourceStakeMustBeDelegated,
+    MarinadeError::SourceStakeMustNotBeDeactivating,
+    MarinadeError::SourceStakeMustBeUpdated,
+    MarinadeError::InvalidSourceStakeDelegation,
+    MarinadeError::InvalidDelayedUnstakeTicket,
+    MarinadeError::ReusingDelayedUnstakeTicket,
+    MarinadeError::EmergencyUnstakingFromNonZeroScoredValidator,
+    MarinadeError::WrongValidatorDuplicationFlag,
+    MarinadeError::RedepositingMarinadeStake,
+    MarinadeError::RemovingValidatorWithBalance,
+    MarinadeError::RedelegateOverTarget,
+    MarinadeError::SourceAndDestValidatorsAreTheSame,
+    MarinadeError::UnregisteredMsolMinted,
+    MarinadeError::UnregisteredLPMinted,
+    MarinadeError::ListIndexOutOfBounds,
+    MarinadeError::ListOverflow,
+    MarinadeError::AlreadyPaused,
+    MarinadeError::NotPaused,
+    MarinadeError::ProgramIsPaused,
+    MarinadeError::InvalidPauseAuthority,
+    MarinadeError::SelectedStakeAccountHasNotEnoughFunds,
+    MarinadeError::BasisPointCentsOverflow,
+    MarinadeError::WithdrawStakeAccountIsNotEnabled,
+    MarinadeError::WithdrawStakeAccountFeeIsTooHigh,
+    MarinadeError::DelayedUnstakeFeeIsTooHigh,
+    MarinadeError::WithdrawStakeLamportsIsTooLow,
+    MarinadeError::StakeAccountRemainderTooLow,
+    MarinadeError::ShrinkingListWithDeletingContents,
+];
+
+impl MarinadeError {
+    /// The program error whose code is `code`,
+    /// e.g. from a failed transaction's `InstructionError::Custom(code)`
+    #[inline]
+    pub const fn from_custom_code(code: u32) -> Option<Self> {
+        match code.checked_sub(MARINADE_ERROR_CODE_OFFSET) {
+            Some(i) if (i as usize) < PROGRAM_ERRORS.len() => Some(PROGRAM_ERRORS[i as usize]),
+            _ => None,
+        }
+    }
+
+    /// Code of the program error, `None` for SDK-only errors
+    #[inline]
+    pub const fn to_custom_code(self) -> Option<u32> {
+        let code = self as u32;
+        if code.wrapping_sub(MARINADE_ERROR_CODE_OFFSET) < PROGRAM_ERRORS.len() as u32 {
+            Some(code)
+        } else {
+            None
+        }
+    }
 }
 
 impl Display for MarinadeError {
@@ -0,0 +1,71 @@
+use crate::{Instruction, COMPUTE_BUDGET_PROGRAM};
+
+/// `ComputeBudgetInstruction::SetComputeUnitLimit`
+pub const INSTRUCTION_DISCRIM_SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+
+/// `ComputeBudgetInstruction::SetComputeUnitPrice`
+pub const INSTRUCTION_DISCRIM_SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+pub type SetComputeUnitLimitIx = Instruction<0, SET_COMPUTE_UNIT_LIMIT_IX_DATA_LEN>;
+
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetComputeUnitLimitIxData([u8; SET_COMPUTE_UNIT_LIMIT_IX_DATA_LEN]);
+
+pub const SET_COMPUTE_UNIT_LIMIT_IX_DATA_LEN: usize = 5;
+
+impl SetComputeUnitLimitIxData {
+    #[inline]
+    pub fn new(units: u32) -> Self {
+        let mut buf = [0u8; SET_COMPUTE_UNIT_LIMIT_IX_DATA_LEN];
+
+        buf[0] = INSTRUCTION_DISCRIM_SET_COMPUTE_UNIT_LIMIT;
+        buf[1..5].copy_from_slice(&units.to_le_bytes());
+
+        Self(buf)
+    }
+
+    #[inline]
+    pub const fn to_buf(&self) -> [u8; SET_COMPUTE_UNIT_LIMIT_IX_DATA_LEN] {
+        self.0
+    }
+
+    /// The compute budget program takes no accounts
+    #[inline]
+    pub fn to_ix(&self) -> SetComputeUnitLimitIx {
+        Instruction::new(COMPUTE_BUDGET_PROGRAM, [], [], [], self.to_buf())
+    }
+}
+
+pub type SetComputeUnitPriceIx = Instruction<0, SET_COMPUTE_UNIT_PRICE_IX_DATA_LEN>;
+
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetComputeUnitPriceIxData([u8; SET_COMPUTE_UNIT_PRICE_IX_DATA_LEN]);
+
+pub const SET_COMPUTE_UNIT_PRICE_IX_DATA_LEN: usize = 9;
+
+impl SetComputeUnitPriceIxData {
+    #[inline]
+    pub fn new(micro_lamports: u64) -> Self {
+        let mut buf = [0u8; SET_COMPUTE_UNIT_PRICE_IX_DATA_LEN];
+
+        buf[0] = INSTRUCTION_DISCRIM_SET_COMPUTE_UNIT_PRICE;
+        buf[1..9].copy_from_slice(&micro_lamports.to_le_bytes());
+
+        Self(buf)
+    }
+
+    #[inline]
+    pub const fn to_buf(&self) -> [u8; SET_COMPUTE_UNIT_PRICE_IX_DATA_LEN] {
+        self.0
+    }
+
+    /// The compute budget program takes no accounts
+    #[inline]
+    pub fn to_ix(&self) -> SetComputeUnitPriceIx {
+        Instruction::new(COMPUTE_BUDGET_PROGRAM, [], [], [], self.to_buf())
+    }
+}
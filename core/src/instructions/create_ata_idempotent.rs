@@ -0,0 +1,127 @@
+use generic_array_struct::generic_array_struct;
+
+use crate::{
+    find_ata, instructions::validate::key_mismatches, Instruction, KeyMismatch,
+    ASSOCIATED_TOKEN_PROGRAM, SYSTEM_PROGRAM, TOKEN_PROGRAM,
+};
+
+/// `AssociatedTokenAccountInstruction::CreateIdempotent`
+pub const INSTRUCTION_DISCRIM_CREATE_ATA_IDEMPOTENT: u8 = 1;
+
+#[generic_array_struct(builder pub)]
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreateAtaIdempotentIxAccs<T> {
+    pub funding: T,
+    pub ata: T,
+    pub wallet: T,
+    pub mint: T,
+    pub system_program: T,
+    pub token_program: T,
+}
+
+pub type CreateAtaIdempotentIxKeysOwned = CreateAtaIdempotentIxAccs<[u8; 32]>;
+pub type CreateAtaIdempotentIxKeys<'a> = CreateAtaIdempotentIxAccs<&'a [u8; 32]>;
+pub type CreateAtaIdempotentIxAccsFlag = CreateAtaIdempotentIxAccs<bool>;
+
+pub const CREATE_ATA_IDEMPOTENT_IX_PREFIX_IS_WRITER: CreateAtaIdempotentIxAccsFlag =
+    CreateAtaIdempotentIxAccs([false; CREATE_ATA_IDEMPOTENT_IX_ACCS_LEN])
+        .const_with_funding(true)
+        .const_with_ata(true);
+
+pub const CREATE_ATA_IDEMPOTENT_IX_PREFIX_IS_SIGNER: CreateAtaIdempotentIxAccsFlag =
+    CreateAtaIdempotentIxAccs([false; CREATE_ATA_IDEMPOTENT_IX_ACCS_LEN]).const_with_funding(true);
+
+impl<T: Clone> CreateAtaIdempotentIxAccs<T> {
+    #[inline]
+    pub const fn new(arr: [T; CREATE_ATA_IDEMPOTENT_IX_ACCS_LEN]) -> Self {
+        Self(arr)
+    }
+}
+
+pub type CreateAtaIdempotentIx =
+    Instruction<CREATE_ATA_IDEMPOTENT_IX_ACCS_LEN, CREATE_ATA_IDEMPOTENT_IX_DATA_LEN>;
+
+impl CreateAtaIdempotentIxKeysOwned {
+    #[inline]
+    pub fn to_ix(&self, data: &CreateAtaIdempotentIxData) -> CreateAtaIdempotentIx {
+        Instruction::new(
+            ASSOCIATED_TOKEN_PROGRAM,
+            self.0,
+            CREATE_ATA_IDEMPOTENT_IX_PREFIX_IS_SIGNER.0,
+            CREATE_ATA_IDEMPOTENT_IX_PREFIX_IS_WRITER.0,
+            data.to_buf(),
+        )
+    }
+
+    #[inline]
+    pub fn as_borrowed(&self) -> CreateAtaIdempotentIxKeys<'_> {
+        CreateAtaIdempotentIxKeys::new(self.0.each_ref())
+    }
+
+    #[inline]
+    pub fn with_consts(self) -> Self {
+        self.as_borrowed().with_consts().into_owned()
+    }
+
+    /// Sets `wallet`, `mint` and `ata` to the associated token account of `wallet` for `mint`
+    #[inline]
+    pub fn with_ata_of(self, wallet: [u8; 32], mint: [u8; 32]) -> Self {
+        self.with_ata(find_ata(&wallet, &mint).0)
+            .with_wallet(wallet)
+            .with_mint(mint)
+    }
+
+    /// Checks `ata` against the associated token account of `wallet` for `mint`
+    /// and the program accounts, returning the mismatched ones.
+    ///
+    /// `funding`, `wallet` and `mint` are not checked
+    #[inline]
+    pub fn validate(&self) -> CreateAtaIdempotentIxAccs<Option<KeyMismatch>> {
+        let expected = Self::default()
+            .with_consts()
+            .with_ata(find_ata(self.wallet(), self.mint()).0);
+        CreateAtaIdempotentIxAccs(key_mismatches(&expected.0.map(Some), &self.0))
+    }
+}
+
+impl<'a> CreateAtaIdempotentIxKeys<'a> {
+    #[inline]
+    pub fn into_owned(self) -> CreateAtaIdempotentIxKeysOwned {
+        CreateAtaIdempotentIxKeysOwned::new(self.0.map(|pk| *pk))
+    }
+
+    #[inline]
+    pub const fn with_consts(self) -> Self {
+        // mSOL and the LP token are both under token program, not token-22
+        self.const_with_system_program(&SYSTEM_PROGRAM)
+            .const_with_token_program(&TOKEN_PROGRAM)
+    }
+}
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreateAtaIdempotentIxData([u8; CREATE_ATA_IDEMPOTENT_IX_DATA_LEN]);
+
+pub const CREATE_ATA_IDEMPOTENT_IX_DATA_LEN: usize = 1;
+
+impl CreateAtaIdempotentIxData {
+    #[inline]
+    pub const fn new() -> Self {
+        Self([INSTRUCTION_DISCRIM_CREATE_ATA_IDEMPOTENT])
+    }
+
+    #[inline]
+    pub const fn to_buf(&self) -> [u8; CREATE_ATA_IDEMPOTENT_IX_DATA_LEN] {
+        self.0
+    }
+}
+
+impl Default for CreateAtaIdempotentIxData {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,8 +1,11 @@
 use generic_array_struct::generic_array_struct;
 
 use crate::{
-    State, LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY, LIQ_POOL_SOL_LEG_PUBKEY, MSOL_MINT_AUTHORITY_PUBKEY,
-    RESERVE_PUBKEY, STATE_PUBKEY, SYSTEM_PROGRAM, TOKEN_PROGRAM,
+    find_ata,
+    instructions::validate::{key_mismatches, StatePdas},
+    Instruction, KeyMismatch, State, LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY, LIQ_POOL_SOL_LEG_PUBKEY,
+    MARINADE_STAKING_PROGRAM, MSOL_MINT_AUTHORITY_PUBKEY, RESERVE_PUBKEY, STATE_PUBKEY,
+    SYSTEM_PROGRAM, TOKEN_PROGRAM,
 };
 
 pub const INSTRUCTION_DISCRIM_DEPOSIT: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
@@ -49,7 +52,20 @@ impl<T: Clone> DepositIxAccs<T> {
     }
 }
 
+pub type DepositIx = Instruction<DEPOSIT_IX_ACCS_LEN, DEPOSIT_IX_DATA_LEN>;
+
 impl DepositIxKeysOwned {
+    #[inline]
+    pub fn to_ix(&self, data: &DepositIxData) -> DepositIx {
+        Instruction::new(
+            MARINADE_STAKING_PROGRAM,
+            self.0,
+            DEPOSIT_IX_PREFIX_IS_SIGNER.0,
+            DEPOSIT_IX_PREFIX_IS_WRITER.0,
+            data.to_buf(),
+        )
+    }
+
     #[inline]
     pub fn as_borrowed(&self) -> DepositIxKeys<'_> {
         DepositIxKeys::new(self.0.each_ref())
@@ -71,6 +87,39 @@ impl DepositIxKeysOwned {
     pub fn with_mainnet_consts(self) -> Self {
         self.as_borrowed().with_mainnet_consts().into_owned()
     }
+
+    /// Checks the keys that are determined by `pool` and its state account `state_pubkey`,
+    /// e.g. [`STATE_PUBKEY`] on mainnet, returning the mismatched ones.
+    ///
+    /// PDAs whose bump in `pool` is invalid for `state_pubkey` are always mismatched,
+    /// with no expected key.
+    /// User accounts are not checked (`transfer_from` and `mint_to`)
+    #[inline]
+    pub fn validate(
+        &self,
+        pool: &State,
+        state_pubkey: &[u8; 32],
+    ) -> DepositIxAccs<Option<KeyMismatch>> {
+        let pdas = StatePdas::new(pool, state_pubkey);
+        let keys = Self::default()
+            .with_consts()
+            .with_keys_from_stake_pool(pool)
+            .with_state(*state_pubkey);
+        let expected = DepositIxAccs(keys.0.map(Some))
+            .with_liq_pool_sol_leg_pda(pdas.liq_pool_sol_leg)
+            .with_liq_pool_msol_leg_authority(pdas.liq_pool_msol_leg_authority)
+            .with_reserve_pda(pdas.reserve)
+            .with_msol_mint_authority(pdas.msol_mint_authority);
+        DepositIxAccs(key_mismatches(&expected.0, &self.0))
+    }
+
+    /// Sets `mint_to` to `wallet`'s associated token account for `msol_mint`,
+    /// so `msol_mint` must already be set, e.g. by [`Self::with_keys_from_stake_pool`]
+    #[inline]
+    pub fn with_mint_to_wallet(self, wallet: [u8; 32]) -> Self {
+        let mint_to = find_ata(&wallet, self.msol_mint()).0;
+        self.with_mint_to(mint_to)
+    }
 }
 
 impl<'a> DepositIxKeys<'a> {
@@ -113,12 +162,14 @@ impl<'a> DepositIxKeys<'a> {
 #[repr(transparent)]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct DepositIxData([u8; 16]);
+pub struct DepositIxData([u8; DEPOSIT_IX_DATA_LEN]);
+
+pub const DEPOSIT_IX_DATA_LEN: usize = 16;
 
 impl DepositIxData {
     #[inline]
     pub fn new(deposit_lamports: u64) -> Self {
-        let mut buf = [0u8; 16];
+        let mut buf = [0u8; DEPOSIT_IX_DATA_LEN];
 
         buf[0..8].copy_from_slice(&INSTRUCTION_DISCRIM_DEPOSIT);
         buf[8..16].copy_from_slice(&deposit_lamports.to_le_bytes());
@@ -127,7 +178,7 @@ impl DepositIxData {
     }
 
     #[inline]
-    pub const fn to_buf(&self) -> [u8; 16] {
+    pub const fn to_buf(&self) -> [u8; DEPOSIT_IX_DATA_LEN] {
         self.0
     }
 }
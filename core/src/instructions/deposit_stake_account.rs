@@ -1,8 +1,10 @@
 use generic_array_struct::generic_array_struct;
 
 use crate::{
-    State, MSOL_MINT_AUTHORITY_PUBKEY, STAKE_PROGRAM, STATE_PUBKEY, SYSTEM_PROGRAM, SYSVAR_CLOCK,
-    SYSVAR_RENT, TOKEN_PROGRAM,
+    find_ata,
+    instructions::validate::{key_mismatches, StatePdas},
+    Instruction, KeyMismatch, State, MARINADE_STAKING_PROGRAM, MSOL_MINT_AUTHORITY_PUBKEY,
+    STAKE_PROGRAM, STATE_PUBKEY, SYSTEM_PROGRAM, SYSVAR_CLOCK, SYSVAR_RENT, TOKEN_PROGRAM,
 };
 
 pub const INSTRUCTION_DISCRIM_DEPOSIT_STAKE_ACCOUNT: [u8; 8] = [110, 130, 115, 41, 164, 102, 2, 59];
@@ -57,7 +59,21 @@ impl<T: Clone> DepositStakeAccountIxAccs<T> {
     }
 }
 
+pub type DepositStakeAccountIx =
+    Instruction<DEPOSIT_STAKE_ACCOUNT_IX_ACCS_LEN, DEPOSIT_STAKE_ACCOUNT_IX_DATA_LEN>;
+
 impl DepositStakeAccountIxKeysOwned {
+    #[inline]
+    pub fn to_ix(&self, data: &DepositStakeAccountIxData) -> DepositStakeAccountIx {
+        Instruction::new(
+            MARINADE_STAKING_PROGRAM,
+            self.0,
+            DEPOSIT_STAKE_ACCOUNT_IX_PREFIX_IS_SIGNER.0,
+            DEPOSIT_STAKE_ACCOUNT_IX_PREFIX_IS_WRITER.0,
+            data.to_buf(),
+        )
+    }
+
     #[inline]
     pub fn as_borrowed(&self) -> DepositStakeAccountIxKeys<'_> {
         DepositStakeAccountIxKeys::new(self.0.each_ref())
@@ -79,6 +95,36 @@ impl DepositStakeAccountIxKeysOwned {
     pub fn with_mainnet_consts(self) -> Self {
         self.as_borrowed().with_mainnet_consts().into_owned()
     }
+
+    /// Checks the keys that are determined by `pool` and its state account `state_pubkey`,
+    /// e.g. [`STATE_PUBKEY`] on mainnet, returning the mismatched ones.
+    ///
+    /// PDAs whose bump in `pool` is invalid for `state_pubkey` are always mismatched,
+    /// with no expected key.
+    /// User accounts are not checked (`stake_account`, `stake_authority`, `duplication_flag`, `rent_payer` and `mint_to`)
+    #[inline]
+    pub fn validate(
+        &self,
+        pool: &State,
+        state_pubkey: &[u8; 32],
+    ) -> DepositStakeAccountIxAccs<Option<KeyMismatch>> {
+        let pdas = StatePdas::new(pool, state_pubkey);
+        let keys = Self::default()
+            .with_consts()
+            .with_keys_from_stake_pool(pool)
+            .with_state(*state_pubkey);
+        let expected = DepositStakeAccountIxAccs(keys.0.map(Some))
+            .with_msol_mint_authority(pdas.msol_mint_authority);
+        DepositStakeAccountIxAccs(key_mismatches(&expected.0, &self.0))
+    }
+
+    /// Sets `mint_to` to `wallet`'s associated token account for `msol_mint`,
+    /// so `msol_mint` must already be set, e.g. by [`Self::with_keys_from_stake_pool`]
+    #[inline]
+    pub fn with_mint_to_wallet(self, wallet: [u8; 32]) -> Self {
+        let mint_to = find_ata(&wallet, self.msol_mint()).0;
+        self.with_mint_to(mint_to)
+    }
 }
 
 impl<'a> DepositStakeAccountIxKeys<'a> {
@@ -121,12 +167,14 @@ impl<'a> DepositStakeAccountIxKeys<'a> {
 #[repr(transparent)]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct DepositStakeAccountIxData([u8; 12]);
+pub struct DepositStakeAccountIxData([u8; DEPOSIT_STAKE_ACCOUNT_IX_DATA_LEN]);
+
+pub const DEPOSIT_STAKE_ACCOUNT_IX_DATA_LEN: usize = 12;
 
 impl DepositStakeAccountIxData {
     #[inline]
     pub fn new(validator_index: u32) -> Self {
-        let mut buf = [0u8; 12];
+        let mut buf = [0u8; DEPOSIT_STAKE_ACCOUNT_IX_DATA_LEN];
 
         buf[0..8].copy_from_slice(&INSTRUCTION_DISCRIM_DEPOSIT_STAKE_ACCOUNT);
         buf[8..12].copy_from_slice(&validator_index.to_le_bytes());
@@ -135,7 +183,7 @@ impl DepositStakeAccountIxData {
     }
 
     #[inline]
-    pub const fn to_buf(&self) -> [u8; 12] {
+    pub const fn to_buf(&self) -> [u8; DEPOSIT_STAKE_ACCOUNT_IX_DATA_LEN] {
         self.0
     }
 }
@@ -0,0 +1,64 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountMeta {
+    pub pubkey: [u8; 32],
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// An instruction with `N` accounts and `D` bytes of data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instruction<const N: usize, const D: usize> {
+    pub program_id: [u8; 32],
+    pub accounts: [AccountMeta; N],
+    pub data: [u8; D],
+}
+
+impl<const N: usize, const D: usize> Instruction<N, D> {
+    #[inline]
+    pub fn new(
+        program_id: [u8; 32],
+        keys: [[u8; 32]; N],
+        is_signer: [bool; N],
+        is_writable: [bool; N],
+        data: [u8; D],
+    ) -> Self {
+        Self {
+            program_id,
+            accounts: core::array::from_fn(|i| AccountMeta {
+                pubkey: keys[i],
+                is_signer: is_signer[i],
+                is_writable: is_writable[i],
+            }),
+            data,
+        }
+    }
+}
+
+/// An [`Instruction`] with its number of accounts and data length erased,
+/// for lists of different instructions
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynInstruction {
+    pub program_id: [u8; 32],
+    pub accounts: alloc::vec::Vec<AccountMeta>,
+    pub data: alloc::vec::Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize, const D: usize> From<Instruction<N, D>> for DynInstruction {
+    #[inline]
+    fn from(
+        Instruction {
+            program_id,
+            accounts,
+            data,
+        }: Instruction<N, D>,
+    ) -> Self {
+        Self {
+            program_id,
+            accounts: accounts.into(),
+            data: data.into(),
+        }
+    }
+}
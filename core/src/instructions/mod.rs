@@ -1,7 +1,19 @@
+mod compute_budget;
+mod create_ata_idempotent;
 mod deposit;
 mod deposit_stake_account;
+mod instruction;
+mod update_active;
+mod update_deactivated;
+mod validate;
 mod withdraw_stake_account;
 
+pub use compute_budget::*;
+pub use create_ata_idempotent::*;
 pub use deposit::*;
 pub use deposit_stake_account::*;
+pub use instruction::*;
+pub use update_active::*;
+pub use update_deactivated::*;
+pub use validate::*;
 pub use withdraw_stake_account::*;
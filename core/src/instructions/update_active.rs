@@ -0,0 +1,182 @@
+use generic_array_struct::generic_array_struct;
+
+use crate::{
+    instructions::validate::{key_mismatches, StatePdas},
+    Instruction, KeyMismatch, State, MARINADE_STAKING_PROGRAM, MSOL_MINT_AUTHORITY_PUBKEY,
+    RESERVE_PUBKEY, STAKE_LIST_PUBKEY, STAKE_PROGRAM, STAKE_WITHDRAW_AUTHORITY_PUBKEY,
+    STATE_PUBKEY, SYSVAR_CLOCK, SYSVAR_STAKE_HISTORY, TOKEN_PROGRAM, VALIDATOR_LIST_PUBKEY,
+};
+
+pub const INSTRUCTION_DISCRIM_UPDATE_ACTIVE: [u8; 8] = [4, 67, 81, 64, 136, 245, 93, 152];
+
+#[generic_array_struct(builder pub)]
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateActiveIxAccs<T> {
+    pub state: T,
+    pub stake_list: T,
+    pub stake_account: T,
+    pub stake_withdraw_authority: T,
+    pub reserve_pda: T,
+    pub msol_mint: T,
+    pub msol_mint_authority: T,
+    pub treasury_msol_account: T,
+    pub clock: T,
+    pub stake_history: T,
+    pub stake_program: T,
+    pub token_program: T,
+    pub validator_list: T,
+}
+
+pub type UpdateActiveIxKeysOwned = UpdateActiveIxAccs<[u8; 32]>;
+pub type UpdateActiveIxKeys<'a> = UpdateActiveIxAccs<&'a [u8; 32]>;
+pub type UpdateActiveIxAccsFlag = UpdateActiveIxAccs<bool>;
+
+pub const UPDATE_ACTIVE_IX_PREFIX_IS_WRITER: UpdateActiveIxAccsFlag =
+    UpdateActiveIxAccs([false; UPDATE_ACTIVE_IX_ACCS_LEN])
+        .const_with_state(true)
+        .const_with_stake_list(true)
+        .const_with_stake_account(true)
+        .const_with_reserve_pda(true)
+        .const_with_msol_mint(true)
+        .const_with_treasury_msol_account(true)
+        .const_with_validator_list(true);
+
+pub const UPDATE_ACTIVE_IX_PREFIX_IS_SIGNER: UpdateActiveIxAccsFlag =
+    UpdateActiveIxAccs([false; UPDATE_ACTIVE_IX_ACCS_LEN]);
+
+impl<T: Clone> UpdateActiveIxAccs<T> {
+    #[inline]
+    pub const fn new(arr: [T; UPDATE_ACTIVE_IX_ACCS_LEN]) -> Self {
+        Self(arr)
+    }
+}
+
+pub type UpdateActiveIx = Instruction<UPDATE_ACTIVE_IX_ACCS_LEN, UPDATE_ACTIVE_IX_DATA_LEN>;
+
+impl UpdateActiveIxKeysOwned {
+    #[inline]
+    pub fn to_ix(&self, data: &UpdateActiveIxData) -> UpdateActiveIx {
+        Instruction::new(
+            MARINADE_STAKING_PROGRAM,
+            self.0,
+            UPDATE_ACTIVE_IX_PREFIX_IS_SIGNER.0,
+            UPDATE_ACTIVE_IX_PREFIX_IS_WRITER.0,
+            data.to_buf(),
+        )
+    }
+
+    #[inline]
+    pub fn as_borrowed(&self) -> UpdateActiveIxKeys<'_> {
+        UpdateActiveIxKeys::new(self.0.each_ref())
+    }
+
+    #[inline]
+    pub fn with_keys_from_stake_pool(self, pool: &State) -> Self {
+        self.as_borrowed()
+            .with_keys_from_stake_pool(pool)
+            .into_owned()
+    }
+
+    #[inline]
+    pub fn with_consts(self) -> Self {
+        self.as_borrowed().with_consts().into_owned()
+    }
+
+    #[inline]
+    pub fn with_mainnet_consts(self) -> Self {
+        self.as_borrowed().with_mainnet_consts().into_owned()
+    }
+
+    /// Checks the keys that are determined by `pool` and its state account `state_pubkey`,
+    /// e.g. [`STATE_PUBKEY`] on mainnet, returning the mismatched ones.
+    ///
+    /// PDAs whose bump in `pool` is invalid for `state_pubkey` are always mismatched,
+    /// with no expected key.
+    /// User accounts are not checked (`stake_account`)
+    #[inline]
+    pub fn validate(
+        &self,
+        pool: &State,
+        state_pubkey: &[u8; 32],
+    ) -> UpdateActiveIxAccs<Option<KeyMismatch>> {
+        let pdas = StatePdas::new(pool, state_pubkey);
+        let keys = Self::default()
+            .with_consts()
+            .with_keys_from_stake_pool(pool)
+            .with_state(*state_pubkey);
+        let expected = UpdateActiveIxAccs(keys.0.map(Some))
+            .with_stake_withdraw_authority(pdas.stake_withdraw_authority)
+            .with_reserve_pda(pdas.reserve)
+            .with_msol_mint_authority(pdas.msol_mint_authority);
+        UpdateActiveIxAccs(key_mismatches(&expected.0, &self.0))
+    }
+}
+
+impl<'a> UpdateActiveIxKeys<'a> {
+    #[inline]
+    pub fn into_owned(self) -> UpdateActiveIxKeysOwned {
+        UpdateActiveIxKeysOwned::new(self.0.map(|pk| *pk))
+    }
+
+    #[inline]
+    pub const fn with_keys_from_stake_pool(
+        self,
+        State {
+            msol_mint,
+            validator_system,
+            stake_system,
+            treasury_msol_account,
+            ..
+        }: &'a State,
+    ) -> Self {
+        self.const_with_validator_list(&validator_system.validator_list.account)
+            .const_with_stake_list(&stake_system.stake_list.account)
+            .const_with_msol_mint(msol_mint)
+            .const_with_treasury_msol_account(treasury_msol_account)
+    }
+
+    #[inline]
+    pub const fn with_consts(self) -> Self {
+        self.const_with_clock(&SYSVAR_CLOCK)
+            .const_with_stake_history(&SYSVAR_STAKE_HISTORY)
+            .const_with_stake_program(&STAKE_PROGRAM)
+            .const_with_token_program(&TOKEN_PROGRAM)
+    }
+
+    #[inline]
+    pub fn with_mainnet_consts(self) -> Self {
+        self.const_with_state(&STATE_PUBKEY)
+            .const_with_validator_list(&VALIDATOR_LIST_PUBKEY)
+            .const_with_stake_list(&STAKE_LIST_PUBKEY)
+            .const_with_stake_withdraw_authority(&STAKE_WITHDRAW_AUTHORITY_PUBKEY)
+            .const_with_reserve_pda(&RESERVE_PUBKEY)
+            .const_with_msol_mint_authority(&MSOL_MINT_AUTHORITY_PUBKEY)
+    }
+}
+
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateActiveIxData([u8; UPDATE_ACTIVE_IX_DATA_LEN]);
+
+pub const UPDATE_ACTIVE_IX_DATA_LEN: usize = 16;
+
+impl UpdateActiveIxData {
+    #[inline]
+    pub fn new(stake_index: u32, validator_index: u32) -> Self {
+        let mut buf = [0u8; UPDATE_ACTIVE_IX_DATA_LEN];
+
+        buf[0..8].copy_from_slice(&INSTRUCTION_DISCRIM_UPDATE_ACTIVE);
+        buf[8..12].copy_from_slice(&stake_index.to_le_bytes());
+        buf[12..16].copy_from_slice(&validator_index.to_le_bytes());
+
+        Self(buf)
+    }
+
+    #[inline]
+    pub const fn to_buf(&self) -> [u8; UPDATE_ACTIVE_IX_DATA_LEN] {
+        self.0
+    }
+}
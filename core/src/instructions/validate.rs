@@ -0,0 +1,68 @@
+use crate::{
+    liq_pool_msol_leg_authority, liq_pool_sol_leg_pda, msol_mint_authority, reserve_pda,
+    stake_deposit_authority, stake_withdraw_authority, State,
+};
+
+/// An account key that differs from the one expected by [`State`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyMismatch {
+    /// `None` if the account is a PDA whose bump stored in [`State`]
+    /// is invalid for the state account, so that no key can match
+    pub expected: Option<[u8; 32]>,
+    pub actual: [u8; 32],
+}
+
+/// Compares `actual` against `expected`, skipping zeroed `expected` keys,
+/// which are the ones that are not determined by the state.
+///
+/// `None` `expected` keys are PDAs that cannot be derived and always mismatch
+#[inline]
+pub(crate) fn key_mismatches<const N: usize>(
+    expected: &[Option<[u8; 32]>; N],
+    actual: &[[u8; 32]; N],
+) -> [Option<KeyMismatch>; N] {
+    core::array::from_fn(|i| {
+        let (expected, actual) = (expected[i], actual[i]);
+        (expected != Some([0; 32]) && expected != Some(actual))
+            .then_some(KeyMismatch { expected, actual })
+    })
+}
+
+/// PDAs of the state account `state_pubkey` derived from the bumps stored in `pool`,
+/// `None` if a bump is invalid for `state_pubkey`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StatePdas {
+    pub reserve: Option<[u8; 32]>,
+    pub msol_mint_authority: Option<[u8; 32]>,
+    pub liq_pool_sol_leg: Option<[u8; 32]>,
+    pub liq_pool_msol_leg_authority: Option<[u8; 32]>,
+    pub stake_withdraw_authority: Option<[u8; 32]>,
+    pub stake_deposit_authority: Option<[u8; 32]>,
+}
+
+impl StatePdas {
+    #[inline]
+    pub fn new(pool: &State, state_pubkey: &[u8; 32]) -> Self {
+        Self {
+            reserve: reserve_pda(state_pubkey, pool.reserve_bump_seed),
+            msol_mint_authority: msol_mint_authority(
+                state_pubkey,
+                pool.msol_mint_authority_bump_seed,
+            ),
+            liq_pool_sol_leg: liq_pool_sol_leg_pda(state_pubkey, pool.liq_pool.sol_leg_bump_seed),
+            liq_pool_msol_leg_authority: liq_pool_msol_leg_authority(
+                state_pubkey,
+                pool.liq_pool.msol_leg_authority_bump_seed,
+            ),
+            stake_withdraw_authority: stake_withdraw_authority(
+                state_pubkey,
+                pool.stake_system.stake_withdraw_bump_seed,
+            ),
+            stake_deposit_authority: stake_deposit_authority(
+                state_pubkey,
+                pool.stake_system.stake_deposit_bump_seed,
+            ),
+        }
+    }
+}
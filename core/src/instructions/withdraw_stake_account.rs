@@ -1,9 +1,11 @@
 use generic_array_struct::generic_array_struct;
 
 use crate::{
-    State, STAKE_DEPOSIT_AUTHORITY_PUBKEY, STAKE_LIST_PUBKEY, STAKE_PROGRAM,
-    STAKE_WITHDRAW_AUTHORITY_PUBKEY, STATE_PUBKEY, SYSTEM_PROGRAM, SYSVAR_CLOCK, TOKEN_PROGRAM,
-    VALIDATOR_LIST_PUBKEY,
+    find_ata,
+    instructions::validate::{key_mismatches, StatePdas},
+    Instruction, KeyMismatch, State, MARINADE_STAKING_PROGRAM, STAKE_DEPOSIT_AUTHORITY_PUBKEY,
+    STAKE_LIST_PUBKEY, STAKE_PROGRAM, STAKE_WITHDRAW_AUTHORITY_PUBKEY, STATE_PUBKEY,
+    SYSTEM_PROGRAM, SYSVAR_CLOCK, TOKEN_PROGRAM, VALIDATOR_LIST_PUBKEY,
 };
 
 pub const INSTRUCTION_DISCRIM_WITHDRAW_STAKE_ACCOUNT: [u8; 8] =
@@ -62,7 +64,21 @@ impl<T: Clone> WithdrawStakeAccountIxAccs<T> {
     }
 }
 
+pub type WithdrawStakeAccountIx =
+    Instruction<WITHDRAW_STAKE_ACCOUNT_IX_ACCS_LEN, WITHDRAW_STAKE_ACCOUNT_IX_DATA_LEN>;
+
 impl WithdrawStakeAccountIxKeysOwned {
+    #[inline]
+    pub fn to_ix(&self, data: &WithdrawStakeAccountIxData) -> WithdrawStakeAccountIx {
+        Instruction::new(
+            MARINADE_STAKING_PROGRAM,
+            self.0,
+            WITHDRAW_STAKE_ACCOUNT_IX_PREFIX_IS_SIGNER.0,
+            WITHDRAW_STAKE_ACCOUNT_IX_PREFIX_IS_WRITER.0,
+            data.to_buf(),
+        )
+    }
+
     #[inline]
     pub fn as_borrowed(&self) -> WithdrawStakeAccountIxKeys<'_> {
         WithdrawStakeAccountIxKeys::new(self.0.each_ref())
@@ -84,6 +100,39 @@ impl WithdrawStakeAccountIxKeysOwned {
     pub fn with_mainnet_consts(self) -> Self {
         self.as_borrowed().with_mainnet_consts().into_owned()
     }
+
+    /// Checks the keys that are determined by `pool` and its state account `state_pubkey`,
+    /// e.g. [`STATE_PUBKEY`] on mainnet, returning the mismatched ones.
+    ///
+    /// PDAs whose bump in `pool` is invalid for `state_pubkey` are always mismatched,
+    /// with no expected key.
+    /// User accounts are not checked (`burn_msol_from`, `burn_msol_authority`, `stake_account`, `split_stake_account` and `split_stake_rent_payer`)
+    #[inline]
+    pub fn validate(
+        &self,
+        pool: &State,
+        state_pubkey: &[u8; 32],
+    ) -> WithdrawStakeAccountIxAccs<Option<KeyMismatch>> {
+        let pdas = StatePdas::new(pool, state_pubkey);
+        let keys = Self::default()
+            .with_consts()
+            .with_keys_from_stake_pool(pool)
+            .with_state(*state_pubkey);
+        let expected = WithdrawStakeAccountIxAccs(keys.0.map(Some))
+            .with_stake_withdraw_authority(pdas.stake_withdraw_authority)
+            .with_stake_deposit_authority(pdas.stake_deposit_authority);
+        WithdrawStakeAccountIxAccs(key_mismatches(&expected.0, &self.0))
+    }
+
+    /// Sets `burn_msol_authority` to `wallet` and `burn_msol_from` to its associated
+    /// token account for `msol_mint`, so `msol_mint` must already be set,
+    /// e.g. by [`Self::with_keys_from_stake_pool`]
+    #[inline]
+    pub fn with_burn_msol_from_wallet(self, wallet: [u8; 32]) -> Self {
+        let burn_msol_from = find_ata(&wallet, self.msol_mint()).0;
+        self.with_burn_msol_from(burn_msol_from)
+            .with_burn_msol_authority(wallet)
+    }
 }
 
 impl<'a> WithdrawStakeAccountIxKeys<'a> {
@@ -131,9 +180,12 @@ impl<'a> WithdrawStakeAccountIxKeys<'a> {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WithdrawStakeAccountIxData(
-    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))] [u8; 56],
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes"))]
+    [u8; WITHDRAW_STAKE_ACCOUNT_IX_DATA_LEN],
 );
 
+pub const WITHDRAW_STAKE_ACCOUNT_IX_DATA_LEN: usize = 56;
+
 impl WithdrawStakeAccountIxData {
     #[inline]
     pub fn new(
@@ -142,7 +194,7 @@ impl WithdrawStakeAccountIxData {
         msol_amount: u64,
         beneficiary: [u8; 32],
     ) -> Self {
-        let mut buf = [0u8; 56];
+        let mut buf = [0u8; WITHDRAW_STAKE_ACCOUNT_IX_DATA_LEN];
 
         buf[0..8].copy_from_slice(&INSTRUCTION_DISCRIM_WITHDRAW_STAKE_ACCOUNT);
         buf[8..12].copy_from_slice(&stake_index.to_le_bytes());
@@ -154,7 +206,7 @@ impl WithdrawStakeAccountIxData {
     }
 
     #[inline]
-    pub const fn to_buf(&self) -> [u8; 56] {
+    pub const fn to_buf(&self) -> [u8; WITHDRAW_STAKE_ACCOUNT_IX_DATA_LEN] {
         self.0
     }
 }
@@ -162,6 +214,6 @@ impl WithdrawStakeAccountIxData {
 impl Default for WithdrawStakeAccountIxData {
     #[inline]
     fn default() -> Self {
-        Self([0u8; 56])
+        Self([0u8; WITHDRAW_STAKE_ACCOUNT_IX_DATA_LEN])
     }
 }
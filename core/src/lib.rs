@@ -1,20 +1,43 @@
 #![cfg_attr(all(not(test), not(feature = "std")), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod activation;
 mod consts;
+mod crank;
 mod error;
 mod instructions;
 mod internal_utils;
 mod keys;
+#[cfg(feature = "alloc")]
+mod message;
 mod pda;
+mod preflight;
+mod quoter;
 mod state;
+#[cfg(feature = "std")]
+mod transaction;
 mod typedefs;
+mod user;
 mod utils;
+mod withdraw;
 
+pub use activation::*;
 pub use consts::*;
+pub use crank::*;
 pub use error::*;
 pub use instructions::*;
 pub use keys::*;
+#[cfg(feature = "alloc")]
+pub use message::*;
 pub use pda::*;
+pub use preflight::*;
+pub use quoter::*;
 pub use state::*;
+#[cfg(feature = "std")]
+pub use transaction::*;
 pub use typedefs::*;
+pub use user::*;
 pub use utils::*;
+pub use withdraw::*;
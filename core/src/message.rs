@@ -0,0 +1,421 @@
+use core::ops::Range;
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{DynInstruction, MarinadeError, PACKET_DATA_SIZE, SYSTEM_PROGRAM};
+
+/// First byte of a versioned message, OR-ed with its version
+pub const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+/// `SystemInstruction::AdvanceNonceAccount`, which makes the first instruction's
+/// first account the nonce account
+const ADVANCE_NONCE_PREFIX: [u8; 4] = [4, 0, 0, 0];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageHeader {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed_accounts: u8,
+    pub num_readonly_unsigned_accounts: u8,
+}
+
+/// An instruction with its program and accounts replaced by their indices in the message's keys
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct CompiledInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// The addresses of an on-chain address lookup table account at `key`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AddressLookupTable<'a> {
+    pub key: [u8; 32],
+    pub addresses: &'a [[u8; 32]],
+}
+
+/// Accounts of a v0 message loaded from the address lookup table at `account_key`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct MessageAddressTableLookup {
+    pub account_key: [u8; 32],
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+/// A legacy or v0 transaction message, compiled the same way as `solana-sdk` does.
+///
+/// Static account keys are ordered writable signers, starting with the fee payer,
+/// then readonly signers, writable non-signers and readonly non-signers,
+/// each group sorted by pubkey.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Message {
+    pub header: MessageHeader,
+    pub account_keys: Vec<[u8; 32]>,
+    pub recent_blockhash: [u8; 32],
+    pub instructions: Vec<CompiledInstruction>,
+
+    /// `None` for legacy messages
+    pub address_table_lookups: Option<Vec<MessageAddressTableLookup>>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct KeyMeta {
+    is_signer: bool,
+    is_writable: bool,
+    is_invoked: bool,
+    is_nonce: bool,
+}
+
+impl KeyMeta {
+    /// Only these accounts can be loaded from address lookup tables
+    #[inline]
+    const fn is_lookupable(&self) -> bool {
+        !self.is_signer && !self.is_invoked && !self.is_nonce
+    }
+}
+
+fn compile_keys(ixs: &[DynInstruction], fee_payer: &[u8; 32]) -> BTreeMap<[u8; 32], KeyMeta> {
+    let mut keys = BTreeMap::<[u8; 32], KeyMeta>::new();
+    for ix in ixs {
+        keys.entry(ix.program_id).or_default().is_invoked = true;
+        for meta in ix.accounts.iter() {
+            let key = keys.entry(meta.pubkey).or_default();
+            key.is_signer |= meta.is_signer;
+            key.is_writable |= meta.is_writable;
+        }
+    }
+    if let Some(nonce) = ixs.first().and_then(|ix| {
+        (ix.program_id == SYSTEM_PROGRAM && ix.data.starts_with(&ADVANCE_NONCE_PREFIX))
+            .then(|| ix.accounts.first())
+            .flatten()
+    }) {
+        keys.entry(nonce.pubkey).or_default().is_nonce = true;
+    }
+    // fee payer is always the first writable signer
+    keys.remove(fee_payer);
+    keys
+}
+
+/// Accounts loaded from address lookup tables, in message index order
+#[derive(Debug, Default)]
+struct LoadedAddresses {
+    writable: Vec<[u8; 32]>,
+    readonly: Vec<[u8; 32]>,
+}
+
+/// Moves the keys of `keys` that `alt` can load to `loaded`
+fn extract_table_lookup(
+    keys: &mut BTreeMap<[u8; 32], KeyMeta>,
+    alt: &AddressLookupTable,
+    loaded: &mut LoadedAddresses,
+) -> Result<Option<MessageAddressTableLookup>, MarinadeError> {
+    let mut drain = |is_writable: bool| -> Result<(Vec<u8>, Vec<[u8; 32]>), MarinadeError> {
+        let mut indexes = Vec::new();
+        let mut drained = Vec::new();
+        for (key, _) in keys
+            .iter()
+            .filter(|(_, meta)| meta.is_lookupable() && meta.is_writable == is_writable)
+        {
+            if let Some(i) = alt.addresses.iter().position(|a| a == key) {
+                indexes
+                    .push(u8::try_from(i).map_err(|_| MarinadeError::AddressTableIndexOverflow)?);
+                drained.push(*key);
+            }
+        }
+        drained.iter().for_each(|key| {
+            keys.remove(key);
+        });
+        Ok((indexes, drained))
+    };
+    let (writable_indexes, writable) = drain(true)?;
+    let (readonly_indexes, readonly) = drain(false)?;
+
+    if writable_indexes.is_empty() && readonly_indexes.is_empty() {
+        return Ok(None);
+    }
+    loaded.writable.extend(writable);
+    loaded.readonly.extend(readonly);
+    Ok(Some(MessageAddressTableLookup {
+        account_key: alt.key,
+        writable_indexes,
+        readonly_indexes,
+    }))
+}
+
+fn static_keys(
+    keys: &BTreeMap<[u8; 32], KeyMeta>,
+    fee_payer: &[u8; 32],
+) -> Result<(MessageHeader, Vec<[u8; 32]>), MarinadeError> {
+    let group = |is_signer: bool, is_writable: bool| {
+        keys.iter()
+            .filter(move |(_, meta)| meta.is_signer == is_signer && meta.is_writable == is_writable)
+            .map(|(key, _)| *key)
+    };
+    let try_into_u8 = |n: usize| u8::try_from(n).map_err(|_| MarinadeError::AccountIndexOverflow);
+
+    let mut account_keys = Vec::with_capacity(keys.len() + 1);
+    account_keys.push(*fee_payer);
+    account_keys.extend(group(true, true));
+    account_keys.extend(group(true, false));
+    let num_required_signatures = try_into_u8(account_keys.len())?;
+    let num_readonly_signed_accounts = try_into_u8(group(true, false).count())?;
+    account_keys.extend(group(false, true));
+    account_keys.extend(group(false, false));
+    let num_readonly_unsigned_accounts = try_into_u8(group(false, false).count())?;
+
+    Ok((
+        MessageHeader {
+            num_required_signatures,
+            num_readonly_signed_accounts,
+            num_readonly_unsigned_accounts,
+        },
+        account_keys,
+    ))
+}
+
+/// `keys` are all keys of the message in index order,
+/// the static keys followed by those loaded from lookup tables
+fn compile_instructions(
+    ixs: &[DynInstruction],
+    keys: &[[u8; 32]],
+) -> Result<Vec<CompiledInstruction>, MarinadeError> {
+    let index_of = |pubkey: &[u8; 32]| {
+        keys.iter()
+            .position(|k| k == pubkey)
+            .and_then(|i| u8::try_from(i).ok())
+            .ok_or(MarinadeError::AccountIndexOverflow)
+    };
+    ixs.iter()
+        .map(|ix| {
+            Ok(CompiledInstruction {
+                program_id_index: index_of(&ix.program_id)?,
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|meta| index_of(&meta.pubkey))
+                    .collect::<Result<_, _>>()?,
+                data: ix.data.clone(),
+            })
+        })
+        .collect()
+}
+
+impl Message {
+    /// Returns [`MarinadeError::AccountIndexOverflow`] if `ixs` reference more than 256 accounts
+    pub fn compile_legacy(
+        ixs: &[DynInstruction],
+        fee_payer: &[u8; 32],
+        recent_blockhash: &[u8; 32],
+    ) -> Result<Self, MarinadeError> {
+        let keys = compile_keys(ixs, fee_payer);
+        let (header, account_keys) = static_keys(&keys, fee_payer)?;
+        let instructions = compile_instructions(ixs, &account_keys)?;
+        Ok(Self {
+            header,
+            account_keys,
+            recent_blockhash: *recent_blockhash,
+            instructions,
+            address_table_lookups: None,
+        })
+    }
+
+    /// Accounts of `ixs` that are not signers, invoked programs or a nonce account
+    /// are loaded from the first of `alts` that contains them.
+    /// Lookup tables that none are loaded from are left out.
+    ///
+    /// Returns [`MarinadeError::AccountIndexOverflow`] if `ixs` reference more than 256 accounts
+    /// and [`MarinadeError::AddressTableIndexOverflow`] if a loaded account is past
+    /// the 256th address of its lookup table
+    pub fn compile_v0(
+        ixs: &[DynInstruction],
+        fee_payer: &[u8; 32],
+        recent_blockhash: &[u8; 32],
+        alts: &[AddressLookupTable],
+    ) -> Result<Self, MarinadeError> {
+        let mut keys = compile_keys(ixs, fee_payer);
+        let mut lookups = Vec::new();
+        let mut loaded = LoadedAddresses::default();
+        for alt in alts {
+            if let Some(lookup) = extract_table_lookup(&mut keys, alt, &mut loaded)? {
+                lookups.push(lookup);
+            }
+        }
+
+        let (header, account_keys) = static_keys(&keys, fee_payer)?;
+        let all_keys: Vec<_> = account_keys
+            .iter()
+            .chain(loaded.writable.iter())
+            .chain(loaded.readonly.iter())
+            .copied()
+            .collect();
+        let instructions = compile_instructions(ixs, &all_keys)?;
+        Ok(Self {
+            header,
+            account_keys,
+            recent_blockhash: *recent_blockhash,
+            instructions,
+            address_table_lookups: Some(lookups),
+        })
+    }
+
+    /// Groups consecutive `ixs` into as few v0 messages as fit in transactions
+    /// of at most [`PACKET_DATA_SIZE`] bytes, see [`Self::compile_v0`].
+    ///
+    /// An instruction that does not fit on its own gets its own message.
+    pub fn compile_v0_batches(
+        ixs: &[DynInstruction],
+        fee_payer: &[u8; 32],
+        recent_blockhash: &[u8; 32],
+        alts: &[AddressLookupTable],
+    ) -> Result<Vec<Self>, MarinadeError> {
+        compile_batches(ixs.len(), |range| {
+            Self::compile_v0(&ixs[range], fee_payer, recent_blockhash, alts)
+        })
+        .into_iter()
+        .map(|(_, message)| message)
+        .collect()
+    }
+
+    /// The bytes to sign
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_len());
+        if self.address_table_lookups.is_some() {
+            buf.push(MESSAGE_VERSION_PREFIX);
+        }
+        let MessageHeader {
+            num_required_signatures,
+            num_readonly_signed_accounts,
+            num_readonly_unsigned_accounts,
+        } = self.header;
+        buf.extend([
+            num_required_signatures,
+            num_readonly_signed_accounts,
+            num_readonly_unsigned_accounts,
+        ]);
+        write_compact_u16(&mut buf, self.account_keys.len());
+        self.account_keys
+            .iter()
+            .for_each(|key| buf.extend_from_slice(key));
+        buf.extend_from_slice(&self.recent_blockhash);
+        write_compact_u16(&mut buf, self.instructions.len());
+        for ix in self.instructions.iter() {
+            buf.push(ix.program_id_index);
+            write_compact_u16(&mut buf, ix.accounts.len());
+            buf.extend_from_slice(&ix.accounts);
+            write_compact_u16(&mut buf, ix.data.len());
+            buf.extend_from_slice(&ix.data);
+        }
+        if let Some(lookups) = &self.address_table_lookups {
+            write_compact_u16(&mut buf, lookups.len());
+            for lookup in lookups {
+                buf.extend_from_slice(&lookup.account_key);
+                write_compact_u16(&mut buf, lookup.writable_indexes.len());
+                buf.extend_from_slice(&lookup.writable_indexes);
+                write_compact_u16(&mut buf, lookup.readonly_indexes.len());
+                buf.extend_from_slice(&lookup.readonly_indexes);
+            }
+        }
+        buf
+    }
+
+    /// Length of [`Self::serialize`]
+    pub fn serialized_len(&self) -> usize {
+        let ixs_len: usize = self
+            .instructions
+            .iter()
+            .map(|ix| {
+                1 + compact_u16_len(ix.accounts.len())
+                    + ix.accounts.len()
+                    + compact_u16_len(ix.data.len())
+                    + ix.data.len()
+            })
+            .sum();
+        let lookups_len = self.address_table_lookups.as_ref().map_or(0, |lookups| {
+            1 + compact_u16_len(lookups.len())
+                + lookups
+                    .iter()
+                    .map(|l| {
+                        32 + compact_u16_len(l.writable_indexes.len())
+                            + l.writable_indexes.len()
+                            + compact_u16_len(l.readonly_indexes.len())
+                            + l.readonly_indexes.len()
+                    })
+                    .sum::<usize>()
+        });
+        3 + compact_u16_len(self.account_keys.len())
+            + 32 * self.account_keys.len()
+            + 32
+            + compact_u16_len(self.instructions.len())
+            + ixs_len
+            + lookups_len
+    }
+
+    /// Length of the signed transaction of this message,
+    /// to compare against [`crate::PACKET_DATA_SIZE`]
+    #[inline]
+    pub fn transaction_len(&self) -> usize {
+        let n_signatures = usize::from(self.header.num_required_signatures);
+        compact_u16_len(n_signatures) + 64 * n_signatures + self.serialized_len()
+    }
+}
+
+/// Ranges of consecutive `ixs` that fit in legacy transactions of at most [`PACKET_DATA_SIZE`] bytes
+/// paid for by `fee_payer`, see [`Message::compile_legacy`].
+///
+/// An instruction that does not fit on its own gets its own range.
+pub(crate) fn legacy_batches(ixs: &[DynInstruction], fee_payer: &[u8; 32]) -> Vec<Range<usize>> {
+    // the blockhash does not affect the size
+    compile_batches(ixs.len(), |range| {
+        Message::compile_legacy(&ixs[range], fee_payer, &[0; 32])
+    })
+    .into_iter()
+    .map(|(range, _)| range)
+    .collect()
+}
+
+/// Splits `0..len` into consecutive ranges, each extended for as long as
+/// its message compiled by `compile` fits in a transaction of at most [`PACKET_DATA_SIZE`] bytes.
+///
+/// Each range has at least one instruction and is returned with its compiled message
+fn compile_batches(
+    len: usize,
+    mut compile: impl FnMut(Range<usize>) -> Result<Message, MarinadeError>,
+) -> Vec<(Range<usize>, Result<Message, MarinadeError>)> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let mut message = compile(start..start + 1);
+        let mut end = start + 1;
+        while end < len {
+            match compile(start..end + 1) {
+                Ok(next) if next.transaction_len() <= PACKET_DATA_SIZE => message = Ok(next),
+                _ => break,
+            }
+            end += 1;
+        }
+        res.push((start..end, message));
+        start = end;
+    }
+    res
+}
+
+pub(crate) const fn compact_u16_len(n: usize) -> usize {
+    match n {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// `n` must fit in a `u16`, which every length in a transaction under
+/// [`crate::PACKET_DATA_SIZE`] does
+fn write_compact_u16(buf: &mut Vec<u8>, mut n: usize) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
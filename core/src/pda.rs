@@ -1,3 +1,12 @@
+use const_crypto::{
+    ed25519::{crypto_unsafe_is_on_curve, derive_program_address},
+    sha2::Sha256,
+};
+
+use crate::{
+    ASSOCIATED_TOKEN_PROGRAM, LP_MINT_ADDR, MARINADE_STAKING_PROGRAM, MSOL_MINT_ADDR, TOKEN_PROGRAM,
+};
+
 pub const RESERVE_SEED: [u8; 7] = *b"reserve";
 pub const SOL_LEG_SEED: [u8; 7] = *b"liq_sol";
 pub const MSOL_LEG_AUTHORITY_SEED: [u8; 20] = *b"liq_st_sol_authority";
@@ -43,3 +52,139 @@ pub const fn stake_withdraw_authority_seeds(state: &[u8; 32]) -> (&[u8; 32], &[u
 pub const fn stake_deposit_authority_seeds(state: &[u8; 32]) -> (&[u8; 32], &[u8; 7]) {
     (state, &STAKE_DEPOSIT_AUTHORITY_SEED)
 }
+
+/// Seeds of the associated token account of `wallet` for `mint`.
+///
+/// Both mSOL and the LP token are under the token program, not token-22
+#[inline]
+pub const fn ata_seeds<'a>(
+    wallet: &'a [u8; 32],
+    mint: &'a [u8; 32],
+) -> (&'a [u8; 32], &'static [u8; 32], &'a [u8; 32]) {
+    (wallet, &TOKEN_PROGRAM, mint)
+}
+
+const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
+
+/// Runtime counterpart of [`const_crypto::ed25519::derive_program_address`]
+/// for a known `bump`, such as those stored in [`crate::State`].
+///
+/// Returns `None` if the resulting address is on the curve
+#[inline]
+pub const fn create_program_address(
+    seeds: &[&[u8]],
+    bump: u8,
+    program: &[u8; 32],
+) -> Option<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    let mut i = 0;
+    while i < seeds.len() {
+        hasher = hasher.update(seeds[i]);
+        i += 1;
+    }
+    let res = hasher
+        .update(&[bump])
+        .update(program)
+        .update(PDA_MARKER)
+        .finalize();
+    if crypto_unsafe_is_on_curve(&res) {
+        None
+    } else {
+        Some(res)
+    }
+}
+
+/// Reserve PDA of the state account `state` with [`crate::State::reserve_bump_seed`]
+#[inline]
+pub const fn reserve_pda(state: &[u8; 32], bump: u8) -> Option<[u8; 32]> {
+    let (state, seed) = reserve_seeds(state);
+    create_program_address(&[state, seed], bump, &MARINADE_STAKING_PROGRAM)
+}
+
+/// Liquidity pool's SOL leg PDA of the state account `state`
+/// with [`crate::LiqPool::sol_leg_bump_seed`]
+#[inline]
+pub const fn liq_pool_sol_leg_pda(state: &[u8; 32], bump: u8) -> Option<[u8; 32]> {
+    let (state, seed) = liq_pool_sol_leg_seeds(state);
+    create_program_address(&[state, seed], bump, &MARINADE_STAKING_PROGRAM)
+}
+
+/// mSOL mint authority of the state account `state`
+/// with [`crate::State::msol_mint_authority_bump_seed`]
+#[inline]
+pub const fn msol_mint_authority(state: &[u8; 32], bump: u8) -> Option<[u8; 32]> {
+    let (state, seed) = msol_mint_authority_seeds(state);
+    create_program_address(&[state, seed], bump, &MARINADE_STAKING_PROGRAM)
+}
+
+/// Withdraw authority of the stake accounts of the state account `state`
+/// with [`crate::StakeSystem::stake_withdraw_bump_seed`]
+#[inline]
+pub const fn stake_withdraw_authority(state: &[u8; 32], bump: u8) -> Option<[u8; 32]> {
+    let (state, seed) = stake_withdraw_authority_seeds(state);
+    create_program_address(&[state, seed], bump, &MARINADE_STAKING_PROGRAM)
+}
+
+/// Stake authority of the stake accounts of the state account `state`
+/// with [`crate::StakeSystem::stake_deposit_bump_seed`]
+#[inline]
+pub const fn stake_deposit_authority(state: &[u8; 32], bump: u8) -> Option<[u8; 32]> {
+    let (state, seed) = stake_deposit_authority_seeds(state);
+    create_program_address(&[state, seed], bump, &MARINADE_STAKING_PROGRAM)
+}
+
+/// Owner of the liquidity pool's mSOL leg token account
+/// for the state account `state` with [`crate::LiqPool::msol_leg_authority_bump_seed`]
+#[inline]
+pub const fn liq_pool_msol_leg_authority(state: &[u8; 32], bump: u8) -> Option<[u8; 32]> {
+    let (state, seed) = liq_pool_msol_leg_authority_seeds(state);
+    create_program_address(&[state, seed], bump, &MARINADE_STAKING_PROGRAM)
+}
+
+/// Duplication flag of `validator_account` for the state account `state`
+/// with [`crate::ValidatorRecord::duplication_flag_bump_seed`]
+#[inline]
+pub const fn duplication_flag(
+    state: &[u8; 32],
+    validator_account: &[u8; 32],
+    bump: u8,
+) -> Option<[u8; 32]> {
+    let (state, seed, validator_account) = duplication_flag_seeds(state, validator_account);
+    create_program_address(
+        &[state, seed, validator_account],
+        bump,
+        &MARINADE_STAKING_PROGRAM,
+    )
+}
+
+/// Associated token account of `wallet` for `mint` and its bump
+#[inline]
+pub const fn find_ata(wallet: &[u8; 32], mint: &[u8; 32]) -> ([u8; 32], u8) {
+    let (wallet, token_program, mint) = ata_seeds(wallet, mint);
+    derive_program_address(&[wallet, token_program, mint], &ASSOCIATED_TOKEN_PROGRAM)
+}
+
+/// [`find_ata`] for a known `bump`, skipping the bump search.
+///
+/// Returns `None` if the resulting address is on the curve
+#[inline]
+pub const fn create_ata_address(wallet: &[u8; 32], mint: &[u8; 32], bump: u8) -> Option<[u8; 32]> {
+    let (wallet, token_program, mint) = ata_seeds(wallet, mint);
+    create_program_address(
+        &[wallet, token_program, mint],
+        bump,
+        &ASSOCIATED_TOKEN_PROGRAM,
+    )
+}
+
+/// mSOL associated token account of `wallet` and its bump
+#[inline]
+pub const fn find_msol_ata(wallet: &[u8; 32]) -> ([u8; 32], u8) {
+    find_ata(wallet, &MSOL_MINT_ADDR)
+}
+
+/// Liquidity pool LP token associated token account of `wallet` and its bump
+#[inline]
+pub const fn find_lp_ata(wallet: &[u8; 32]) -> ([u8; 32], u8) {
+    find_ata(wallet, &LP_MINT_ADDR)
+}
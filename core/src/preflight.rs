@@ -0,0 +1,54 @@
+use crate::{activation_status, Clock, MarinadeError, StakeHistory, StakeStateV2, ValidatorList};
+
+/// Checks that the stake account can be deposited with `stake_authority` as signer,
+/// returning the `validator_index` of its voter in `validator_list`
+/// to pass to [`crate::DepositStakeAccountIxData::new`].
+///
+/// The program reassigns both authorities of the stake account,
+/// which requires the withdrawer's signature.
+///
+/// See [`crate::activation_status`] for `new_rate_activation_epoch`.
+///
+/// This does not check deposit amounts, use [`crate::State::quote_deposit_stake`] for that.
+pub fn preflight_deposit_stake(
+    stake_state: &StakeStateV2,
+    stake_authority: &[u8; 32],
+    validator_list: &ValidatorList,
+    clock: &Clock,
+    stake_history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> Result<u32, MarinadeError> {
+    let StakeStateV2::Stake(meta, stake, _) = stake_state else {
+        return Err(MarinadeError::RequiredDelegatedStake);
+    };
+    let delegation = &stake.delegation;
+
+    if delegation.is_deactivated() {
+        return Err(MarinadeError::RequiredActiveStake);
+    }
+
+    if meta.lockup.is_in_force(clock.unix_timestamp, clock.epoch) {
+        return Err(MarinadeError::StakeAccountWithLockup);
+    }
+
+    let status = activation_status(
+        delegation,
+        clock.epoch,
+        stake_history,
+        new_rate_activation_epoch,
+    );
+    if !status.is_fully_active() || status.effective != delegation.stake {
+        return Err(MarinadeError::DepositingNotActivatedStake);
+    }
+
+    if meta.authorized.withdrawer != *stake_authority {
+        return Err(MarinadeError::WrongStakeAuthority);
+    }
+
+    validator_list
+        .0
+        .iter()
+        .position(|v| v.validator_account() == &delegation.voter_pubkey)
+        .and_then(|i| u32::try_from(i).ok())
+        .ok_or(MarinadeError::WrongValidatorAccountOrIndex)
+}
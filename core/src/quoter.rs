@@ -0,0 +1,203 @@
+use crate::{
+    DepositSolQuote, DepositSolQuoteArgs, DepositStakeQuote, DepositStakeQuoteArgs, MarinadeError,
+    QuoteContext, StakeAccountLamports, State, WithdrawStakeQuote, WithdrawStakeQuoteArgs,
+};
+
+/// Input and output amounts of a quote, in lamports or LST atomics
+/// depending on the direction of the operation
+pub trait LstQuote {
+    fn in_amount(&self) -> u64;
+
+    /// After fees
+    fn out_amount(&self) -> u64;
+}
+
+/// Quoting interface common to liquid staking token pools,
+/// so that routing code can be generic over the pool program.
+///
+/// Pool-specific inputs such as account balances not stored in the pool's state
+/// go in the associated `*Args` types
+pub trait LstQuoter {
+    type Error;
+
+    type DepositSolArgs;
+    type DepositSolQuote: LstQuote;
+
+    /// The stake account to deposit, e.g. its staked and unstaked lamports
+    type DepositStakeAccount;
+    type DepositStakeArgs;
+    type DepositStakeQuote: LstQuote;
+
+    type WithdrawStakeArgs;
+    type WithdrawStakeQuote: LstQuote;
+
+    /// Value of `lst_amount` in lamports at the pool's exchange rate, before any fees.
+    ///
+    /// `None` on arithmetic failure
+    fn lst_to_sol(&self, lst_amount: u64) -> Option<u64>;
+
+    /// Amount of LST that `lamports` are worth at the pool's exchange rate, before any fees.
+    ///
+    /// `None` on arithmetic failure
+    fn sol_to_lst(&self, lamports: u64) -> Option<u64>;
+
+    fn quote_deposit_sol(
+        &self,
+        lamports: u64,
+        args: Self::DepositSolArgs,
+    ) -> Result<Self::DepositSolQuote, Self::Error>;
+
+    fn quote_deposit_stake(
+        &self,
+        stake_account: Self::DepositStakeAccount,
+        args: Self::DepositStakeArgs,
+    ) -> Result<Self::DepositStakeQuote, Self::Error>;
+
+    fn quote_withdraw_stake(
+        &self,
+        lst_amount: u64,
+        args: Self::WithdrawStakeArgs,
+    ) -> Result<Self::WithdrawStakeQuote, Self::Error>;
+}
+
+impl LstQuote for DepositSolQuote {
+    #[inline]
+    fn in_amount(&self) -> u64 {
+        self.in_amount
+    }
+
+    #[inline]
+    fn out_amount(&self) -> u64 {
+        self.out_amount
+    }
+}
+
+impl LstQuote for DepositStakeQuote {
+    #[inline]
+    fn in_amount(&self) -> u64 {
+        self.total_lamports_in
+    }
+
+    #[inline]
+    fn out_amount(&self) -> u64 {
+        self.tokens_out
+    }
+}
+
+impl LstQuote for WithdrawStakeQuote {
+    #[inline]
+    fn in_amount(&self) -> u64 {
+        self.tokens_in
+    }
+
+    #[inline]
+    fn out_amount(&self) -> u64 {
+        self.lamports_staked
+    }
+}
+
+/// Forwards to the inherent [`State`] methods of the same name
+impl LstQuoter for State {
+    type Error = MarinadeError;
+
+    type DepositSolArgs = DepositSolQuoteArgs;
+    type DepositSolQuote = DepositSolQuote;
+
+    type DepositStakeAccount = StakeAccountLamports;
+    type DepositStakeArgs = DepositStakeQuoteArgs;
+    type DepositStakeQuote = DepositStakeQuote;
+
+    type WithdrawStakeArgs = WithdrawStakeQuoteArgs;
+    type WithdrawStakeQuote = WithdrawStakeQuote;
+
+    #[inline]
+    fn lst_to_sol(&self, lst_amount: u64) -> Option<u64> {
+        self.pool_tokens_to_lamports(lst_amount)
+    }
+
+    #[inline]
+    fn sol_to_lst(&self, lamports: u64) -> Option<u64> {
+        self.lamports_to_pool_tokens(lamports)
+    }
+
+    #[inline]
+    fn quote_deposit_sol(
+        &self,
+        lamports: u64,
+        args: Self::DepositSolArgs,
+    ) -> Result<Self::DepositSolQuote, Self::Error> {
+        State::quote_deposit_sol(self, lamports, args)
+    }
+
+    #[inline]
+    fn quote_deposit_stake(
+        &self,
+        stake_account: Self::DepositStakeAccount,
+        args: Self::DepositStakeArgs,
+    ) -> Result<Self::DepositStakeQuote, Self::Error> {
+        State::quote_deposit_stake(self, stake_account, args)
+    }
+
+    #[inline]
+    fn quote_withdraw_stake(
+        &self,
+        lst_amount: u64,
+        args: Self::WithdrawStakeArgs,
+    ) -> Result<Self::WithdrawStakeQuote, Self::Error> {
+        State::quote_withdraw_stake(self, lst_amount, args)
+    }
+}
+
+/// Forwards to the inherent [`QuoteContext`] methods of the same name.
+///
+/// The mSOL leg balance is part of the context, so deposits take no args
+impl LstQuoter for QuoteContext {
+    type Error = MarinadeError;
+
+    type DepositSolArgs = ();
+    type DepositSolQuote = DepositSolQuote;
+
+    type DepositStakeAccount = StakeAccountLamports;
+    type DepositStakeArgs = ();
+    type DepositStakeQuote = DepositStakeQuote;
+
+    type WithdrawStakeArgs = WithdrawStakeQuoteArgs;
+    type WithdrawStakeQuote = WithdrawStakeQuote;
+
+    #[inline]
+    fn lst_to_sol(&self, lst_amount: u64) -> Option<u64> {
+        self.pool_tokens_to_lamports(lst_amount)
+    }
+
+    #[inline]
+    fn sol_to_lst(&self, lamports: u64) -> Option<u64> {
+        self.lamports_to_pool_tokens(lamports)
+    }
+
+    #[inline]
+    fn quote_deposit_sol(
+        &self,
+        lamports: u64,
+        (): Self::DepositSolArgs,
+    ) -> Result<Self::DepositSolQuote, Self::Error> {
+        QuoteContext::quote_deposit_sol(self, lamports)
+    }
+
+    #[inline]
+    fn quote_deposit_stake(
+        &self,
+        stake_account: Self::DepositStakeAccount,
+        (): Self::DepositStakeArgs,
+    ) -> Result<Self::DepositStakeQuote, Self::Error> {
+        QuoteContext::quote_deposit_stake(self, stake_account)
+    }
+
+    #[inline]
+    fn quote_withdraw_stake(
+        &self,
+        lst_amount: u64,
+        args: Self::WithdrawStakeArgs,
+    ) -> Result<Self::WithdrawStakeQuote, Self::Error> {
+        QuoteContext::quote_withdraw_stake(self, lst_amount, args)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    DepositSolQuote, DepositSolQuoteArgs, DepositStakeQuote, LiquidUnstakeQuote,
+    LiquidUnstakeQuoteArgs, StakeRecord, State, ValidatorRecord, WithdrawStakeQuote,
+};
+
+/// State transitions.
+///
+/// These apply a quote to produce the accounts the program would write after executing it,
+/// so that subsequent operations in the same transaction can be quoted against the updated state.
+///
+/// The quote must have been obtained from `self`.
+/// Returns `None` on arithmetic overflow, where the program would fail with
+/// [`crate::MarinadeError::CalculationFailure`].
+impl State {
+    /// The liquidity pool's legs are token and system accounts not tracked in [`State`],
+    /// so the returned [`DepositSolQuoteArgs`] carries the updated mSOL leg balance
+    #[inline]
+    pub fn apply_deposit_sol(
+        &self,
+        quote: &DepositSolQuote,
+        args: DepositSolQuoteArgs,
+    ) -> Option<(Self, DepositSolQuoteArgs)> {
+        let mut res = self.clone();
+        res.msol_supply = res.msol_supply.checked_add(quote.msol_minted)?;
+        res.available_reserve_balance = res
+            .available_reserve_balance
+            .checked_add(quote.lamports_to_reserve)?;
+        let args = DepositSolQuoteArgs {
+            msol_leg_balance: args
+                .msol_leg_balance
+                .checked_sub(quote.msol_from_liq_pool)?,
+        };
+        Some((res, args))
+    }
+
+    /// Returns the updated state, the [`StakeRecord`] appended to the end of the [`crate::StakeList`]
+    /// for `stake_account` and the updated `validator_record` the stake account is delegated to
+    #[inline]
+    pub fn apply_deposit_stake(
+        &self,
+        quote: &DepositStakeQuote,
+        stake_account: [u8; 32],
+        validator_record: &ValidatorRecord,
+        current_epoch: u64,
+    ) -> Option<(Self, StakeRecord, ValidatorRecord)> {
+        let staked = quote.stake_account_lamports_in.staked;
+
+        let mut res = self.clone();
+        res.msol_supply = res.msol_supply.checked_add(quote.tokens_out)?;
+        res.validator_system.total_active_balance = res
+            .validator_system
+            .total_active_balance
+            .checked_add(staked)?;
+        res.stake_system.stake_list.count = res.stake_system.stake_list.count.checked_add(1)?;
+
+        let stake_record = StakeRecord::new(stake_account, staked, current_epoch, false);
+        let validator_record = validator_record
+            .with_active_balance(validator_record.active_balance().checked_add(staked)?);
+
+        Some((res, stake_record, validator_record))
+    }
+
+    /// Returns the updated state, `stake_record` and `validator_record` the stake account is delegated to.
+    ///
+    /// The split-off stake account is owned by the user and not added to the [`crate::StakeList`].
+    /// `msol_supply` decreases by `tokens_in - fee_amount`
+    /// since the fee is transferred from the user's mSOL account to the treasury's
+    /// and only the rest is burnt.
+    #[inline]
+    pub fn apply_withdraw_stake(
+        &self,
+        quote: &WithdrawStakeQuote,
+        stake_record: &StakeRecord,
+        validator_record: &ValidatorRecord,
+    ) -> Option<(Self, StakeRecord, ValidatorRecord)> {
+        let burnt = quote.tokens_in.checked_sub(quote.fee_amount)?;
+
+        let mut res = self.clone();
+        res.msol_supply = res.msol_supply.checked_sub(burnt)?;
+        res.validator_system.total_active_balance = res
+            .validator_system
+            .total_active_balance
+            .checked_sub(quote.lamports_staked)?;
+
+        let stake_record = stake_record.with_last_update_delegated_lamports(
+            stake_record
+                .last_update_delegated_lamports()
+                .checked_sub(quote.lamports_staked)?,
+        );
+        let validator_record = validator_record.with_active_balance(
+            validator_record
+                .active_balance()
+                .checked_sub(quote.lamports_staked)?,
+        );
+
+        Some((res, stake_record, validator_record))
+    }
+
+    /// Liquid unstaking neither burns mSOL nor changes the liquidity pool's fields,
+    /// so the returned state is unchanged. The returned args carry the updated
+    /// balances of the liquidity pool's legs: lamports are transferred from the SOL leg
+    /// to the user and `tokens_in - treasury_msol_cut` mSOL from the user to the mSOL leg
+    #[inline]
+    pub fn apply_liquid_unstake(
+        &self,
+        quote: &LiquidUnstakeQuote,
+        unstake_args: LiquidUnstakeQuoteArgs,
+        deposit_args: DepositSolQuoteArgs,
+    ) -> Option<(Self, LiquidUnstakeQuoteArgs, DepositSolQuoteArgs)> {
+        let unstake_args = LiquidUnstakeQuoteArgs {
+            sol_leg_lamports: unstake_args
+                .sol_leg_lamports
+                .checked_sub(quote.lamports_out)?,
+        };
+        let deposit_args = DepositSolQuoteArgs {
+            msol_leg_balance: deposit_args
+                .msol_leg_balance
+                .checked_add(quote.tokens_in.checked_sub(quote.treasury_msol_cut)?)?,
+        };
+        Some((self.clone(), unstake_args, deposit_args))
+    }
+}
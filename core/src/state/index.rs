@@ -0,0 +1,149 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{StakeList, ValidatorList};
+
+/// Two-way lookup between account pubkeys and their indices in a list account
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListIndex {
+    indices: BTreeMap<[u8; 32], u32>,
+    accounts: Vec<[u8; 32]>,
+}
+
+impl ListIndex {
+    #[inline]
+    pub fn from_accounts<'a>(accounts: impl IntoIterator<Item = &'a [u8; 32]>) -> Self {
+        let mut res = Self::default();
+        res.reload(accounts);
+        res
+    }
+
+    #[inline]
+    pub fn index_of(&self, account: &[u8; 32]) -> Option<u32> {
+        self.indices.get(account).copied()
+    }
+
+    #[inline]
+    pub fn account_at(&self, index: u32) -> Option<&[u8; 32]> {
+        self.accounts.get(usize::try_from(index).ok()?)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Updates the index to the reloaded list's `accounts`,
+    /// only touching entries that changed
+    pub fn reload<'a>(&mut self, accounts: impl IntoIterator<Item = &'a [u8; 32]>) {
+        let mut len = 0;
+        for (i, account) in accounts.into_iter().enumerate() {
+            len = i + 1;
+            match self.accounts.get(i) {
+                Some(old) if old == account => continue,
+                Some(old) => {
+                    // only remove the old entry if it was not already moved to another index
+                    if self.indices.get(old) == Some(&(i as u32)) {
+                        self.indices.remove(old);
+                    }
+                    self.accounts[i] = *account;
+                }
+                None => self.accounts.push(*account),
+            }
+            self.indices.insert(*account, i as u32);
+        }
+        for removed in self.accounts.drain(len..) {
+            if self
+                .indices
+                .get(&removed)
+                .is_some_and(|&i| i as usize >= len)
+            {
+                self.indices.remove(&removed);
+            }
+        }
+    }
+
+    /// Appends `account` to the end of the list, same as the program does
+    /// when adding a validator or depositing a stake account.
+    ///
+    /// Returns its index
+    #[inline]
+    pub fn push(&mut self, account: [u8; 32]) -> u32 {
+        let index = self.accounts.len() as u32;
+        self.accounts.push(account);
+        self.indices.insert(account, index);
+        index
+    }
+
+    /// Removes the account at `index` by moving the last account into its place,
+    /// same as the program does when removing a validator or stake account.
+    ///
+    /// Returns the removed account
+    #[inline]
+    pub fn swap_remove(&mut self, index: u32) -> Option<[u8; 32]> {
+        let i = usize::try_from(index).ok()?;
+        if i >= self.accounts.len() {
+            return None;
+        }
+        let removed = self.accounts.swap_remove(i);
+        self.indices.remove(&removed);
+        if let Some(moved) = self.accounts.get(i) {
+            self.indices.insert(*moved, index);
+        }
+        Some(removed)
+    }
+}
+
+/// [`ListIndex`]es over the [`ValidatorList`] and [`StakeList`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarinadeListIndex {
+    /// vote account <-> validator index
+    pub validators: ListIndex,
+
+    /// stake account <-> stake index
+    pub stakes: ListIndex,
+}
+
+impl MarinadeListIndex {
+    #[inline]
+    pub fn new(validator_list: &ValidatorList, stake_list: &StakeList) -> Self {
+        Self {
+            validators: ListIndex::from_accounts(
+                validator_list.0.iter().map(|v| v.validator_account()),
+            ),
+            stakes: ListIndex::from_accounts(stake_list.0.iter().map(|s| s.stake_account())),
+        }
+    }
+
+    #[inline]
+    pub fn reload(&mut self, validator_list: &ValidatorList, stake_list: &StakeList) {
+        self.validators
+            .reload(validator_list.0.iter().map(|v| v.validator_account()));
+        self.stakes
+            .reload(stake_list.0.iter().map(|s| s.stake_account()));
+    }
+
+    #[inline]
+    pub fn validator_index(&self, vote_account: &[u8; 32]) -> Option<u32> {
+        self.validators.index_of(vote_account)
+    }
+
+    #[inline]
+    pub fn vote_account(&self, validator_index: u32) -> Option<&[u8; 32]> {
+        self.validators.account_at(validator_index)
+    }
+
+    #[inline]
+    pub fn stake_index(&self, stake_account: &[u8; 32]) -> Option<u32> {
+        self.stakes.index_of(stake_account)
+    }
+
+    #[inline]
+    pub fn stake_account(&self, stake_index: u32) -> Option<&[u8; 32]> {
+        self.stakes.account_at(stake_index)
+    }
+}
@@ -0,0 +1,203 @@
+use sanctum_u64_ratio::{Ceil, Ratio};
+
+use crate::{
+    DepositSolQuote, DepositSolQuoteArgs, DepositStakeQuote, DepositStakeQuoteArgs,
+    LiquidUnstakeQuote, LiquidUnstakeQuoteArgs, MarinadeError, QuoteContext, StakeAccountLamports,
+    State, WithdrawStakeQuote, WithdrawStakeQuoteArgs,
+};
+
+/// A quote for a single input amount of a quote ladder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteLadderPoint<Q> {
+    pub in_amount: u64,
+
+    /// The error is the check that rejects `in_amount`,
+    /// e.g. [`MarinadeError::StakingIsCapped`] where deposits stop being usable
+    pub quote: Result<Q, MarinadeError>,
+}
+
+/// Iterator over the geometric series `start, start * factor, start * factor^2, ...`
+/// of up to `count` input amounts, for sampling the output curve of a quote ladder.
+///
+/// Each amount is rounded up and at least 1 more than the previous one,
+/// so the series is strictly increasing even for small `start` or `factor`.
+/// The series ends early once the next amount would exceed `u64::MAX`.
+#[derive(Debug, Clone, Copy)]
+pub struct GeometricAmounts {
+    next: Option<u64>,
+    factor: Ratio<u64, u64>,
+    remaining: usize,
+}
+
+impl GeometricAmounts {
+    #[inline]
+    pub const fn new(start: u64, factor: Ratio<u64, u64>, count: usize) -> Self {
+        Self {
+            next: Some(start),
+            factor,
+            remaining: count,
+        }
+    }
+}
+
+impl Iterator for GeometricAmounts {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let curr = self.next?;
+        self.remaining -= 1;
+        self.next = match (Ceil(self.factor).apply(curr), curr.checked_add(1)) {
+            (Some(scaled), Some(min)) => Some(scaled.max(min)),
+            _ => None,
+        };
+        Some(curr)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.next {
+            None => (0, Some(0)),
+            Some(_) => (usize::from(self.remaining > 0), Some(self.remaining)),
+        }
+    }
+}
+
+/// Quote ladders.
+///
+/// These quote each of `in_amounts` independently against `self`,
+/// as if each were the only operation executed,
+/// for routers to sample the output curve and split orders
+impl QuoteContext {
+    #[inline]
+    pub fn deposit_sol_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+    ) -> impl Iterator<Item = QuoteLadderPoint<DepositSolQuote>> + 'a {
+        ladder(in_amounts, move |in_amount| {
+            self.quote_deposit_sol(in_amount)
+        })
+    }
+
+    /// `in_amounts` are the staked lamports of the stake account to deposit
+    /// and `unstaked` its unstaked lamports at every point, which are passed through to
+    /// [`DepositStakeQuote::stake_account_lamports_in`] without affecting the mSOL output
+    #[inline]
+    pub fn deposit_stake_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+        unstaked: u64,
+    ) -> impl Iterator<Item = QuoteLadderPoint<DepositStakeQuote>> + 'a {
+        ladder(in_amounts, move |staked| {
+            self.quote_deposit_stake(StakeAccountLamports { staked, unstaked })
+        })
+    }
+
+    /// `in_amounts` are the mSOL to burn for a stake account split from `args.stake_record`
+    #[inline]
+    pub fn withdraw_stake_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+        args: WithdrawStakeQuoteArgs,
+    ) -> impl Iterator<Item = QuoteLadderPoint<WithdrawStakeQuote>> + 'a {
+        ladder(in_amounts, move |in_amount| {
+            self.quote_withdraw_stake(in_amount, args)
+        })
+    }
+
+    #[inline]
+    pub fn liquid_unstake_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+        args: LiquidUnstakeQuoteArgs,
+    ) -> impl Iterator<Item = QuoteLadderPoint<LiquidUnstakeQuote>> + 'a {
+        ladder(in_amounts, move |in_amount| {
+            self.quote_liquid_unstake(in_amount, args)
+        })
+    }
+}
+
+/// Quote ladders, see the [`QuoteContext`] functions of the same name.
+///
+/// These build a [`QuoteContext`] once for all of `in_amounts`
+/// and only fall back to quoting each amount against `self`
+/// if it cannot be built, for the points' errors
+impl State {
+    #[inline]
+    pub fn deposit_sol_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+        args: DepositSolQuoteArgs,
+    ) -> impl Iterator<Item = QuoteLadderPoint<DepositSolQuote>> + 'a {
+        let ctx = self.quote_context(args.msol_leg_balance);
+        ladder(in_amounts, move |in_amount| match ctx {
+            Some(ctx) => ctx.quote_deposit_sol(in_amount),
+            None => self.quote_deposit_sol(in_amount, args),
+        })
+    }
+
+    /// `in_amounts` are the staked lamports of the stake account to deposit
+    /// and `unstaked` its unstaked lamports at every point, which are passed through to
+    /// [`DepositStakeQuote::stake_account_lamports_in`] without affecting the mSOL output
+    #[inline]
+    pub fn deposit_stake_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+        unstaked: u64,
+        args: DepositStakeQuoteArgs,
+    ) -> impl Iterator<Item = QuoteLadderPoint<DepositStakeQuote>> + 'a {
+        let ctx = self.quote_context(args.msol_leg_balance);
+        ladder(in_amounts, move |staked| {
+            let stake_account_lamports = StakeAccountLamports { staked, unstaked };
+            match ctx {
+                Some(ctx) => ctx.quote_deposit_stake(stake_account_lamports),
+                None => self.quote_deposit_stake(stake_account_lamports, args),
+            }
+        })
+    }
+
+    /// `in_amounts` are the mSOL to burn for a stake account split from `args.stake_record`
+    #[inline]
+    pub fn withdraw_stake_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+        args: WithdrawStakeQuoteArgs,
+    ) -> impl Iterator<Item = QuoteLadderPoint<WithdrawStakeQuote>> + 'a {
+        // the mSOL leg only affects deposits
+        let ctx = self.quote_context(0);
+        ladder(in_amounts, move |in_amount| match ctx {
+            Some(ctx) => ctx.quote_withdraw_stake(in_amount, args),
+            None => self.quote_withdraw_stake(in_amount, args),
+        })
+    }
+
+    #[inline]
+    pub fn liquid_unstake_ladder<'a>(
+        &'a self,
+        in_amounts: impl IntoIterator<Item = u64> + 'a,
+        args: LiquidUnstakeQuoteArgs,
+    ) -> impl Iterator<Item = QuoteLadderPoint<LiquidUnstakeQuote>> + 'a {
+        // the mSOL leg only affects deposits
+        let ctx = self.quote_context(0);
+        ladder(in_amounts, move |in_amount| match ctx {
+            Some(ctx) => ctx.quote_liquid_unstake(in_amount, args),
+            None => self.quote_liquid_unstake(in_amount, args),
+        })
+    }
+}
+
+#[inline]
+fn ladder<Q>(
+    in_amounts: impl IntoIterator<Item = u64>,
+    mut quote: impl FnMut(u64) -> Result<Q, MarinadeError>,
+) -> impl Iterator<Item = QuoteLadderPoint<Q>> {
+    in_amounts
+        .into_iter()
+        .map(move |in_amount| QuoteLadderPoint {
+            in_amount,
+            quote: quote(in_amount),
+        })
+}
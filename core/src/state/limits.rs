@@ -0,0 +1,203 @@
+use crate::{
+    DepositSolQuoteArgs, DepositStakeQuoteArgs, MarinadeError, StakeAccountLamports, StakeRecord,
+    State, WithdrawStakeQuoteArgs,
+};
+
+/// Min and max amounts that pass the checks of the corresponding quote functions.
+///
+/// Each of these returns the error of the corresponding quote function
+/// if no amount can pass its checks.
+impl State {
+    /// Largest amount of lamports that [`Self::quote_deposit_sol`] accepts,
+    /// taking into account the portion of the deposit that is swapped
+    /// from the liquidity pool's mSOL leg and not counted against `staking_sol_cap`
+    #[inline]
+    pub fn max_deposit_sol(&self, msol_leg_balance: u64) -> Result<u64, MarinadeError> {
+        let max = self.max_deposit_under_staking_cap(msol_leg_balance)?;
+        self.quote_deposit_sol(max, DepositSolQuoteArgs { msol_leg_balance })
+            .map(|_| max)
+    }
+
+    /// Smallest amount of lamports that [`Self::quote_deposit_sol`] accepts
+    #[inline]
+    pub fn min_deposit_sol(&self, msol_leg_balance: u64) -> Result<u64, MarinadeError> {
+        let args = DepositSolQuoteArgs { msol_leg_balance };
+        let min = self.min_deposit_under_staking_cap(self.min_deposit, msol_leg_balance)?;
+        self.quote_deposit_sol(min, args).map(|_| min)
+    }
+
+    /// Largest amount of staked lamports that [`Self::quote_deposit_stake`] accepts
+    #[inline]
+    pub fn max_deposit_stake(&self, msol_leg_balance: u64) -> Result<u64, MarinadeError> {
+        let max = self.max_deposit_under_staking_cap(msol_leg_balance)?;
+        self.quote_deposit_stake(
+            StakeAccountLamports {
+                staked: max,
+                unstaked: 0,
+            },
+            DepositStakeQuoteArgs { msol_leg_balance },
+        )
+        .map(|_| max)
+    }
+
+    /// Smallest amount of staked lamports that [`Self::quote_deposit_stake`] accepts
+    #[inline]
+    pub fn min_deposit_stake(&self, msol_leg_balance: u64) -> Result<u64, MarinadeError> {
+        let min =
+            self.min_deposit_under_staking_cap(self.stake_system.min_stake, msol_leg_balance)?;
+        self.quote_deposit_stake(
+            StakeAccountLamports {
+                staked: min,
+                unstaked: 0,
+            },
+            DepositStakeQuoteArgs { msol_leg_balance },
+        )
+        .map(|_| min)
+    }
+
+    /// Largest amount of mSOL that [`Self::quote_withdraw_stake`] accepts for `stake_record`,
+    /// such that the stake account remainder after the split is at least `min_stake`
+    #[inline]
+    pub fn max_withdraw_stake(&self, stake_record: &StakeRecord) -> Result<u64, MarinadeError> {
+        let max_split_lamports = stake_record
+            .last_update_delegated_lamports()
+            .saturating_sub(self.stake_system.min_stake);
+        // None here means a 100% fee, where any amount of lamports leaves 0 after fees
+        let max_total_lamports = self
+            .withdraw_stake_account_fee
+            .to_fee_floor()
+            .ok_or(MarinadeError::CalculationFailure)?
+            .reverse_from_rem(max_split_lamports)
+            .map_or(u64::MAX, |range| *range.end());
+        let max = self
+            .max_pool_tokens_not_exceeding_lamports(max_total_lamports)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        self.quote_withdraw_stake(
+            max,
+            WithdrawStakeQuoteArgs {
+                stake_record: *stake_record,
+            },
+        )
+        .map(|_| max)
+    }
+
+    /// Smallest amount of mSOL that [`Self::quote_withdraw_stake`] accepts for `stake_record`,
+    /// such that the split stake account receives at least `min_stake`
+    #[inline]
+    pub fn min_withdraw_stake(&self, stake_record: &StakeRecord) -> Result<u64, MarinadeError> {
+        self.quote_withdraw_stake_exact_out(
+            self.stake_system.min_stake,
+            WithdrawStakeQuoteArgs {
+                stake_record: *stake_record,
+            },
+        )
+        .map(|quote| quote.tokens_in)
+    }
+}
+
+impl State {
+    /// Largest deposit that does not exceed `staking_sol_cap`.
+    ///
+    /// A deposit whose mSOL buy order is fully filled by the mSOL leg never exceeds the cap.
+    /// Above that, the lamports swapped for the whole mSOL leg are constant
+    /// and only the rest counts against the cap.
+    #[inline]
+    fn max_deposit_under_staking_cap(&self, msol_leg_balance: u64) -> Result<u64, MarinadeError> {
+        let headroom = self.staking_sol_cap.saturating_sub(
+            self.total_lamports_under_control()
+                .ok_or(MarinadeError::CalculationFailure)?,
+        );
+        // largest deposit whose mSOL buy order does not overflow u64
+        let max_buy_order = self
+            .max_lamports_not_exceeding_pool_tokens(u64::MAX)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        if msol_leg_balance == 0 {
+            return Ok(headroom.min(max_buy_order));
+        }
+        let max_fully_swapped = self
+            .max_lamports_not_exceeding_pool_tokens(msol_leg_balance)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        // overflow means no u64 deposit can buy out the whole mSOL leg
+        let sol_swapped = self
+            .pool_tokens_to_lamports(msol_leg_balance)
+            .unwrap_or(u64::MAX);
+        // the mSOL output of a partially swapped deposit is the whole mSOL leg + mSOL minted,
+        // which must not overflow u64
+        let max_minted = self
+            .max_lamports_not_exceeding_pool_tokens(u64::MAX - msol_leg_balance)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        let max_partially_swapped = sol_swapped.saturating_add(headroom.min(max_minted));
+        Ok(max_fully_swapped
+            .max(max_partially_swapped)
+            .min(max_buy_order))
+    }
+
+    /// Deposits too small to buy any mSOL are not swapped from the mSOL leg,
+    /// so if `min` exceeds the cap, the smallest deposit that is swapped might not
+    #[inline]
+    fn min_deposit_under_staking_cap(
+        &self,
+        min: u64,
+        msol_leg_balance: u64,
+    ) -> Result<u64, MarinadeError> {
+        if msol_leg_balance == 0 || !self.will_deposit_exceed_staking_cap(min, msol_leg_balance)? {
+            return Ok(min);
+        }
+        self.pool_tokens_to_lamports_ceil(1)
+            .map(|swapped_min| swapped_min.max(min))
+            .ok_or(MarinadeError::CalculationFailure)
+    }
+
+    /// Largest `x` such that `self.lamports_to_pool_tokens(x) <= pool_tokens`,
+    /// saturating at `u64::MAX`
+    #[inline]
+    const fn max_lamports_not_exceeding_pool_tokens(&self, pool_tokens: u64) -> Option<u64> {
+        let Some(total_virtual_staked_lamports) = self.total_virtual_staked_lamports() else {
+            return None;
+        };
+        Some(max_floor_input_not_exceeding(
+            pool_tokens,
+            self.msol_supply,
+            total_virtual_staked_lamports,
+        ))
+    }
+
+    /// Largest `x` such that `self.pool_tokens_to_lamports(x) <= lamports`,
+    /// saturating at `u64::MAX`
+    #[inline]
+    const fn max_pool_tokens_not_exceeding_lamports(&self, lamports: u64) -> Option<u64> {
+        let Some(total_virtual_staked_lamports) = self.total_virtual_staked_lamports() else {
+            return None;
+        };
+        Some(max_floor_input_not_exceeding(
+            lamports,
+            total_virtual_staked_lamports,
+            self.msol_supply,
+        ))
+    }
+}
+
+/// Largest `x` such that `floor(x * n / d) <= y`, saturating at `u64::MAX`.
+///
+/// A zero ratio is treated as identity, same as the `State` conversion functions.
+///
+/// ```md
+/// floor(xn / d) <= y
+/// xn / d < y + 1
+/// xn < d(y + 1)
+/// x <= (d(y + 1) - 1) / n
+/// ```
+#[inline]
+const fn max_floor_input_not_exceeding(y: u64, n: u64, d: u64) -> u64 {
+    if n == 0 || d == 0 {
+        return y;
+    }
+    // unchecked-arith: d < 2^64 and y + 1 <= 2^64 so product < 2^128,
+    // and product > 0 so - 1 does not underflow
+    let max = (d as u128 * (y as u128 + 1) - 1) / n as u128;
+    if max > u64::MAX as u128 {
+        u64::MAX
+    } else {
+        max as u64
+    }
+}
@@ -2,6 +2,7 @@
 ///
 /// Type T must have an alignment of 1 (typically ensured by using #[repr(C)] and
 /// containing only types that are byte arrays or have alignment 1)
+#[derive(Debug)]
 pub struct ListAccount<'a, T>(pub &'a [T]);
 
 impl<'a, T> ListAccount<'a, T> {
@@ -40,7 +41,7 @@ impl<'a, T> ListAccount<'a, T> {
         let record_size = core::mem::size_of::<T>();
 
         // Calculate bytes needed for the requested count
-        let bytes_needed = count * record_size;
+        let bytes_needed = count.checked_mul(record_size)?;
 
         // Ensure we have enough data for the requested count
         if remaining.len() < bytes_needed {
@@ -0,0 +1,43 @@
+use crate::{
+    liq_pool_msol_leg_authority, liq_pool_sol_leg_pda, msol_mint_authority, reserve_pda,
+    stake_deposit_authority, stake_withdraw_authority, State, STAKE_PROGRAM, SYSTEM_PROGRAM,
+    SYSVAR_CLOCK, SYSVAR_RENT, SYSVAR_STAKE_HISTORY, TOKEN_PROGRAM,
+};
+
+/// Number of [`State::lookup_table_addresses`]
+pub const MARINADE_LOOKUP_TABLE_LEN: usize = 19;
+
+impl State {
+    /// Accounts that are the same across Marinade instructions for the deployment
+    /// whose state account is `state_pubkey`, e.g. [`crate::STATE_PUBKEY`],
+    /// to put in an address lookup table for [`crate::Message::compile_v0`].
+    ///
+    /// PDAs are derived from the bumps stored in this state.
+    /// Returns `None` if any of them is invalid
+    pub fn lookup_table_addresses(
+        &self,
+        state_pubkey: &[u8; 32],
+    ) -> Option<[[u8; 32]; MARINADE_LOOKUP_TABLE_LEN]> {
+        Some([
+            *state_pubkey,
+            self.msol_mint,
+            self.validator_system.validator_list.account,
+            self.stake_system.stake_list.account,
+            self.treasury_msol_account,
+            self.operational_sol_account,
+            self.liq_pool.msol_leg,
+            reserve_pda(state_pubkey, self.reserve_bump_seed)?,
+            msol_mint_authority(state_pubkey, self.msol_mint_authority_bump_seed)?,
+            liq_pool_sol_leg_pda(state_pubkey, self.liq_pool.sol_leg_bump_seed)?,
+            liq_pool_msol_leg_authority(state_pubkey, self.liq_pool.msol_leg_authority_bump_seed)?,
+            stake_withdraw_authority(state_pubkey, self.stake_system.stake_withdraw_bump_seed)?,
+            stake_deposit_authority(state_pubkey, self.stake_system.stake_deposit_bump_seed)?,
+            SYSVAR_CLOCK,
+            SYSVAR_RENT,
+            SYSVAR_STAKE_HISTORY,
+            SYSTEM_PROGRAM,
+            TOKEN_PROGRAM,
+            STAKE_PROGRAM,
+        ])
+    }
+}
@@ -1,15 +1,39 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use list::ListAccount;
+use quote::{convert_ceil, convert_floor, LiquidUnstakeFees, QuoteChecks};
 use sanctum_u64_ratio::{Floor, Ratio};
 
 use crate::{
     DepositSolQuote, DepositSolQuoteArgs, DepositStakeQuote, DepositStakeQuoteArgs, Fee, FeeCents,
-    LiqPool, MarinadeError, StakeAccountLamports, StakeRecord, StakeSystem, ValidatorRecord,
-    ValidatorSystem, WithdrawStakeQuote, WithdrawStakeQuoteArgs,
+    LiqPool, LiquidUnstakeQuote, LiquidUnstakeQuoteArgs, MarinadeError, StakeAccountLamports,
+    StakeRecord, StakeSystem, ValidatorRecord, ValidatorSystem, WithdrawStakeQuote,
+    WithdrawStakeQuoteArgs,
 };
 
 pub mod list;
 
+mod apply;
+mod ladder;
+mod limits;
+mod lookup_table;
+mod quote;
+mod quote_context;
+mod reconcile;
+mod targets;
+mod token;
+
+pub use ladder::*;
+pub use lookup_table::*;
+pub use quote_context::*;
+pub use reconcile::*;
+pub use targets::*;
+
+#[cfg(feature = "alloc")]
+mod index;
+
+#[cfg(feature = "alloc")]
+pub use index::*;
+
 pub type ValidatorList<'a> = ListAccount<'a, ValidatorRecord>;
 pub type StakeList<'a> = ListAccount<'a, StakeRecord>;
 
@@ -124,13 +148,13 @@ impl State {
     };
 
     #[inline]
-    pub fn is_sol_deposit_too_low(&self, lamports: u64) -> bool {
-        lamports < self.min_deposit
+    pub const fn is_sol_deposit_too_low(&self, lamports: u64) -> bool {
+        QuoteChecks::new(self).is_sol_deposit_too_low(lamports)
     }
 
     #[inline]
-    pub fn is_stake_deposit_too_low(&self, stake_lamports: u64) -> bool {
-        stake_lamports < self.stake_system.min_stake
+    pub const fn is_stake_deposit_too_low(&self, stake_lamports: u64) -> bool {
+        QuoteChecks::new(self).is_stake_deposit_too_low(stake_lamports)
     }
 
     #[inline]
@@ -139,47 +163,36 @@ impl State {
         lamports: u64,
         msol_leg_balance: u64,
     ) -> Result<bool, MarinadeError> {
-        let msol_buy_order = self
-            .lamports_to_pool_tokens(lamports)
+        let DepositSolQuote {
+            lamports_to_reserve,
+            ..
+        } = self
+            .quote_deposit_sol_unchecked(lamports, DepositSolQuoteArgs { msol_leg_balance })
+            .ok_or(MarinadeError::CalculationFailure)?;
+        let staking_cap_headroom = self
+            .staking_cap_headroom()
             .ok_or(MarinadeError::CalculationFailure)?;
 
-        let msol_swapped = msol_buy_order.min(msol_leg_balance);
-        let sol_swapped = if msol_swapped > 0 {
-            if msol_buy_order == msol_swapped {
-                lamports
-            } else {
-                self.pool_tokens_to_lamports(msol_swapped)
-                    .ok_or(MarinadeError::CalculationFailure)?
-            }
-        } else {
-            0
-        };
-
-        let sol_deposited = lamports.saturating_sub(sol_swapped);
-        if sol_deposited == 0 {
-            return Ok(false);
-        }
-
-        // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/state/mod.rs#L196
-        let result_amount = self
-            .total_lamports_under_control()
-            .saturating_add(sol_deposited);
-
-        Ok(result_amount > self.staking_sol_cap)
+        Ok(quote::exceeds_staking_cap(
+            lamports_to_reserve,
+            staking_cap_headroom,
+        ))
     }
 
+    /// The deposit first buys as much mSOL as it can from the liquidity pool's mSOL leg,
+    /// then the remaining SOL is transferred to the reserve to mint new mSOL
     #[inline]
-    pub fn quote_deposit_sol_unchecked(&self, lamports: u64) -> Option<DepositSolQuote> {
-        let out_amount = if self.msol_supply == 0 {
-            lamports
-        } else {
-            self.lamports_to_pool_tokens(lamports)?
-        };
-
-        Some(DepositSolQuote {
-            in_amount: lamports,
-            out_amount,
-        })
+    pub fn quote_deposit_sol_unchecked(
+        &self,
+        lamports: u64,
+        args: DepositSolQuoteArgs,
+    ) -> Option<DepositSolQuote> {
+        quote::deposit_sol_unchecked(
+            self.supply_over_lamports()?,
+            self.lamports_over_supply()?,
+            args.msol_leg_balance,
+            lamports,
+        )
     }
 
     #[inline]
@@ -188,19 +201,13 @@ impl State {
         lamports: u64,
         args: DepositSolQuoteArgs,
     ) -> Result<DepositSolQuote, MarinadeError> {
-        if self.paused {
-            return Err(MarinadeError::ProgramIsPaused);
-        }
-
-        if self.is_sol_deposit_too_low(lamports) {
-            return Err(MarinadeError::DepositAmountIsTooLow);
-        }
+        QuoteChecks::new(self).deposit_sol(lamports)?;
 
         if self.will_deposit_exceed_staking_cap(lamports, args.msol_leg_balance)? {
             return Err(MarinadeError::StakingIsCapped);
         }
 
-        self.quote_deposit_sol_unchecked(lamports)
+        self.quote_deposit_sol_unchecked(lamports, args)
             .ok_or(MarinadeError::CalculationFailure)
     }
 
@@ -209,36 +216,19 @@ impl State {
         &self,
         stake_account_lamports: StakeAccountLamports,
     ) -> Option<DepositStakeQuote> {
-        let new_pool_tokens = self.lamports_to_pool_tokens(stake_account_lamports.total())?;
-        let new_pool_tokens_from_stake =
-            self.lamports_to_pool_tokens(stake_account_lamports.staked)?;
-
-        if new_pool_tokens_from_stake > new_pool_tokens {
-            return None;
-        }
-
-        Some(DepositStakeQuote {
-            stake_account_lamports_in: stake_account_lamports,
-            // TODO: confirm it's _from_stake and not just total
-            tokens_out: new_pool_tokens_from_stake,
-        })
+        quote::deposit_stake_unchecked(self.supply_over_lamports()?, stake_account_lamports)
     }
 
     /// For a successful stake deposit, the user must further ensure that the stake account
-    /// to be deposited is active and delegated to a validator on the [`ValidatorList`]
+    /// to be deposited is active and delegated to a validator on the [`ValidatorList`],
+    /// which [`crate::preflight_deposit_stake`] checks
     #[inline]
     pub fn quote_deposit_stake(
         &self,
         stake_account_lamports: StakeAccountLamports,
         args: DepositStakeQuoteArgs,
     ) -> Result<DepositStakeQuote, MarinadeError> {
-        if self.paused {
-            return Err(MarinadeError::ProgramIsPaused);
-        }
-
-        if self.is_stake_deposit_too_low(stake_account_lamports.staked) {
-            return Err(MarinadeError::TooLowDelegationInDepositingStake);
-        }
+        QuoteChecks::new(self).deposit_stake(stake_account_lamports.staked)?;
 
         if self
             .will_deposit_exceed_staking_cap(stake_account_lamports.staked, args.msol_leg_balance)?
@@ -252,23 +242,12 @@ impl State {
 
     #[inline]
     pub fn quote_withdraw_stake_unchecked(&self, pool_tokens: u64) -> Option<WithdrawStakeQuote> {
-        let total_lamports = self.pool_tokens_to_lamports(pool_tokens)?;
-
-        // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/instructions/user/withdraw_stake_account.rs#L176
-        let withdraw_stake_account_fee_lamports = self
-            .withdraw_stake_account_fee
-            .to_fee_floor()?
-            .apply(total_lamports)?;
-
-        let split_lamports = withdraw_stake_account_fee_lamports.rem();
-
-        let msol_fees = pool_tokens.saturating_sub(self.lamports_to_pool_tokens(split_lamports)?);
-
-        Some(WithdrawStakeQuote {
-            tokens_in: pool_tokens,
-            lamports_staked: split_lamports,
-            fee_amount: msol_fees,
-        })
+        quote::withdraw_stake_unchecked(
+            self.supply_over_lamports()?,
+            self.lamports_over_supply()?,
+            self.withdraw_stake_account_fee.to_fee_floor()?,
+            pool_tokens,
+        )
     }
 
     #[inline]
@@ -277,100 +256,257 @@ impl State {
         pool_tokens: u64,
         args: WithdrawStakeQuoteArgs,
     ) -> Result<WithdrawStakeQuote, MarinadeError> {
-        if self.paused {
-            return Err(MarinadeError::ProgramIsPaused);
-        }
-
-        if !self.withdraw_stake_account_enabled {
-            return Err(MarinadeError::WithdrawStakeAccountIsNotEnabled);
-        }
-
-        if args.stake_record.is_emergency_unstaking() {
-            return Err(MarinadeError::StakeAccountIsEmergencyUnstaking);
-        }
+        let checks = QuoteChecks::new(self);
+        checks.withdraw_stake(&args)?;
 
         let quote = self
             .quote_withdraw_stake_unchecked(pool_tokens)
             .ok_or(MarinadeError::CalculationFailure)?;
 
-        if quote.lamports_staked < self.stake_system.min_stake {
-            return Err(MarinadeError::WithdrawStakeLamportsIsTooLow);
-        }
+        checks.withdraw_stake_quote(&quote, args.stake_record.last_update_delegated_lamports())?;
 
-        if args.stake_record.last_update_delegated_lamports() < quote.lamports_staked {
-            return Err(MarinadeError::SelectedStakeAccountHasNotEnoughFunds);
-        }
+        Ok(quote)
+    }
 
-        let remainder_stake = args
-            .stake_record
-            .last_update_delegated_lamports()
-            .saturating_sub(quote.lamports_staked);
+    /// Fee charged for a liquid unstake that leaves `lamports_after` available
+    /// in the liquidity pool's SOL leg, decreasing linearly from `lp_max_fee` at 0
+    /// to `lp_min_fee` at `lp_liquidity_target`.
+    ///
+    /// `None` if `lp_min_fee > lp_max_fee`
+    #[inline]
+    pub const fn liquid_unstake_fee(&self, lamports_after: u64) -> Option<Fee> {
+        LiquidUnstakeFees::new(&self.liq_pool).fee(lamports_after)
+    }
 
-        if remainder_stake < self.stake_system.min_stake {
-            return Err(MarinadeError::StakeAccountRemainderTooLow);
-        }
+    /// The fee is determined by the SOL leg's liquidity remaining
+    /// after removing the value of `pool_tokens` before fees, see [`Self::liquid_unstake_fee`]
+    #[inline]
+    pub fn quote_liquid_unstake_unchecked(
+        &self,
+        pool_tokens: u64,
+        args: LiquidUnstakeQuoteArgs,
+    ) -> Option<LiquidUnstakeQuote> {
+        quote::liquid_unstake_unchecked(
+            self.lamports_over_supply()?,
+            &LiquidUnstakeFees::new(&self.liq_pool),
+            self.rent_exempt_for_token_acc,
+            args.sol_leg_lamports,
+            pool_tokens,
+        )
+    }
+
+    #[inline]
+    pub fn quote_liquid_unstake(
+        &self,
+        pool_tokens: u64,
+        args: LiquidUnstakeQuoteArgs,
+    ) -> Result<LiquidUnstakeQuote, MarinadeError> {
+        let checks = QuoteChecks::new(self);
+        checks.liquid_unstake()?;
+
+        let quote = self
+            .quote_liquid_unstake_unchecked(pool_tokens, args)
+            .ok_or(MarinadeError::CalculationFailure)?;
+
+        checks.liquid_unstake_quote(
+            &quote,
+            args.sol_leg_lamports,
+            self.rent_exempt_for_token_acc,
+        )?;
 
         Ok(quote)
     }
 }
 
+/// Exact-out (reverse) quotes.
+///
+/// These find the minimum input required to produce at least the requested output,
+/// rounding up. The returned quotes are forward quotes of that input,
+/// so their outputs always meet the target but may exceed it by rounding dust.
+impl State {
+    /// Partially swapping from the liquidity pool's mSOL leg may round in the user's favour,
+    /// so the returned input is sufficient but not always minimal in that case
+    #[inline]
+    pub fn quote_deposit_sol_exact_out_unchecked(
+        &self,
+        msol_out: u64,
+        args: DepositSolQuoteArgs,
+    ) -> Option<DepositSolQuote> {
+        quote::deposit_sol_exact_out_unchecked(
+            self.supply_over_lamports()?,
+            self.lamports_over_supply()?,
+            args.msol_leg_balance,
+            msol_out,
+        )
+    }
+
+    #[inline]
+    pub fn quote_deposit_sol_exact_out(
+        &self,
+        msol_out: u64,
+        args: DepositSolQuoteArgs,
+    ) -> Result<DepositSolQuote, MarinadeError> {
+        let quote = self
+            .quote_deposit_sol_exact_out_unchecked(msol_out, args)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        self.quote_deposit_sol(quote.in_amount, args)
+    }
+
+    /// `unstaked` is passed through unchanged to the returned quote's
+    /// [`DepositStakeQuote::stake_account_lamports_in`] since it does not affect the mSOL output
+    #[inline]
+    pub fn quote_deposit_stake_exact_out_unchecked(
+        &self,
+        tokens_out: u64,
+        unstaked: u64,
+    ) -> Option<DepositStakeQuote> {
+        quote::deposit_stake_exact_out_unchecked(
+            self.supply_over_lamports()?,
+            self.lamports_over_supply()?,
+            tokens_out,
+            unstaked,
+        )
+    }
+
+    /// See [`Self::quote_deposit_stake_exact_out_unchecked`] and [`Self::quote_deposit_stake`]
+    #[inline]
+    pub fn quote_deposit_stake_exact_out(
+        &self,
+        tokens_out: u64,
+        unstaked: u64,
+        args: DepositStakeQuoteArgs,
+    ) -> Result<DepositStakeQuote, MarinadeError> {
+        let quote = self
+            .quote_deposit_stake_exact_out_unchecked(tokens_out, unstaked)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        self.quote_deposit_stake(quote.stake_account_lamports_in, args)
+    }
+
+    /// Returns the quote for the minimum amount of mSOL to burn for the
+    /// split stake account to receive at least `lamports_staked`
+    #[inline]
+    pub fn quote_withdraw_stake_exact_out_unchecked(
+        &self,
+        lamports_staked: u64,
+    ) -> Option<WithdrawStakeQuote> {
+        quote::withdraw_stake_exact_out_unchecked(
+            self.supply_over_lamports()?,
+            self.lamports_over_supply()?,
+            self.withdraw_stake_account_fee.to_fee_floor()?,
+            lamports_staked,
+        )
+    }
+
+    #[inline]
+    pub fn quote_withdraw_stake_exact_out(
+        &self,
+        lamports_staked: u64,
+        args: WithdrawStakeQuoteArgs,
+    ) -> Result<WithdrawStakeQuote, MarinadeError> {
+        let quote = self
+            .quote_withdraw_stake_exact_out_unchecked(lamports_staked)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        self.quote_withdraw_stake(quote.tokens_in, args)
+    }
+}
+
 impl State {
+    /// `None` if the total lamports under control overflow
     #[inline]
-    pub const fn supply_over_lamports(&self) -> Floor<Ratio<u64, u64>> {
-        Floor(Ratio {
+    pub const fn supply_over_lamports(&self) -> Option<Floor<Ratio<u64, u64>>> {
+        let Some(d) = self.total_virtual_staked_lamports() else {
+            return None;
+        };
+        Some(Floor(Ratio {
             n: self.msol_supply,
-            d: self.total_virtual_staked_lamports(),
-        })
+            d,
+        }))
     }
 
+    /// `None` if the total lamports under control overflow
     #[inline]
-    pub const fn lamports_over_supply(&self) -> Floor<Ratio<u64, u64>> {
-        Floor(Ratio {
-            n: self.total_virtual_staked_lamports(),
+    pub const fn lamports_over_supply(&self) -> Option<Floor<Ratio<u64, u64>>> {
+        let Some(n) = self.total_virtual_staked_lamports() else {
+            return None;
+        };
+        Some(Floor(Ratio {
+            n,
             d: self.msol_supply,
-        })
+        }))
     }
 
     #[inline]
     pub const fn lamports_to_pool_tokens(&self, lamports: u64) -> Option<u64> {
-        let ratio = self.supply_over_lamports();
-        if ratio.0.is_zero() {
-            return Some(lamports);
-        }
-        ratio.apply(lamports)
+        let Some(ratio) = self.supply_over_lamports() else {
+            return None;
+        };
+        convert_floor(ratio, lamports)
     }
 
     #[inline]
     pub const fn pool_tokens_to_lamports(&self, pool_tokens: u64) -> Option<u64> {
-        let ratio = self.lamports_over_supply();
-        if ratio.0.is_zero() {
-            return Some(pool_tokens);
-        }
-        ratio.apply(pool_tokens)
+        let Some(ratio) = self.lamports_over_supply() else {
+            return None;
+        };
+        convert_floor(ratio, pool_tokens)
+    }
+
+    /// Minimum amount of pool tokens `x` such that
+    /// `self.pool_tokens_to_lamports(x) >= lamports`
+    #[inline]
+    pub const fn lamports_to_pool_tokens_ceil(&self, lamports: u64) -> Option<u64> {
+        let Some(ratio) = self.supply_over_lamports() else {
+            return None;
+        };
+        convert_ceil(ratio, lamports)
     }
 
+    /// Minimum amount of lamports `x` such that
+    /// `self.lamports_to_pool_tokens(x) >= pool_tokens`
     #[inline]
-    const fn total_cooling_down(&self) -> u64 {
+    pub const fn pool_tokens_to_lamports_ceil(&self, pool_tokens: u64) -> Option<u64> {
+        let Some(ratio) = self.lamports_over_supply() else {
+            return None;
+        };
+        convert_ceil(ratio, pool_tokens)
+    }
+
+    #[inline]
+    const fn total_cooling_down(&self) -> Option<u64> {
         self.stake_system
             .delayed_unstake_cooling_down
             .checked_add(self.emergency_cooling_down)
-            .expect("Total cooling down overflow")
     }
 
     #[inline]
-    const fn total_lamports_under_control(&self) -> u64 {
-        self.validator_system
+    const fn total_lamports_under_control(&self) -> Option<u64> {
+        let Some(total_cooling_down) = self.total_cooling_down() else {
+            return None;
+        };
+        let Some(staked) = self
+            .validator_system
             .total_active_balance
-            .checked_add(self.total_cooling_down())
-            .expect("Stake balance overflow")
-            .checked_add(self.available_reserve_balance)
-            .expect("Total SOLs under control overflow")
+            .checked_add(total_cooling_down)
+        else {
+            return None;
+        };
+        staked.checked_add(self.available_reserve_balance)
     }
 
     #[inline]
-    const fn total_virtual_staked_lamports(&self) -> u64 {
-        self.total_lamports_under_control()
-            .saturating_sub(self.circulating_ticket_balance)
+    const fn total_virtual_staked_lamports(&self) -> Option<u64> {
+        let Some(total) = self.total_lamports_under_control() else {
+            return None;
+        };
+        Some(total.saturating_sub(self.circulating_ticket_balance))
+    }
+
+    #[inline]
+    const fn staking_cap_headroom(&self) -> Option<u64> {
+        let Some(total) = self.total_lamports_under_control() else {
+            return None;
+        };
+        Some(quote::staking_cap_headroom(self.staking_sol_cap, total))
     }
 }
 
@@ -0,0 +1,435 @@
+use sanctum_u64_ratio::{Ceil, Floor, Ratio};
+
+use crate::{
+    DepositSolQuote, DepositStakeQuote, Fee, LiqPool, LiquidUnstakeQuote, MarinadeError,
+    StakeAccountLamports, State, WithdrawStakeQuote, WithdrawStakeQuoteArgs,
+};
+
+pub(crate) type WithdrawStakeAccountFee = sanctum_fee_ratio::Fee<Floor<Ratio<u32, u32>>>;
+
+/// Converts `amount` at `ratio`, 1:1 if `ratio` is zero, i.e. for an empty pool
+#[inline]
+pub(crate) const fn convert_floor(ratio: Floor<Ratio<u64, u64>>, amount: u64) -> Option<u64> {
+    if ratio.0.is_zero() {
+        return Some(amount);
+    }
+    ratio.apply(amount)
+}
+
+/// [`convert_floor`] rounding up
+#[inline]
+pub(crate) const fn convert_ceil(Floor(ratio): Floor<Ratio<u64, u64>>, amount: u64) -> Option<u64> {
+    if ratio.is_zero() {
+        return Some(amount);
+    }
+    Ceil(ratio).apply(amount)
+}
+
+/// Max lamports that can be transferred to the reserve
+/// before a deposit exceeds `staking_sol_cap`
+#[inline]
+pub(crate) const fn staking_cap_headroom(
+    staking_sol_cap: u64,
+    total_lamports_under_control: u64,
+) -> u64 {
+    // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/state/mod.rs#L196
+    // total_lamports_under_control + x saturates, so nothing exceeds a cap of u64::MAX
+    if staking_sol_cap == u64::MAX {
+        u64::MAX
+    } else {
+        staking_sol_cap.saturating_sub(total_lamports_under_control)
+    }
+}
+
+#[inline]
+pub(crate) const fn exceeds_staking_cap(
+    lamports_to_reserve: u64,
+    staking_cap_headroom: u64,
+) -> bool {
+    lamports_to_reserve > staking_cap_headroom
+}
+
+/// See [`State::quote_deposit_sol_unchecked`]
+#[inline]
+pub(crate) const fn deposit_sol_unchecked(
+    supply_over_lamports: Floor<Ratio<u64, u64>>,
+    lamports_over_supply: Floor<Ratio<u64, u64>>,
+    msol_leg_balance: u64,
+    lamports: u64,
+) -> Option<DepositSolQuote> {
+    // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/instructions/user/deposit.rs
+    let Some(msol_buy_order) = convert_floor(supply_over_lamports, lamports) else {
+        return None;
+    };
+
+    let msol_from_liq_pool = if msol_buy_order < msol_leg_balance {
+        msol_buy_order
+    } else {
+        msol_leg_balance
+    };
+    let lamports_to_liq_pool = if msol_from_liq_pool == 0 {
+        0
+    } else if msol_from_liq_pool == msol_buy_order {
+        lamports
+    } else {
+        let Some(lamports_to_liq_pool) = convert_floor(lamports_over_supply, msol_from_liq_pool)
+        else {
+            return None;
+        };
+        lamports_to_liq_pool
+    };
+
+    let lamports_to_reserve = lamports.saturating_sub(lamports_to_liq_pool);
+    let msol_minted = if lamports_to_reserve == 0 {
+        0
+    } else {
+        let Some(msol_minted) = convert_floor(supply_over_lamports, lamports_to_reserve) else {
+            return None;
+        };
+        msol_minted
+    };
+
+    let Some(out_amount) = msol_from_liq_pool.checked_add(msol_minted) else {
+        return None;
+    };
+    Some(DepositSolQuote {
+        in_amount: lamports,
+        out_amount,
+        msol_from_liq_pool,
+        msol_minted,
+        lamports_to_liq_pool,
+        lamports_to_reserve,
+    })
+}
+
+/// See [`State::quote_deposit_stake_unchecked`]
+#[inline]
+pub(crate) const fn deposit_stake_unchecked(
+    supply_over_lamports: Floor<Ratio<u64, u64>>,
+    stake_account_lamports: StakeAccountLamports,
+) -> Option<DepositStakeQuote> {
+    let Some(total_lamports_in) = stake_account_lamports.total() else {
+        return None;
+    };
+    let Some(new_pool_tokens) = convert_floor(supply_over_lamports, total_lamports_in) else {
+        return None;
+    };
+    let Some(new_pool_tokens_from_stake) =
+        convert_floor(supply_over_lamports, stake_account_lamports.staked)
+    else {
+        return None;
+    };
+
+    if new_pool_tokens_from_stake > new_pool_tokens {
+        return None;
+    }
+
+    Some(DepositStakeQuote {
+        stake_account_lamports_in: stake_account_lamports,
+        total_lamports_in,
+        // TODO: confirm it's _from_stake and not just total
+        tokens_out: new_pool_tokens_from_stake,
+    })
+}
+
+/// See [`State::quote_withdraw_stake_unchecked`]
+#[inline]
+pub(crate) const fn withdraw_stake_unchecked(
+    supply_over_lamports: Floor<Ratio<u64, u64>>,
+    lamports_over_supply: Floor<Ratio<u64, u64>>,
+    withdraw_stake_account_fee: WithdrawStakeAccountFee,
+    pool_tokens: u64,
+) -> Option<WithdrawStakeQuote> {
+    let Some(total_lamports) = convert_floor(lamports_over_supply, pool_tokens) else {
+        return None;
+    };
+
+    // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/instructions/user/withdraw_stake_account.rs#L176
+    let Some(withdraw_stake_account_fee_lamports) =
+        withdraw_stake_account_fee.apply(total_lamports)
+    else {
+        return None;
+    };
+    let split_lamports = withdraw_stake_account_fee_lamports.rem();
+
+    let Some(split_pool_tokens) = convert_floor(supply_over_lamports, split_lamports) else {
+        return None;
+    };
+    let msol_fees = pool_tokens.saturating_sub(split_pool_tokens);
+
+    Some(WithdrawStakeQuote {
+        tokens_in: pool_tokens,
+        lamports_staked: split_lamports,
+        fee_amount: msol_fees,
+    })
+}
+
+/// See [`State::quote_liquid_unstake_unchecked`]
+#[inline]
+pub(crate) const fn liquid_unstake_unchecked(
+    lamports_over_supply: Floor<Ratio<u64, u64>>,
+    liquid_unstake_fees: &LiquidUnstakeFees,
+    rent_exempt_for_token_acc: u64,
+    sol_leg_lamports: u64,
+    pool_tokens: u64,
+) -> Option<LiquidUnstakeQuote> {
+    // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/instructions/user/liquid_unstake.rs
+    let max_lamports = sol_leg_lamports.saturating_sub(rent_exempt_for_token_acc);
+    let Some(user_remove_lamports) = convert_floor(lamports_over_supply, pool_tokens) else {
+        return None;
+    };
+    let fee = match max_lamports.checked_sub(user_remove_lamports) {
+        None | Some(0) => liquid_unstake_fees.lp_max_fee,
+        Some(lamports_after) => match liquid_unstake_fees.fee(lamports_after) {
+            Some(fee) => fee,
+            None => return None,
+        },
+    };
+
+    let Some(fee) = fee.to_fee_floor() else {
+        return None;
+    };
+    let Some(msol_fee) = fee.apply(pool_tokens) else {
+        return None;
+    };
+    let Some(lamports_out) = convert_floor(lamports_over_supply, msol_fee.rem()) else {
+        return None;
+    };
+    let Some(treasury_cut) = liquid_unstake_fees.treasury_cut.to_fee_floor() else {
+        return None;
+    };
+    let Some(treasury_msol_cut) = treasury_cut.apply(msol_fee.fee()) else {
+        return None;
+    };
+
+    Some(LiquidUnstakeQuote {
+        tokens_in: pool_tokens,
+        lamports_out,
+        fee_amount: msol_fee.fee(),
+        treasury_msol_cut: treasury_msol_cut.fee(),
+    })
+}
+
+/// See [`State::quote_deposit_sol_exact_out_unchecked`]
+#[inline]
+pub(crate) const fn deposit_sol_exact_out_unchecked(
+    supply_over_lamports: Floor<Ratio<u64, u64>>,
+    lamports_over_supply: Floor<Ratio<u64, u64>>,
+    msol_leg_balance: u64,
+    msol_out: u64,
+) -> Option<DepositSolQuote> {
+    let Some(lamports) = convert_ceil(lamports_over_supply, msol_out) else {
+        return None;
+    };
+    deposit_sol_unchecked(
+        supply_over_lamports,
+        lamports_over_supply,
+        msol_leg_balance,
+        lamports,
+    )
+}
+
+/// See [`State::quote_deposit_stake_exact_out_unchecked`]
+#[inline]
+pub(crate) const fn deposit_stake_exact_out_unchecked(
+    supply_over_lamports: Floor<Ratio<u64, u64>>,
+    lamports_over_supply: Floor<Ratio<u64, u64>>,
+    tokens_out: u64,
+    unstaked: u64,
+) -> Option<DepositStakeQuote> {
+    let Some(staked) = convert_ceil(lamports_over_supply, tokens_out) else {
+        return None;
+    };
+    deposit_stake_unchecked(
+        supply_over_lamports,
+        StakeAccountLamports { staked, unstaked },
+    )
+}
+
+/// See [`State::quote_withdraw_stake_exact_out_unchecked`]
+#[inline]
+pub(crate) const fn withdraw_stake_exact_out_unchecked(
+    supply_over_lamports: Floor<Ratio<u64, u64>>,
+    lamports_over_supply: Floor<Ratio<u64, u64>>,
+    withdraw_stake_account_fee: WithdrawStakeAccountFee,
+    lamports_staked: u64,
+) -> Option<WithdrawStakeQuote> {
+    // rem(x) = x - floor(x * fee) is non-decreasing in steps of at most 1,
+    // so the start of the range is the min total lamports that meets the target
+    let Some(total_lamports) = withdraw_stake_account_fee.reverse_from_rem(lamports_staked) else {
+        return None;
+    };
+    let Some(pool_tokens) = convert_ceil(supply_over_lamports, *total_lamports.start()) else {
+        return None;
+    };
+    withdraw_stake_unchecked(
+        supply_over_lamports,
+        lamports_over_supply,
+        withdraw_stake_account_fee,
+        pool_tokens,
+    )
+}
+
+/// The [`State`] values that quotes check before and after any calculation
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct QuoteChecks {
+    min_deposit: u64,
+    min_stake: u64,
+    min_withdraw: u64,
+    paused: bool,
+    withdraw_stake_account_enabled: bool,
+}
+
+impl QuoteChecks {
+    #[inline]
+    pub const fn new(state: &State) -> Self {
+        Self {
+            min_deposit: state.min_deposit,
+            min_stake: state.stake_system.min_stake,
+            min_withdraw: state.min_withdraw,
+            paused: state.paused,
+            withdraw_stake_account_enabled: state.withdraw_stake_account_enabled,
+        }
+    }
+
+    #[inline]
+    pub const fn is_sol_deposit_too_low(&self, lamports: u64) -> bool {
+        lamports < self.min_deposit
+    }
+
+    #[inline]
+    pub const fn is_stake_deposit_too_low(&self, stake_lamports: u64) -> bool {
+        stake_lamports < self.min_stake
+    }
+
+    #[inline]
+    pub const fn deposit_sol(&self, lamports: u64) -> Result<(), MarinadeError> {
+        if self.paused {
+            return Err(MarinadeError::ProgramIsPaused);
+        }
+        if self.is_sol_deposit_too_low(lamports) {
+            return Err(MarinadeError::DepositAmountIsTooLow);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub const fn deposit_stake(&self, staked: u64) -> Result<(), MarinadeError> {
+        if self.paused {
+            return Err(MarinadeError::ProgramIsPaused);
+        }
+        if self.is_stake_deposit_too_low(staked) {
+            return Err(MarinadeError::TooLowDelegationInDepositingStake);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn withdraw_stake(&self, args: &WithdrawStakeQuoteArgs) -> Result<(), MarinadeError> {
+        if self.paused {
+            return Err(MarinadeError::ProgramIsPaused);
+        }
+        if !self.withdraw_stake_account_enabled {
+            return Err(MarinadeError::WithdrawStakeAccountIsNotEnabled);
+        }
+        if args.stake_record.is_emergency_unstaking() {
+            return Err(MarinadeError::StakeAccountIsEmergencyUnstaking);
+        }
+        Ok(())
+    }
+
+    /// Both the split stake account and the remainder of
+    /// the stake account with `delegated` lamports must have at least `min_stake`
+    #[inline]
+    pub const fn withdraw_stake_quote(
+        &self,
+        quote: &WithdrawStakeQuote,
+        delegated: u64,
+    ) -> Result<(), MarinadeError> {
+        if quote.lamports_staked < self.min_stake {
+            return Err(MarinadeError::WithdrawStakeLamportsIsTooLow);
+        }
+        if delegated < quote.lamports_staked {
+            return Err(MarinadeError::SelectedStakeAccountHasNotEnoughFunds);
+        }
+        if delegated.saturating_sub(quote.lamports_staked) < self.min_stake {
+            return Err(MarinadeError::StakeAccountRemainderTooLow);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub const fn liquid_unstake(&self) -> Result<(), MarinadeError> {
+        if self.paused {
+            return Err(MarinadeError::ProgramIsPaused);
+        }
+        Ok(())
+    }
+
+    /// The SOL leg must keep `rent_exempt_for_token_acc` after paying out the quote
+    #[inline]
+    pub const fn liquid_unstake_quote(
+        &self,
+        quote: &LiquidUnstakeQuote,
+        sol_leg_lamports: u64,
+        rent_exempt_for_token_acc: u64,
+    ) -> Result<(), MarinadeError> {
+        if quote.lamports_out.saturating_add(rent_exempt_for_token_acc) > sol_leg_lamports {
+            return Err(MarinadeError::InsufficientLiquidity);
+        }
+        if quote.lamports_out < self.min_withdraw {
+            return Err(MarinadeError::WithdrawAmountIsTooLow);
+        }
+        Ok(())
+    }
+}
+
+/// The [`LiqPool`] values that liquid unstake fees depend on
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LiquidUnstakeFees {
+    lp_liquidity_target: u64,
+    lp_max_fee: Fee,
+    lp_min_fee: Fee,
+    treasury_cut: Fee,
+}
+
+impl LiquidUnstakeFees {
+    #[inline]
+    pub const fn new(liq_pool: &LiqPool) -> Self {
+        Self {
+            lp_liquidity_target: liq_pool.lp_liquidity_target,
+            lp_max_fee: liq_pool.lp_max_fee,
+            lp_min_fee: liq_pool.lp_min_fee,
+            treasury_cut: liq_pool.treasury_cut,
+        }
+    }
+
+    /// See [`State::liquid_unstake_fee`]
+    #[inline]
+    pub const fn fee(&self, lamports_after: u64) -> Option<Fee> {
+        // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/state/liq_pool.rs
+        let Self {
+            lp_liquidity_target,
+            lp_max_fee,
+            lp_min_fee,
+            ..
+        } = *self;
+        if lamports_after >= lp_liquidity_target {
+            return Some(lp_min_fee);
+        }
+        let Some(range) = lp_max_fee.basis_points.checked_sub(lp_min_fee.basis_points) else {
+            return None;
+        };
+        // lamports_after < lp_liquidity_target so discount < range
+        let Some(discount) = Floor(Ratio {
+            n: lamports_after,
+            d: lp_liquidity_target,
+        })
+        .apply(range as u64) else {
+            return None;
+        };
+        Some(Fee {
+            basis_points: lp_max_fee.basis_points - discount as u32,
+        })
+    }
+}
@@ -0,0 +1,335 @@
+use sanctum_u64_ratio::{Floor, Ratio};
+
+use super::quote::{
+    self, convert_ceil, convert_floor, LiquidUnstakeFees, QuoteChecks, WithdrawStakeAccountFee,
+};
+use crate::{
+    DepositSolQuote, DepositStakeQuote, Fee, LiquidUnstakeQuote, LiquidUnstakeQuoteArgs,
+    MarinadeError, StakeAccountLamports, State, WithdrawStakeQuote, WithdrawStakeQuoteArgs,
+};
+
+/// The values of a [`State`] and the liquidity pool's mSOL leg balance that
+/// its quote functions depend on, precomputed once for repeated quoting.
+///
+/// All quote functions return the same results as the [`State`] functions of the same name.
+/// A `QuoteContext` is a snapshot and must be rebuilt whenever
+/// the [`State`] or the mSOL leg balance changes.
+#[derive(Debug, Clone, Copy)]
+pub struct QuoteContext {
+    supply_over_lamports: Floor<Ratio<u64, u64>>,
+    lamports_over_supply: Floor<Ratio<u64, u64>>,
+    staking_cap_headroom: u64,
+    withdraw_stake_account_fee: Option<WithdrawStakeAccountFee>,
+    msol_leg_balance: u64,
+    rent_exempt_for_token_acc: u64,
+    liquid_unstake_fees: LiquidUnstakeFees,
+    checks: QuoteChecks,
+}
+
+impl QuoteContext {
+    /// `None` if the total lamports under control overflow,
+    /// in which case all of `state`'s quotes fail with [`MarinadeError::CalculationFailure`]
+    /// unless an earlier check fails first
+    #[inline]
+    pub const fn new(state: &State, msol_leg_balance: u64) -> Option<Self> {
+        let (Some(supply_over_lamports), Some(lamports_over_supply), Some(staking_cap_headroom)) = (
+            state.supply_over_lamports(),
+            state.lamports_over_supply(),
+            state.staking_cap_headroom(),
+        ) else {
+            return None;
+        };
+        Some(Self {
+            supply_over_lamports,
+            lamports_over_supply,
+            staking_cap_headroom,
+            withdraw_stake_account_fee: state.withdraw_stake_account_fee.to_fee_floor(),
+            msol_leg_balance,
+            rent_exempt_for_token_acc: state.rent_exempt_for_token_acc,
+            liquid_unstake_fees: LiquidUnstakeFees::new(&state.liq_pool),
+            checks: QuoteChecks::new(state),
+        })
+    }
+
+    #[inline]
+    pub const fn msol_leg_balance(&self) -> u64 {
+        self.msol_leg_balance
+    }
+
+    /// Max lamports that can be transferred to the reserve
+    /// before a deposit exceeds `staking_sol_cap`
+    #[inline]
+    pub const fn staking_cap_headroom(&self) -> u64 {
+        self.staking_cap_headroom
+    }
+}
+
+/// Conversions, see the [`State`] functions of the same name
+impl QuoteContext {
+    #[inline]
+    pub const fn supply_over_lamports(&self) -> Floor<Ratio<u64, u64>> {
+        self.supply_over_lamports
+    }
+
+    #[inline]
+    pub const fn lamports_over_supply(&self) -> Floor<Ratio<u64, u64>> {
+        self.lamports_over_supply
+    }
+
+    #[inline]
+    pub const fn lamports_to_pool_tokens(&self, lamports: u64) -> Option<u64> {
+        convert_floor(self.supply_over_lamports, lamports)
+    }
+
+    #[inline]
+    pub const fn pool_tokens_to_lamports(&self, pool_tokens: u64) -> Option<u64> {
+        convert_floor(self.lamports_over_supply, pool_tokens)
+    }
+
+    #[inline]
+    pub const fn lamports_to_pool_tokens_ceil(&self, lamports: u64) -> Option<u64> {
+        convert_ceil(self.supply_over_lamports, lamports)
+    }
+
+    #[inline]
+    pub const fn pool_tokens_to_lamports_ceil(&self, pool_tokens: u64) -> Option<u64> {
+        convert_ceil(self.lamports_over_supply, pool_tokens)
+    }
+}
+
+/// Quotes, see the [`State`] functions of the same name
+impl QuoteContext {
+    #[inline]
+    pub const fn is_sol_deposit_too_low(&self, lamports: u64) -> bool {
+        self.checks.is_sol_deposit_too_low(lamports)
+    }
+
+    #[inline]
+    pub const fn is_stake_deposit_too_low(&self, stake_lamports: u64) -> bool {
+        self.checks.is_stake_deposit_too_low(stake_lamports)
+    }
+
+    #[inline]
+    pub fn will_deposit_exceed_staking_cap(&self, lamports: u64) -> Result<bool, MarinadeError> {
+        self.quote_deposit_sol_unchecked(lamports)
+            .map(|quote| self.exceeds_staking_cap(&quote))
+            .ok_or(MarinadeError::CalculationFailure)
+    }
+
+    #[inline]
+    const fn exceeds_staking_cap(&self, quote: &DepositSolQuote) -> bool {
+        quote::exceeds_staking_cap(quote.lamports_to_reserve, self.staking_cap_headroom)
+    }
+
+    #[inline]
+    pub const fn quote_deposit_sol_unchecked(&self, lamports: u64) -> Option<DepositSolQuote> {
+        quote::deposit_sol_unchecked(
+            self.supply_over_lamports,
+            self.lamports_over_supply,
+            self.msol_leg_balance,
+            lamports,
+        )
+    }
+
+    #[inline]
+    pub fn quote_deposit_sol(&self, lamports: u64) -> Result<DepositSolQuote, MarinadeError> {
+        self.checks.deposit_sol(lamports)?;
+
+        let quote = self
+            .quote_deposit_sol_unchecked(lamports)
+            .ok_or(MarinadeError::CalculationFailure)?;
+
+        if self.exceeds_staking_cap(&quote) {
+            return Err(MarinadeError::StakingIsCapped);
+        }
+
+        Ok(quote)
+    }
+
+    #[inline]
+    pub const fn quote_deposit_stake_unchecked(
+        &self,
+        stake_account_lamports: StakeAccountLamports,
+    ) -> Option<DepositStakeQuote> {
+        quote::deposit_stake_unchecked(self.supply_over_lamports, stake_account_lamports)
+    }
+
+    #[inline]
+    pub fn quote_deposit_stake(
+        &self,
+        stake_account_lamports: StakeAccountLamports,
+    ) -> Result<DepositStakeQuote, MarinadeError> {
+        self.checks.deposit_stake(stake_account_lamports.staked)?;
+
+        if self.will_deposit_exceed_staking_cap(stake_account_lamports.staked)? {
+            return Err(MarinadeError::StakingIsCapped);
+        }
+
+        self.quote_deposit_stake_unchecked(stake_account_lamports)
+            .ok_or(MarinadeError::CalculationFailure)
+    }
+
+    #[inline]
+    pub const fn quote_withdraw_stake_unchecked(
+        &self,
+        pool_tokens: u64,
+    ) -> Option<WithdrawStakeQuote> {
+        let Some(withdraw_stake_account_fee) = self.withdraw_stake_account_fee else {
+            return None;
+        };
+        quote::withdraw_stake_unchecked(
+            self.supply_over_lamports,
+            self.lamports_over_supply,
+            withdraw_stake_account_fee,
+            pool_tokens,
+        )
+    }
+
+    #[inline]
+    pub fn quote_withdraw_stake(
+        &self,
+        pool_tokens: u64,
+        args: WithdrawStakeQuoteArgs,
+    ) -> Result<WithdrawStakeQuote, MarinadeError> {
+        self.checks.withdraw_stake(&args)?;
+
+        let quote = self
+            .quote_withdraw_stake_unchecked(pool_tokens)
+            .ok_or(MarinadeError::CalculationFailure)?;
+
+        self.checks
+            .withdraw_stake_quote(&quote, args.stake_record.last_update_delegated_lamports())?;
+
+        Ok(quote)
+    }
+
+    #[inline]
+    pub const fn liquid_unstake_fee(&self, lamports_after: u64) -> Option<Fee> {
+        self.liquid_unstake_fees.fee(lamports_after)
+    }
+
+    #[inline]
+    pub const fn quote_liquid_unstake_unchecked(
+        &self,
+        pool_tokens: u64,
+        args: LiquidUnstakeQuoteArgs,
+    ) -> Option<LiquidUnstakeQuote> {
+        quote::liquid_unstake_unchecked(
+            self.lamports_over_supply,
+            &self.liquid_unstake_fees,
+            self.rent_exempt_for_token_acc,
+            args.sol_leg_lamports,
+            pool_tokens,
+        )
+    }
+
+    #[inline]
+    pub fn quote_liquid_unstake(
+        &self,
+        pool_tokens: u64,
+        args: LiquidUnstakeQuoteArgs,
+    ) -> Result<LiquidUnstakeQuote, MarinadeError> {
+        self.checks.liquid_unstake()?;
+
+        let quote = self
+            .quote_liquid_unstake_unchecked(pool_tokens, args)
+            .ok_or(MarinadeError::CalculationFailure)?;
+
+        self.checks.liquid_unstake_quote(
+            &quote,
+            args.sol_leg_lamports,
+            self.rent_exempt_for_token_acc,
+        )?;
+
+        Ok(quote)
+    }
+}
+
+/// Exact-out quotes, see the [`State`] functions of the same name
+impl QuoteContext {
+    #[inline]
+    pub const fn quote_deposit_sol_exact_out_unchecked(
+        &self,
+        msol_out: u64,
+    ) -> Option<DepositSolQuote> {
+        quote::deposit_sol_exact_out_unchecked(
+            self.supply_over_lamports,
+            self.lamports_over_supply,
+            self.msol_leg_balance,
+            msol_out,
+        )
+    }
+
+    #[inline]
+    pub fn quote_deposit_sol_exact_out(
+        &self,
+        msol_out: u64,
+    ) -> Result<DepositSolQuote, MarinadeError> {
+        let quote = self
+            .quote_deposit_sol_exact_out_unchecked(msol_out)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        self.quote_deposit_sol(quote.in_amount)
+    }
+
+    #[inline]
+    pub const fn quote_deposit_stake_exact_out_unchecked(
+        &self,
+        tokens_out: u64,
+        unstaked: u64,
+    ) -> Option<DepositStakeQuote> {
+        quote::deposit_stake_exact_out_unchecked(
+            self.supply_over_lamports,
+            self.lamports_over_supply,
+            tokens_out,
+            unstaked,
+        )
+    }
+
+    #[inline]
+    pub fn quote_deposit_stake_exact_out(
+        &self,
+        tokens_out: u64,
+        unstaked: u64,
+    ) -> Result<DepositStakeQuote, MarinadeError> {
+        let quote = self
+            .quote_deposit_stake_exact_out_unchecked(tokens_out, unstaked)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        self.quote_deposit_stake(quote.stake_account_lamports_in)
+    }
+
+    #[inline]
+    pub const fn quote_withdraw_stake_exact_out_unchecked(
+        &self,
+        lamports_staked: u64,
+    ) -> Option<WithdrawStakeQuote> {
+        let Some(withdraw_stake_account_fee) = self.withdraw_stake_account_fee else {
+            return None;
+        };
+        quote::withdraw_stake_exact_out_unchecked(
+            self.supply_over_lamports,
+            self.lamports_over_supply,
+            withdraw_stake_account_fee,
+            lamports_staked,
+        )
+    }
+
+    #[inline]
+    pub fn quote_withdraw_stake_exact_out(
+        &self,
+        lamports_staked: u64,
+        args: WithdrawStakeQuoteArgs,
+    ) -> Result<WithdrawStakeQuote, MarinadeError> {
+        let quote = self
+            .quote_withdraw_stake_exact_out_unchecked(lamports_staked)
+            .ok_or(MarinadeError::CalculationFailure)?;
+        self.quote_withdraw_stake(quote.tokens_in, args)
+    }
+}
+
+impl State {
+    /// See [`QuoteContext::new`]
+    #[inline]
+    pub const fn quote_context(&self, msol_leg_balance: u64) -> Option<QuoteContext> {
+        QuoteContext::new(self, msol_leg_balance)
+    }
+}
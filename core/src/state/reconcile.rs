@@ -0,0 +1,89 @@
+use crate::State;
+
+/// Real on-chain values tracked by the virtual [`State`] fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReconcileArgs {
+    /// The reserve PDA's lamports
+    pub reserve_lamports: u64,
+
+    /// The mSOL mint's supply
+    pub msol_mint_supply: u64,
+}
+
+/// Drift between the virtual [`State`] fields and their real values,
+/// and the values the next update instruction sets them to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reconciliation {
+    /// Real reserve balance minus `available_reserve_balance`,
+    /// positive if lamports were transferred into the reserve
+    pub reserve_drift: i128,
+
+    /// Real mSOL supply minus `msol_supply`,
+    /// negative if mSOL was burnt, positive if mSOL was minted outside the program
+    pub msol_supply_drift: i128,
+
+    pub available_reserve_balance: u64,
+    pub msol_supply: u64,
+
+    /// 0 if mSOL was minted outside the program, which pauses staking
+    pub staking_sol_cap: u64,
+}
+
+impl Reconciliation {
+    #[inline]
+    pub const fn is_drifted(&self) -> bool {
+        self.reserve_drift != 0 || self.msol_supply_drift != 0
+    }
+}
+
+/// Realignment of the virtual values, same as what every update instruction
+/// (`update_active`, `update_deactivated`) does before processing its stake account
+impl State {
+    /// The rent-exempt amount subtracted from `reserve_lamports` is `rent_exempt_for_token_acc`,
+    /// same as the program
+    #[inline]
+    pub fn reconcile(
+        &self,
+        ReconcileArgs {
+            reserve_lamports,
+            msol_mint_supply,
+        }: ReconcileArgs,
+    ) -> Reconciliation {
+        let available_reserve_balance =
+            reserve_lamports.saturating_sub(self.rent_exempt_for_token_acc);
+        Reconciliation {
+            reserve_drift: i128::from(available_reserve_balance)
+                - i128::from(self.available_reserve_balance),
+            msol_supply_drift: i128::from(msol_mint_supply) - i128::from(self.msol_supply),
+            available_reserve_balance,
+            msol_supply: msol_mint_supply,
+            staking_sol_cap: if msol_mint_supply > self.msol_supply {
+                0
+            } else {
+                self.staking_sol_cap
+            },
+        }
+    }
+
+    /// The state with its virtual values realigned to `args`.
+    ///
+    /// Quote against this instead of `self` for amounts that match
+    /// what the program computes after the next update instruction.
+    #[inline]
+    pub fn reconciled(&self, args: ReconcileArgs) -> Self {
+        let Reconciliation {
+            available_reserve_balance,
+            msol_supply,
+            staking_sol_cap,
+            ..
+        } = self.reconcile(args);
+        Self {
+            available_reserve_balance,
+            msol_supply,
+            staking_sol_cap,
+            ..self.clone()
+        }
+    }
+}
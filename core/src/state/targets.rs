@@ -0,0 +1,93 @@
+use crate::{State, ValidatorList, ValidatorRecord};
+
+/// A validator's score-proportional stake target against its current active balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidatorStakeTarget {
+    pub validator_index: u32,
+
+    /// Target lamports from the validator's share of `total_validator_score`
+    pub target: u64,
+
+    /// The validator record's `active_balance`
+    pub active_balance: u64,
+
+    /// `active_balance - target`.
+    ///
+    /// Positive if overstaked, negative if understaked
+    pub delta: i128,
+}
+
+/// Stake targets, same as the program's `stake_reserve` and `partial_unstake` instructions
+impl State {
+    /// Lamports available for staking (positive) or that need to be unstaked (negative)
+    /// given the reserve PDA's current lamports `reserve_balance`.
+    ///
+    /// `emergency_cooling_down` is only counted when unstaking.
+    #[inline]
+    pub fn stake_delta(&self, reserve_balance: u64) -> i128 {
+        let raw = i128::from(reserve_balance.saturating_sub(self.rent_exempt_for_token_acc))
+            + i128::from(self.stake_system.delayed_unstake_cooling_down)
+            - i128::from(self.circulating_ticket_balance);
+        if raw >= 0 {
+            raw
+        } else {
+            (raw + i128::from(self.emergency_cooling_down)).min(0)
+        }
+    }
+
+    /// `total_active_balance` adjusted by [`Self::stake_delta`]
+    #[inline]
+    pub fn total_stake_target(&self, reserve_balance: u64) -> u64 {
+        let total_active_balance = self.validator_system.total_active_balance;
+        let stake_delta = self.stake_delta(reserve_balance);
+        let abs = u64::try_from(stake_delta.unsigned_abs()).unwrap_or(u64::MAX);
+        if stake_delta >= 0 {
+            total_active_balance.saturating_add(abs)
+        } else {
+            total_active_balance.saturating_sub(abs)
+        }
+    }
+
+    /// The validator's share of `total_stake_target` by score, 0 if `total_validator_score` is 0
+    #[inline]
+    pub fn validator_stake_target(
+        &self,
+        validator_record: &ValidatorRecord,
+        total_stake_target: u64,
+    ) -> u64 {
+        let total_score = self.validator_system.total_validator_score;
+        if total_score == 0 {
+            return 0;
+        }
+        let target = u128::from(total_stake_target) * u128::from(validator_record.score())
+            / u128::from(total_score);
+        // saturate for inconsistent states where score > total_validator_score
+        u64::try_from(target).unwrap_or(u64::MAX)
+    }
+
+    /// [`ValidatorStakeTarget`]s of every validator in `validator_list`
+    /// given the reserve PDA's current lamports `reserve_balance`
+    #[inline]
+    pub fn validator_stake_targets<'a>(
+        &'a self,
+        validator_list: &'a ValidatorList<'a>,
+        reserve_balance: u64,
+    ) -> impl Iterator<Item = ValidatorStakeTarget> + 'a {
+        let total_stake_target = self.total_stake_target(reserve_balance);
+        validator_list
+            .0
+            .iter()
+            .zip(0..)
+            .map(move |(validator_record, validator_index)| {
+                let target = self.validator_stake_target(validator_record, total_stake_target);
+                let active_balance = validator_record.active_balance();
+                ValidatorStakeTarget {
+                    validator_index,
+                    target,
+                    active_balance,
+                    delta: i128::from(active_balance) - i128::from(target),
+                }
+            })
+    }
+}
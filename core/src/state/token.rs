@@ -0,0 +1,63 @@
+use crate::{
+    liq_pool_msol_leg_authority, DepositSolQuoteArgs, DepositStakeQuoteArgs, MarinadeError, State,
+    TokenAccount,
+};
+
+impl State {
+    /// Balance of the liquidity pool's mSOL leg from the data of
+    /// the token account at [`crate::LiqPool::msol_leg`], e.g. [`crate::LIQ_POOL_MSOL_LEG_PUBKEY`].
+    ///
+    /// Checks that its mint is `msol_mint` and its owner is the mSOL leg authority PDA
+    /// of this state account at `state_pubkey`, e.g. [`crate::STATE_PUBKEY`].
+    #[inline]
+    pub fn msol_leg_balance(
+        &self,
+        state_pubkey: &[u8; 32],
+        msol_leg_data: &[u8],
+    ) -> Result<u64, MarinadeError> {
+        let TokenAccount {
+            mint,
+            owner,
+            amount,
+            ..
+        } = TokenAccount::try_from_acc_data(msol_leg_data)
+            .ok_or(MarinadeError::InvalidTokenAccount)?;
+        if mint != self.msol_mint {
+            return Err(MarinadeError::WrongTokenMint);
+        }
+        if liq_pool_msol_leg_authority(state_pubkey, self.liq_pool.msol_leg_authority_bump_seed)
+            != Some(owner)
+        {
+            return Err(MarinadeError::WrongTokenOwner);
+        }
+        Ok(amount)
+    }
+}
+
+impl DepositSolQuoteArgs {
+    /// See [`State::msol_leg_balance`]
+    #[inline]
+    pub fn try_from_msol_leg(
+        state: &State,
+        state_pubkey: &[u8; 32],
+        msol_leg_data: &[u8],
+    ) -> Result<Self, MarinadeError> {
+        state
+            .msol_leg_balance(state_pubkey, msol_leg_data)
+            .map(|msol_leg_balance| Self { msol_leg_balance })
+    }
+}
+
+impl DepositStakeQuoteArgs {
+    /// See [`State::msol_leg_balance`]
+    #[inline]
+    pub fn try_from_msol_leg(
+        state: &State,
+        state_pubkey: &[u8; 32],
+        msol_leg_data: &[u8],
+    ) -> Result<Self, MarinadeError> {
+        state
+            .msol_leg_balance(state_pubkey, msol_leg_data)
+            .map(|msol_leg_balance| Self { msol_leg_balance })
+    }
+}
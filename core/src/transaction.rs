@@ -0,0 +1,227 @@
+use alloc::vec::Vec;
+
+use crate::{
+    deposit_sol_ix, deposit_stake_ix, withdraw_stake_ix, CreateAtaIdempotentIxData,
+    CreateAtaIdempotentIxKeysOwned, DepositSolQuote, DepositSolQuoteArgs, DepositStakeIxArgs,
+    DepositStakeQuote, DynInstruction, MarinadeError, SetComputeUnitLimitIxData,
+    SetComputeUnitPriceIxData, State, ValidatorList, WithdrawStakeCandidate, WithdrawStakeQuote,
+};
+
+// Default compute unit limits, with headroom over what the mainnet program consumes
+
+pub const DEPOSIT_SOL_COMPUTE_UNITS: u32 = 80_000;
+pub const DEPOSIT_STAKE_COMPUTE_UNITS: u32 = 100_000;
+pub const WITHDRAW_STAKE_COMPUTE_UNITS: u32 = 150_000;
+pub const CREATE_ATA_COMPUTE_UNITS: u32 = 30_000;
+
+/// What a [`MarinadeTransactionBuilder`]'s transaction does for its user
+#[derive(Debug, Clone, Copy)]
+pub enum MarinadeIntent<'a> {
+    /// See [`deposit_sol_ix`]
+    DepositSol {
+        lamports: u64,
+        args: DepositSolQuoteArgs,
+    },
+
+    /// See [`deposit_stake_ix`]
+    DepositStake {
+        validator_list: &'a ValidatorList<'a>,
+        stake_account: [u8; 32],
+        args: DepositStakeIxArgs<'a>,
+    },
+
+    /// See [`withdraw_stake_ix`].
+    ///
+    /// `split_stake_account` is a fresh keypair's pubkey that must sign the transaction
+    WithdrawStake {
+        candidate: WithdrawStakeCandidate,
+        pool_tokens: u64,
+        split_stake_account: [u8; 32],
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarinadeIntentQuote {
+    DepositSol(DepositSolQuote),
+    DepositStake(DepositStakeQuote),
+    WithdrawStake(WithdrawStakeQuote),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarinadeTransaction {
+    /// Compute budget instructions, then the creation of the user's mSOL
+    /// associated token account if any, then the Marinade instruction
+    pub instructions: Vec<DynInstruction>,
+
+    /// Every signer of `instructions`, starting with the fee payer.
+    /// Empty only if constructed by hand
+    pub signers: Vec<[u8; 32]>,
+
+    pub quote: MarinadeIntentQuote,
+}
+
+impl MarinadeTransaction {
+    #[inline]
+    pub fn fee_payer(&self) -> Option<&[u8; 32]> {
+        self.signers.first()
+    }
+}
+
+/// Builds the instructions of a transaction carrying out a [`MarinadeIntent`] for `user`
+/// against the mainnet stake pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarinadeTransactionBuilder {
+    pub user: [u8; 32],
+
+    /// Pays for the transaction and the mSOL associated token account's rent,
+    /// defaults to `user`
+    pub fee_payer: Option<[u8; 32]>,
+
+    /// Defaults to the sum of the `*_COMPUTE_UNITS` consts of the instructions
+    pub compute_unit_limit: Option<u32>,
+
+    /// Priority fee in micro-lamports per compute unit
+    pub compute_unit_price: u64,
+
+    /// Create the user's mSOL associated token account before deposits
+    /// if it does not exist yet. Defaults to `true`
+    pub create_msol_ata: bool,
+}
+
+impl MarinadeTransactionBuilder {
+    #[inline]
+    pub const fn new(user: [u8; 32]) -> Self {
+        Self {
+            user,
+            fee_payer: None,
+            compute_unit_limit: None,
+            compute_unit_price: 0,
+            create_msol_ata: true,
+        }
+    }
+
+    #[inline]
+    pub const fn with_fee_payer(mut self, fee_payer: [u8; 32]) -> Self {
+        self.fee_payer = Some(fee_payer);
+        self
+    }
+
+    #[inline]
+    pub const fn with_compute_unit_limit(mut self, compute_unit_limit: u32) -> Self {
+        self.compute_unit_limit = Some(compute_unit_limit);
+        self
+    }
+
+    #[inline]
+    pub const fn with_compute_unit_price(mut self, compute_unit_price: u64) -> Self {
+        self.compute_unit_price = compute_unit_price;
+        self
+    }
+
+    #[inline]
+    pub const fn with_create_msol_ata(mut self, create_msol_ata: bool) -> Self {
+        self.create_msol_ata = create_msol_ata;
+        self
+    }
+
+    pub fn build(
+        &self,
+        state: &State,
+        intent: MarinadeIntent,
+    ) -> Result<MarinadeTransaction, MarinadeError> {
+        let fee_payer = self.fee_payer.unwrap_or(self.user);
+
+        let (ix, quote, is_deposit, compute_units): (DynInstruction, _, _, _) = match intent {
+            MarinadeIntent::DepositSol { lamports, args } => {
+                let (ix, quote) = deposit_sol_ix(state, self.user, lamports, args)?;
+                (
+                    ix.into(),
+                    MarinadeIntentQuote::DepositSol(quote),
+                    true,
+                    DEPOSIT_SOL_COMPUTE_UNITS,
+                )
+            }
+            MarinadeIntent::DepositStake {
+                validator_list,
+                stake_account,
+                args,
+            } => {
+                let (ix, quote) =
+                    deposit_stake_ix(state, validator_list, self.user, stake_account, args)?;
+                (
+                    ix.into(),
+                    MarinadeIntentQuote::DepositStake(quote),
+                    true,
+                    DEPOSIT_STAKE_COMPUTE_UNITS,
+                )
+            }
+            MarinadeIntent::WithdrawStake {
+                candidate,
+                pool_tokens,
+                split_stake_account,
+            } => {
+                let (ix, quote) = withdraw_stake_ix(
+                    state,
+                    &candidate,
+                    self.user,
+                    split_stake_account,
+                    pool_tokens,
+                )?;
+                (
+                    ix.into(),
+                    MarinadeIntentQuote::WithdrawStake(quote),
+                    false,
+                    WITHDRAW_STAKE_COMPUTE_UNITS,
+                )
+            }
+        };
+
+        let create_ata = (is_deposit && self.create_msol_ata).then(|| {
+            CreateAtaIdempotentIxKeysOwned::default()
+                .with_consts()
+                .with_funding(fee_payer)
+                .with_ata_of(self.user, state.msol_mint)
+                .to_ix(&CreateAtaIdempotentIxData::new())
+                .into()
+        });
+        let compute_unit_limit = self.compute_unit_limit.unwrap_or(
+            compute_units
+                + if create_ata.is_some() {
+                    CREATE_ATA_COMPUTE_UNITS
+                } else {
+                    0
+                },
+        );
+
+        let mut instructions = Vec::with_capacity(4);
+        instructions.push(
+            SetComputeUnitLimitIxData::new(compute_unit_limit)
+                .to_ix()
+                .into(),
+        );
+        instructions.push(
+            SetComputeUnitPriceIxData::new(self.compute_unit_price)
+                .to_ix()
+                .into(),
+        );
+        instructions.extend(create_ata);
+        instructions.push(ix);
+
+        let mut signers = Vec::from([fee_payer]);
+        instructions
+            .iter()
+            .flat_map(|ix| ix.accounts.iter())
+            .filter(|meta| meta.is_signer)
+            .for_each(|meta| {
+                if !signers.contains(&meta.pubkey) {
+                    signers.push(meta.pubkey);
+                }
+            });
+
+        Ok(MarinadeTransaction {
+            instructions,
+            signers,
+            quote,
+        })
+    }
+}
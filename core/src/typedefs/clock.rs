@@ -0,0 +1,16 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Clock sysvar account data
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clock {
+    pub slot: u64,
+    pub epoch_start_timestamp: i64,
+    pub epoch: u64,
+    pub leader_schedule_epoch: u64,
+    pub unix_timestamp: i64,
+}
+
+impl Clock {
+    inherent_borsh_serde!();
+}
@@ -1,13 +1,21 @@
+mod clock;
 mod fee;
 mod fee_cents;
 mod liq_pool;
 mod list;
+mod stake_history;
+mod stake_state;
 mod stake_system;
+mod token;
 mod validator_system;
 
+pub use clock::*;
 pub use fee::*;
 pub use fee_cents::*;
 pub use liq_pool::*;
 pub use list::*;
+pub use stake_history::*;
+pub use stake_state::*;
 pub use stake_system::*;
+pub use token::*;
 pub use validator_system::*;
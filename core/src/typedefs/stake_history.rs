@@ -0,0 +1,73 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::state::list::ListAccount;
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[repr(C)]
+pub struct StakeHistoryEntry {
+    epoch: [u8; 8],
+    effective: [u8; 8],
+    activating: [u8; 8],
+    deactivating: [u8; 8],
+}
+
+impl StakeHistoryEntry {
+    #[inline]
+    pub const fn new(epoch: u64, effective: u64, activating: u64, deactivating: u64) -> Self {
+        Self {
+            epoch: epoch.to_le_bytes(),
+            effective: effective.to_le_bytes(),
+            activating: activating.to_le_bytes(),
+            deactivating: deactivating.to_le_bytes(),
+        }
+    }
+
+    #[inline]
+    pub const fn epoch(&self) -> u64 {
+        u64::from_le_bytes(self.epoch)
+    }
+
+    /// effective stake at this epoch
+    #[inline]
+    pub const fn effective(&self) -> u64 {
+        u64::from_le_bytes(self.effective)
+    }
+
+    /// sum of portion of stakes not fully warmed up
+    #[inline]
+    pub const fn activating(&self) -> u64 {
+        u64::from_le_bytes(self.activating)
+    }
+
+    /// requested to be cooled down, not fully deactivated yet
+    #[inline]
+    pub const fn deactivating(&self) -> u64 {
+        u64::from_le_bytes(self.deactivating)
+    }
+}
+
+/// Zero-copy view of the StakeHistory sysvar's account data,
+/// entries are sorted by epoch in descending order
+#[derive(Debug, Clone, Copy)]
+pub struct StakeHistory<'a>(pub &'a [StakeHistoryEntry]);
+
+impl<'a> StakeHistory<'a> {
+    /// Account data is a u64 little-endian entry count followed by the entries
+    #[inline]
+    pub fn try_from_acc_data(data: &'a [u8]) -> Option<Self> {
+        let len = u64::from_le_bytes(*data.first_chunk()?);
+        if len == 0 {
+            return Some(Self(&[]));
+        }
+        let ListAccount(entries) = ListAccount::try_from_acc_data(data, len.try_into().ok()?)?;
+        Some(Self(entries))
+    }
+
+    #[inline]
+    pub fn get(&self, epoch: u64) -> Option<&StakeHistoryEntry> {
+        self.0
+            .binary_search_by(|probe| epoch.cmp(&probe.epoch()))
+            .ok()
+            .map(|i| &self.0[i])
+    }
+}
@@ -0,0 +1,201 @@
+use borsh::{
+    io::{Error, ErrorKind, Read, Write},
+    BorshDeserialize, BorshSerialize,
+};
+
+/// Native stake program account data.
+///
+/// Unlike borsh enums, the discriminant is serialized as a u32
+/// to match the stake program's bincode layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StakeStateV2 {
+    Uninitialized,
+    Initialized(Meta),
+    Stake(Meta, Stake, StakeFlags),
+    RewardsPool,
+}
+
+impl StakeStateV2 {
+    pub const ACCOUNT_LEN: usize = 200;
+
+    const UNINITIALIZED: u32 = 0;
+    const INITIALIZED: u32 = 1;
+    const STAKE: u32 = 2;
+    const REWARDS_POOL: u32 = 3;
+
+    #[inline]
+    pub const fn meta(&self) -> Option<&Meta> {
+        match self {
+            Self::Initialized(meta) | Self::Stake(meta, _, _) => Some(meta),
+            Self::Uninitialized | Self::RewardsPool => None,
+        }
+    }
+
+    #[inline]
+    pub const fn stake(&self) -> Option<&Stake> {
+        match self {
+            Self::Stake(_, stake, _) => Some(stake),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub const fn delegation(&self) -> Option<&Delegation> {
+        match self.stake() {
+            Some(stake) => Some(&stake.delegation),
+            None => None,
+        }
+    }
+}
+
+impl BorshSerialize for StakeStateV2 {
+    fn serialize<W: Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        match self {
+            Self::Uninitialized => Self::UNINITIALIZED.serialize(writer),
+            Self::Initialized(meta) => {
+                Self::INITIALIZED.serialize(writer)?;
+                meta.serialize(writer)
+            }
+            Self::Stake(meta, stake, flags) => {
+                Self::STAKE.serialize(writer)?;
+                meta.serialize(writer)?;
+                stake.serialize(writer)?;
+                flags.serialize(writer)
+            }
+            Self::RewardsPool => Self::REWARDS_POOL.serialize(writer),
+        }
+    }
+}
+
+impl BorshDeserialize for StakeStateV2 {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        match u32::deserialize_reader(reader)? {
+            Self::UNINITIALIZED => Ok(Self::Uninitialized),
+            Self::INITIALIZED => Ok(Self::Initialized(Meta::deserialize_reader(reader)?)),
+            Self::STAKE => Ok(Self::Stake(
+                Meta::deserialize_reader(reader)?,
+                Stake::deserialize_reader(reader)?,
+                StakeFlags::deserialize_reader(reader)?,
+            )),
+            Self::REWARDS_POOL => Ok(Self::RewardsPool),
+            _ => Err(Error::from(ErrorKind::InvalidData)),
+        }
+    }
+}
+
+impl StakeStateV2 {
+    inherent_borsh_serde!();
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Meta {
+    pub rent_exempt_reserve: u64,
+    pub authorized: Authorized,
+    pub lockup: Lockup,
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Authorized {
+    pub staker: [u8; 32],
+    pub withdrawer: [u8; 32],
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lockup {
+    /// UnixTimestamp at which this stake will allow withdrawal, unless the
+    /// transaction is signed by the custodian
+    pub unix_timestamp: i64,
+
+    /// epoch height at which this stake will allow withdrawal, unless the
+    /// transaction is signed by the custodian
+    pub epoch: u64,
+
+    /// custodian signature on a transaction exempts the operation from
+    /// lockup constraints
+    pub custodian: [u8; 32],
+}
+
+impl Lockup {
+    /// Same as the stake program's check, the lockup is in force
+    /// if either the timestamp or epoch has not yet passed
+    #[inline]
+    pub const fn is_in_force(&self, unix_timestamp: i64, epoch: u64) -> bool {
+        self.unix_timestamp > unix_timestamp || self.epoch > epoch
+    }
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stake {
+    pub delegation: Delegation,
+
+    /// credits observed is credits from vote account state when delegated or redeemed
+    pub credits_observed: u64,
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Delegation {
+    /// to whom the stake is delegated
+    pub voter_pubkey: [u8; 32],
+
+    /// activated stake amount, set at delegate() time
+    pub stake: u64,
+
+    /// epoch at which this stake was activated, u64::MAX if is a bootstrap stake
+    pub activation_epoch: u64,
+
+    /// epoch the stake was deactivated, u64::MAX if not deactivated
+    pub deactivation_epoch: u64,
+
+    /// DEPRECATED, f64 stored as its little-endian bytes
+    /// so that this struct can remain `Eq`
+    warmup_cooldown_rate: [u8; 8],
+}
+
+impl Delegation {
+    #[inline]
+    pub const fn new(
+        voter_pubkey: [u8; 32],
+        stake: u64,
+        activation_epoch: u64,
+        deactivation_epoch: u64,
+    ) -> Self {
+        Self {
+            voter_pubkey,
+            stake,
+            activation_epoch,
+            deactivation_epoch,
+            warmup_cooldown_rate: DEFAULT_WARMUP_COOLDOWN_RATE.to_le_bytes(),
+        }
+    }
+
+    #[inline]
+    pub fn warmup_cooldown_rate(&self) -> f64 {
+        f64::from_le_bytes(self.warmup_cooldown_rate)
+    }
+
+    #[inline]
+    pub const fn is_deactivated(&self) -> bool {
+        self.deactivation_epoch != u64::MAX
+    }
+}
+
+/// Value written to the deprecated [`Delegation`] field by the stake program
+pub const DEFAULT_WARMUP_COOLDOWN_RATE: f64 = 0.25;
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StakeFlags {
+    pub bits: u8,
+}
+
+impl StakeFlags {
+    pub const EMPTY: Self = Self { bits: 0 };
+
+    pub const MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED: Self = Self { bits: 1 };
+}
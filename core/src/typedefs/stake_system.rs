@@ -49,6 +49,22 @@ pub struct StakeRecord {
 }
 
 impl StakeRecord {
+    #[inline]
+    pub const fn new(
+        stake_account: [u8; 32],
+        last_update_delegated_lamports: u64,
+        last_update_epoch: u64,
+        is_emergency_unstaking: bool,
+    ) -> Self {
+        Self {
+            stake_account,
+            last_update_delegated_lamports: last_update_delegated_lamports.to_le_bytes(),
+            last_update_epoch: last_update_epoch.to_le_bytes(),
+            is_emergency_unstaking: is_emergency_unstaking as u8,
+            additional_record_space: [0u8; 7],
+        }
+    }
+
     #[inline]
     pub fn stake_account(&self) -> &[u8; 32] {
         &self.stake_account
@@ -68,4 +84,13 @@ impl StakeRecord {
     pub fn is_emergency_unstaking(&self) -> bool {
         self.is_emergency_unstaking == 1
     }
+
+    #[inline]
+    pub const fn with_last_update_delegated_lamports(
+        mut self,
+        last_update_delegated_lamports: u64,
+    ) -> Self {
+        self.last_update_delegated_lamports = last_update_delegated_lamports.to_le_bytes();
+        self
+    }
 }
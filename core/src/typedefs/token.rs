@@ -0,0 +1,86 @@
+/// Size of an SPL token account, token-2022 accounts may be longer due to extensions
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Size of an SPL mint, token-2022 mints may be longer due to extensions
+pub const MINT_LEN: usize = 82;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TokenAccountState {
+    Initialized = 1,
+    Frozen = 2,
+}
+
+/// The fields of an SPL token account needed for quoting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenAccount {
+    pub mint: [u8; 32],
+    pub owner: [u8; 32],
+    pub amount: u64,
+    pub delegate: Option<[u8; 32]>,
+    pub state: TokenAccountState,
+}
+
+impl TokenAccount {
+    /// Returns `None` if `data` is too short, has invalid option tags or
+    /// is not an initialized token account
+    #[inline]
+    pub fn try_from_acc_data(data: &[u8]) -> Option<Self> {
+        let data: &[u8; TOKEN_ACCOUNT_LEN] = data.first_chunk()?;
+        let state = match data[108] {
+            1 => TokenAccountState::Initialized,
+            2 => TokenAccountState::Frozen,
+            _ => return None,
+        };
+        Some(Self {
+            mint: *subarr(data, 0)?,
+            owner: *subarr(data, 32)?,
+            amount: u64::from_le_bytes(*subarr(data, 64)?),
+            delegate: coption_pubkey(subarr(data, 72)?)?,
+            state,
+        })
+    }
+}
+
+/// The fields of an SPL mint needed for quoting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mint {
+    pub mint_authority: Option<[u8; 32]>,
+    pub supply: u64,
+    pub decimals: u8,
+}
+
+impl Mint {
+    /// Returns `None` if `data` is too short, has invalid option tags or
+    /// is not an initialized mint
+    #[inline]
+    pub fn try_from_acc_data(data: &[u8]) -> Option<Self> {
+        let data: &[u8; MINT_LEN] = data.first_chunk()?;
+        if data[45] != 1 {
+            return None;
+        }
+        Some(Self {
+            mint_authority: coption_pubkey(subarr(data, 0)?)?,
+            supply: u64::from_le_bytes(*subarr(data, 36)?),
+            decimals: data[44],
+        })
+    }
+}
+
+fn subarr<const M: usize>(data: &[u8], start: usize) -> Option<&[u8; M]> {
+    data.get(start..)?.first_chunk()
+}
+
+/// `COption<Pubkey>`: u32 tag followed by the pubkey.
+///
+/// Returns `None` for an invalid tag
+fn coption_pubkey(data: &[u8; 36]) -> Option<Option<[u8; 32]>> {
+    match u32::from_le_bytes(*subarr(data, 0)?) {
+        0 => Some(None),
+        1 => Some(Some(*subarr(data, 4)?)),
+        _ => None,
+    }
+}
@@ -39,6 +39,24 @@ pub struct ValidatorRecord {
 }
 
 impl ValidatorRecord {
+    #[inline]
+    pub const fn new(
+        validator_account: [u8; 32],
+        active_balance: u64,
+        score: u32,
+        last_stake_delta_epoch: u64,
+        duplication_flag_bump_seed: u8,
+    ) -> Self {
+        Self {
+            validator_account,
+            active_balance: active_balance.to_le_bytes(),
+            score: score.to_le_bytes(),
+            last_stake_delta_epoch: last_stake_delta_epoch.to_le_bytes(),
+            duplication_flag_bump_seed,
+            additional_record_space: [0u8; 8],
+        }
+    }
+
     #[inline]
     pub fn validator_account(&self) -> &[u8; 32] {
         &self.validator_account
@@ -63,4 +81,10 @@ impl ValidatorRecord {
     pub fn duplication_flag_bump_seed(&self) -> u8 {
         self.duplication_flag_bump_seed
     }
+
+    #[inline]
+    pub const fn with_active_balance(mut self, active_balance: u64) -> Self {
+        self.active_balance = active_balance.to_le_bytes();
+        self
+    }
 }
@@ -0,0 +1,137 @@
+use crate::{
+    duplication_flag, preflight_deposit_stake, Clock, DepositIx, DepositIxData, DepositIxKeysOwned,
+    DepositSolQuote, DepositSolQuoteArgs, DepositStakeAccountIx, DepositStakeAccountIxData,
+    DepositStakeAccountIxKeysOwned, DepositStakeQuote, DepositStakeQuoteArgs, MarinadeError,
+    StakeAccountLamports, StakeHistory, StakeStateV2, State, ValidatorList, WithdrawStakeAccountIx,
+    WithdrawStakeAccountIxData, WithdrawStakeAccountIxKeysOwned, WithdrawStakeCandidate,
+    WithdrawStakeQuote, WithdrawStakeQuoteArgs, STATE_PUBKEY,
+};
+
+/// Deposits `lamports` from `user` into the mainnet stake pool,
+/// with the mSOL going to `user`'s mSOL associated token account, which must already exist.
+///
+/// Returns the instruction with the quote it was checked against.
+#[inline]
+pub fn deposit_sol_ix(
+    state: &State,
+    user: [u8; 32],
+    lamports: u64,
+    args: DepositSolQuoteArgs,
+) -> Result<(DepositIx, DepositSolQuote), MarinadeError> {
+    let quote = state.quote_deposit_sol(lamports, args)?;
+    let ix = DepositIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(state)
+        .with_transfer_from(user)
+        .with_mint_to_wallet(user)
+        .to_ix(&DepositIxData::new(lamports));
+    Ok((ix, quote))
+}
+
+/// The stake account and sysvars [`deposit_stake_ix`] checks the deposit against
+#[derive(Clone, Copy, Debug)]
+pub struct DepositStakeIxArgs<'a> {
+    /// `lamports` of the stake account
+    pub stake_account_lamports: u64,
+    pub stake_state: &'a StakeStateV2,
+    pub clock: &'a Clock,
+    pub stake_history: &'a StakeHistory<'a>,
+
+    /// See [`crate::activation_status`]
+    pub new_rate_activation_epoch: Option<u64>,
+
+    pub msol_leg_balance: u64,
+}
+
+/// Deposits `stake_account`, whose withdraw authority is `user`, into the mainnet stake pool,
+/// with the mSOL going to `user`'s mSOL associated token account, which must already exist.
+/// `user` also pays for the new stake record's rent.
+///
+/// Returns the instruction with the quote it was checked against,
+/// after [`preflight_deposit_stake`].
+#[inline]
+pub fn deposit_stake_ix(
+    state: &State,
+    validator_list: &ValidatorList,
+    user: [u8; 32],
+    stake_account: [u8; 32],
+    DepositStakeIxArgs {
+        stake_account_lamports,
+        stake_state,
+        clock,
+        stake_history,
+        new_rate_activation_epoch,
+        msol_leg_balance,
+    }: DepositStakeIxArgs,
+) -> Result<(DepositStakeAccountIx, DepositStakeQuote), MarinadeError> {
+    let validator_index = preflight_deposit_stake(
+        stake_state,
+        &user,
+        validator_list,
+        clock,
+        stake_history,
+        new_rate_activation_epoch,
+    )?;
+    let lamports = StakeAccountLamports::from_stake_account(stake_account_lamports, stake_state)
+        .ok_or(MarinadeError::RequiredDelegatedStake)?;
+    let quote = state.quote_deposit_stake(lamports, DepositStakeQuoteArgs { msol_leg_balance })?;
+
+    let validator_record = &validator_list.0[validator_index as usize];
+    let duplication_flag = duplication_flag(
+        &STATE_PUBKEY,
+        validator_record.validator_account(),
+        validator_record.duplication_flag_bump_seed(),
+    )
+    .ok_or(MarinadeError::WrongValidatorAccountOrIndex)?;
+
+    let ix = DepositStakeAccountIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(state)
+        .with_stake_account(stake_account)
+        .with_stake_authority(user)
+        .with_duplication_flag(duplication_flag)
+        .with_rent_payer(user)
+        .with_mint_to_wallet(user)
+        .to_ix(&DepositStakeAccountIxData::new(validator_index));
+    Ok((ix, quote))
+}
+
+/// Withdraws `pool_tokens` from `user`'s mSOL associated token account as a new stake account,
+/// `split_stake_account`, split from `candidate` in the mainnet stake pool.
+/// `user` is the new stake account's authority and pays for its rent.
+///
+/// `split_stake_account` must sign the transaction.
+///
+/// Returns the instruction with the quote it was checked against.
+#[inline]
+pub fn withdraw_stake_ix(
+    state: &State,
+    candidate: &WithdrawStakeCandidate,
+    user: [u8; 32],
+    split_stake_account: [u8; 32],
+    pool_tokens: u64,
+) -> Result<(WithdrawStakeAccountIx, WithdrawStakeQuote), MarinadeError> {
+    let quote = state.quote_withdraw_stake(
+        pool_tokens,
+        WithdrawStakeQuoteArgs {
+            stake_record: candidate.stake_record,
+        },
+    )?;
+    let ix = WithdrawStakeAccountIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(state)
+        .with_burn_msol_from_wallet(user)
+        .with_stake_account(*candidate.stake_record.stake_account())
+        .with_split_stake_account(split_stake_account)
+        .with_split_stake_rent_payer(user)
+        .to_ix(&WithdrawStakeAccountIxData::new(
+            candidate.stake_index,
+            candidate.validator_index,
+            pool_tokens,
+            user,
+        ));
+    Ok((ix, quote))
+}
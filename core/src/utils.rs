@@ -1,6 +1,6 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use crate::StakeRecord;
+use crate::{StakeRecord, StakeStateV2};
 
 #[derive(Clone, Copy, Debug)]
 pub struct DepositSolQuoteArgs {
@@ -16,8 +16,22 @@ pub struct DepositSolQuoteArgs {
 pub struct DepositSolQuote {
     /// Input SOL amount in lamports
     pub in_amount: u64,
-    /// Output mSOL amount in lamports (Marinade does not charge fees on deposits)
+    /// Output mSOL amount in lamports (Marinade does not charge fees on deposits).
+    ///
+    /// `msol_from_liq_pool + msol_minted`
     pub out_amount: u64,
+
+    /// mSOL transferred to the user from the liquidity pool's mSOL leg
+    pub msol_from_liq_pool: u64,
+
+    /// mSOL newly minted to the user
+    pub msol_minted: u64,
+
+    /// SOL transferred to the liquidity pool's SOL leg in exchange for `msol_from_liq_pool`
+    pub lamports_to_liq_pool: u64,
+
+    /// SOL transferred to the reserve in exchange for `msol_minted`
+    pub lamports_to_reserve: u64,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -35,6 +49,9 @@ pub struct DepositStakeQuote {
     /// Staked and unstaked lamports in the stake account
     pub stake_account_lamports_in: StakeAccountLamports,
 
+    /// Total lamports of the stake account, the sum of `stake_account_lamports_in`
+    pub total_lamports_in: u64,
+
     /// Output mSOL tokens (Marinade does not charge fees on deposits)
     pub tokens_out: u64,
 }
@@ -51,8 +68,31 @@ pub struct StakeAccountLamports {
 }
 
 impl StakeAccountLamports {
-    pub fn total(&self) -> u64 {
-        self.staked + self.unstaked
+    /// `None` if the sum overflows
+    pub const fn total(&self) -> Option<u64> {
+        self.staked.checked_add(self.unstaked)
+    }
+
+    /// `staked` is the delegated stake and `unstaked` is the rest of the account's `lamports`,
+    /// which includes `rent_exempt_reserve`.
+    ///
+    /// Returns `None` if the stake account is not delegated or if `lamports`
+    /// does not cover both the delegated stake and `rent_exempt_reserve`
+    #[inline]
+    pub const fn from_stake_account(lamports: u64, stake_state: &StakeStateV2) -> Option<Self> {
+        let (meta, stake) = match stake_state {
+            StakeStateV2::Stake(meta, stake, _) => (meta, stake),
+            _ => return None,
+        };
+        let staked = stake.delegation.stake;
+        let unstaked = match lamports.checked_sub(staked) {
+            Some(u) => u,
+            None => return None,
+        };
+        if unstaked < meta.rent_exempt_reserve {
+            return None;
+        }
+        Some(Self { staked, unstaked })
     }
 }
 
@@ -74,3 +114,29 @@ pub struct WithdrawStakeQuote {
     /// pool's manager fee destination
     pub fee_amount: u64,
 }
+
+#[derive(Clone, Copy, Debug)]
+pub struct LiquidUnstakeQuoteArgs {
+    /// Lamports of the liquidity pool's SOL leg account,
+    /// including `rent_exempt_for_token_acc`
+    pub sol_leg_lamports: u64,
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct LiquidUnstakeQuote {
+    pub tokens_in: u64,
+    /// SOL transferred to the user from the liquidity pool's SOL leg
+    pub lamports_out: u64,
+    /// fee is levied in mSOL and transferred to the liquidity pool's mSOL leg,
+    /// less `treasury_msol_cut`
+    pub fee_amount: u64,
+    /// Portion of `fee_amount` transferred to the treasury's mSOL account.
+    ///
+    /// The program skips this transfer if the treasury's mSOL account is invalid
+    pub treasury_msol_cut: u64,
+}
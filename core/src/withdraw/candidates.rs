@@ -0,0 +1,179 @@
+use core::cmp::Ordering;
+
+use crate::{
+    StakeList, StakeRecord, State, ValidatorList, ValidatorRecord, WithdrawStakeQuote,
+    WithdrawStakeQuoteArgs,
+};
+
+/// A stake record that a withdraw stake quote can be fulfilled from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawStakeCandidate {
+    /// `stake_index` for [`crate::WithdrawStakeAccountIxData::new`]
+    pub stake_index: u32,
+
+    /// `validator_index` for [`crate::WithdrawStakeAccountIxData::new`]
+    pub validator_index: u32,
+
+    pub stake_record: StakeRecord,
+    pub validator_record: ValidatorRecord,
+    pub quote: WithdrawStakeQuote,
+}
+
+impl State {
+    /// Stake records in `stake_list` that [`Self::quote_withdraw_stake`] of `pool_tokens` succeeds for,
+    /// in stake list order.
+    ///
+    /// The stake list does not record which validator each stake account is delegated to,
+    /// so `voter_of` must map a stake account pubkey to its delegation's vote account.
+    /// Stake accounts it returns `None` for, or whose vote account is not in `validator_list`, are skipped.
+    #[inline]
+    pub fn withdraw_stake_candidates<'a, F>(
+        &'a self,
+        validator_list: &'a ValidatorList<'a>,
+        stake_list: &'a StakeList<'a>,
+        voter_of: F,
+        pool_tokens: u64,
+    ) -> impl Iterator<Item = WithdrawStakeCandidate> + 'a
+    where
+        F: FnMut(&[u8; 32]) -> Option<[u8; 32]> + 'a,
+    {
+        withdraw_stake_sources(validator_list, stake_list, voter_of).filter_map(
+            move |(stake_index, validator_index, stake_record, validator_record)| {
+                let quote = self
+                    .quote_withdraw_stake(pool_tokens, WithdrawStakeQuoteArgs { stake_record })
+                    .ok()?;
+                Some(WithdrawStakeCandidate {
+                    stake_index,
+                    validator_index,
+                    stake_record,
+                    validator_record,
+                    quote,
+                })
+            },
+        )
+    }
+
+    /// Same as [`Self::withdraw_stake_candidates`], but each candidate is quoted for
+    /// the most mSOL its stake record can fulfill, as given by [`Self::max_withdraw_stake`]
+    #[inline]
+    pub fn max_withdraw_stake_candidates<'a, F>(
+        &'a self,
+        validator_list: &'a ValidatorList<'a>,
+        stake_list: &'a StakeList<'a>,
+        voter_of: F,
+    ) -> impl Iterator<Item = WithdrawStakeCandidate> + 'a
+    where
+        F: FnMut(&[u8; 32]) -> Option<[u8; 32]> + 'a,
+    {
+        withdraw_stake_sources(validator_list, stake_list, voter_of).filter_map(
+            move |(stake_index, validator_index, stake_record, validator_record)| {
+                let max = self.max_withdraw_stake(&stake_record).ok()?;
+                let quote = self
+                    .quote_withdraw_stake(max, WithdrawStakeQuoteArgs { stake_record })
+                    .ok()?;
+                Some(WithdrawStakeCandidate {
+                    stake_index,
+                    validator_index,
+                    stake_record,
+                    validator_record,
+                    quote,
+                })
+            },
+        )
+    }
+
+    /// [`Self::withdraw_stake_candidates`] sorted by `rank`, ties kept in stake list order
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn ranked_withdraw_stake_candidates<F, R>(
+        &self,
+        validator_list: &ValidatorList,
+        stake_list: &StakeList,
+        voter_of: F,
+        pool_tokens: u64,
+        rank: R,
+    ) -> alloc::vec::Vec<WithdrawStakeCandidate>
+    where
+        F: FnMut(&[u8; 32]) -> Option<[u8; 32]>,
+        R: FnMut(&WithdrawStakeCandidate, &WithdrawStakeCandidate) -> Ordering,
+    {
+        let mut res: alloc::vec::Vec<_> = self
+            .withdraw_stake_candidates(validator_list, stake_list, voter_of, pool_tokens)
+            .collect();
+        res.sort_by(rank);
+        res
+    }
+
+    /// Lamports the validator is staked above its score-proportional share of
+    /// `total_active_balance`, negative if understaked.
+    ///
+    /// See [`Self::validator_stake_targets`] for targets that include the reserve's stake delta.
+    #[inline]
+    pub fn validator_overstake(&self, validator_record: &ValidatorRecord) -> i128 {
+        let target = self
+            .validator_stake_target(validator_record, self.validator_system.total_active_balance);
+        i128::from(validator_record.active_balance()) - i128::from(target)
+    }
+}
+
+/// Rankings for [`State::ranked_withdraw_stake_candidates`].
+///
+/// Combine them with [`Ordering::then_with`].
+impl WithdrawStakeCandidate {
+    /// Most overstaked validators first, so that withdrawals move the stake distribution towards its targets
+    #[inline]
+    pub fn prefer_overstaked(state: &State) -> impl Fn(&Self, &Self) -> Ordering + '_ {
+        |a, b| {
+            state
+                .validator_overstake(&b.validator_record)
+                .cmp(&state.validator_overstake(&a.validator_record))
+        }
+    }
+
+    /// Stake delegated to `vote_account` first
+    #[inline]
+    pub fn prefer_vote_account(vote_account: [u8; 32]) -> impl Fn(&Self, &Self) -> Ordering {
+        move |a, b| {
+            let is_preferred = |c: &Self| *c.validator_record.validator_account() == vote_account;
+            is_preferred(b).cmp(&is_preferred(a))
+        }
+    }
+
+    /// Stake records with the most delegated lamports first
+    #[inline]
+    pub fn prefer_largest(a: &Self, b: &Self) -> Ordering {
+        b.stake_record
+            .last_update_delegated_lamports()
+            .cmp(&a.stake_record.last_update_delegated_lamports())
+    }
+}
+
+/// Stake records in `stake_list` with their `stake_index`, `validator_index` and validator record,
+/// skipping those whose vote account cannot be found
+pub(crate) fn withdraw_stake_sources<'a, F>(
+    validator_list: &'a ValidatorList<'a>,
+    stake_list: &'a StakeList<'a>,
+    mut voter_of: F,
+) -> impl Iterator<Item = (u32, u32, StakeRecord, ValidatorRecord)> + 'a
+where
+    F: FnMut(&[u8; 32]) -> Option<[u8; 32]> + 'a,
+{
+    stake_list
+        .0
+        .iter()
+        .enumerate()
+        .filter_map(move |(stake_index, stake_record)| {
+            let voter = voter_of(stake_record.stake_account())?;
+            let (validator_index, validator_record) = validator_list
+                .0
+                .iter()
+                .enumerate()
+                .find(|(_, v)| *v.validator_account() == voter)?;
+            Some((
+                stake_index.try_into().ok()?,
+                validator_index.try_into().ok()?,
+                *stake_record,
+                *validator_record,
+            ))
+        })
+}
@@ -0,0 +1,9 @@
+mod candidates;
+
+#[cfg(feature = "alloc")]
+mod plan;
+
+pub use candidates::*;
+
+#[cfg(feature = "alloc")]
+pub use plan::*;
@@ -0,0 +1,195 @@
+use core::ops::Range;
+
+use alloc::vec::Vec;
+
+use crate::{
+    message::legacy_batches, AddressLookupTable, DynInstruction, MarinadeError, Message, State,
+    WithdrawStakeAccountIx, WithdrawStakeAccountIxData, WithdrawStakeAccountIxKeysOwned,
+    WithdrawStakeCandidate, WithdrawStakeQuote, WithdrawStakeQuoteArgs,
+};
+
+/// A single withdraw stake account instruction of a [`WithdrawStakePlan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawStakeStep {
+    pub candidate: WithdrawStakeCandidate,
+
+    /// New stake account the user receives, must sign the instruction
+    pub split_stake_account: [u8; 32],
+
+    pub quote: WithdrawStakeQuote,
+    pub ix: WithdrawStakeAccountIx,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithdrawStakePlan {
+    pub steps: Vec<WithdrawStakeStep>,
+
+    /// Ranges of `steps` to be sent in the same transaction
+    pub transactions: Vec<Range<usize>>,
+
+    /// Sum of the `steps`' quotes
+    pub total: WithdrawStakeQuote,
+
+    /// The state after all `steps` have executed
+    pub state_after: State,
+}
+
+impl WithdrawStakePlan {
+    /// The steps' instructions grouped into v0 messages instead of the legacy `transactions`,
+    /// loading accounts from `alts`, e.g. one with [`State::lookup_table_addresses`],
+    /// which fits more steps in each transaction.
+    ///
+    /// See [`Message::compile_v0_batches`]
+    #[inline]
+    pub fn v0_messages(
+        &self,
+        fee_payer: &[u8; 32],
+        recent_blockhash: &[u8; 32],
+        alts: &[AddressLookupTable],
+    ) -> Result<Vec<Message>, MarinadeError> {
+        let ixs: Vec<DynInstruction> = self.steps.iter().map(|step| step.ix.into()).collect();
+        Message::compile_v0_batches(&ixs, fee_payer, recent_blockhash, alts)
+    }
+
+    /// `(split_stake_account, vote_account, lamports_staked)` of each stake account the user ends up with
+    #[inline]
+    pub fn split_stake_accounts(&self) -> impl Iterator<Item = ([u8; 32], [u8; 32], u64)> + '_ {
+        self.steps.iter().map(|step| {
+            (
+                step.split_stake_account,
+                *step.candidate.validator_record.validator_account(),
+                step.quote.lamports_staked,
+            )
+        })
+    }
+}
+
+impl State {
+    /// Splits the withdrawal of `pool_tokens` across the stake records of `candidates`,
+    /// taking as much as possible from each in order.
+    ///
+    /// `candidates` is typically [`Self::max_withdraw_stake_candidates`], sorted by preference.
+    /// Only their indices and records are used.
+    ///
+    /// `keys` must have all keys set except `stake_account` and `split_stake_account`,
+    /// which are set for each step, with `split_stake_account(i)` returning the new
+    /// split stake account for the `i`-th step.
+    ///
+    /// Each step is quoted against the state after the previous steps.
+    /// The steps are grouped into legacy transactions paid for by `split_stake_rent_payer`.
+    /// If `candidates` cannot fulfill the whole amount, returns the reason the last
+    /// skipped candidate could not be used, [`MarinadeError::WithdrawStakeLamportsIsTooLow`]
+    /// if what remains to withdraw from it is below the minimum, or
+    /// [`MarinadeError::SelectedStakeAccountHasNotEnoughFunds`] if none were skipped.
+    pub fn plan_withdraw_stake<I, S>(
+        &self,
+        candidates: I,
+        pool_tokens: u64,
+        keys: &WithdrawStakeAccountIxKeysOwned,
+        beneficiary: [u8; 32],
+        mut split_stake_account: S,
+    ) -> Result<WithdrawStakePlan, MarinadeError>
+    where
+        I: IntoIterator<Item = WithdrawStakeCandidate>,
+        S: FnMut(usize) -> [u8; 32],
+    {
+        let mut state = self.clone();
+        let mut remaining = pool_tokens;
+        let mut last_err = MarinadeError::SelectedStakeAccountHasNotEnoughFunds;
+        let mut steps = Vec::new();
+        let mut total = WithdrawStakeQuote {
+            tokens_in: 0,
+            lamports_staked: 0,
+            fee_amount: 0,
+        };
+
+        for candidate in candidates {
+            if remaining == 0 {
+                break;
+            }
+            let stake_record = candidate.stake_record;
+            let (max, min) = match (
+                state.max_withdraw_stake(&stake_record),
+                state.min_withdraw_stake(&stake_record),
+            ) {
+                (Ok(max), Ok(min)) => (max, min),
+                (Err(e), _) | (_, Err(e)) => {
+                    last_err = e;
+                    continue;
+                }
+            };
+            let mut amount = remaining.min(max);
+            let rest = remaining - amount;
+            if rest != 0 && rest < min {
+                // leave enough for a later step to withdraw
+                amount = amount.saturating_sub(min - rest);
+            }
+            if amount < min {
+                last_err = MarinadeError::WithdrawStakeLamportsIsTooLow;
+                continue;
+            }
+            let quote =
+                match state.quote_withdraw_stake(amount, WithdrawStakeQuoteArgs { stake_record }) {
+                    Ok(quote) => quote,
+                    Err(e) => {
+                        last_err = e;
+                        continue;
+                    }
+                };
+
+            state = state
+                .apply_withdraw_stake(&quote, &stake_record, &candidate.validator_record)
+                .ok_or(MarinadeError::CalculationFailure)?
+                .0;
+            remaining -= amount;
+
+            let split_stake_account = split_stake_account(steps.len());
+            let ix = keys
+                .clone()
+                .with_stake_account(*stake_record.stake_account())
+                .with_split_stake_account(split_stake_account)
+                .to_ix(&WithdrawStakeAccountIxData::new(
+                    candidate.stake_index,
+                    candidate.validator_index,
+                    amount,
+                    beneficiary,
+                ));
+            total = WithdrawStakeQuote {
+                tokens_in: total
+                    .tokens_in
+                    .checked_add(quote.tokens_in)
+                    .ok_or(MarinadeError::CalculationFailure)?,
+                lamports_staked: total
+                    .lamports_staked
+                    .checked_add(quote.lamports_staked)
+                    .ok_or(MarinadeError::CalculationFailure)?,
+                fee_amount: total
+                    .fee_amount
+                    .checked_add(quote.fee_amount)
+                    .ok_or(MarinadeError::CalculationFailure)?,
+            };
+            steps.push(WithdrawStakeStep {
+                candidate,
+                split_stake_account,
+                quote,
+                ix,
+            });
+        }
+
+        if remaining != 0 {
+            return Err(last_err);
+        }
+
+        let transactions = legacy_batches(
+            &steps.iter().map(|step| step.ix.into()).collect::<Vec<_>>(),
+            keys.split_stake_rent_payer(),
+        );
+
+        Ok(WithdrawStakePlan {
+            steps,
+            transactions,
+            total,
+            state_after: state,
+        })
+    }
+}
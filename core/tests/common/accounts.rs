@@ -1,9 +1,9 @@
 use std::{fs::File, path::Path};
 
 use sanctum_marinade_liquid_staking_core::{
-    LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY, LIQ_POOL_SOL_LEG_PUBKEY, MSOL_MINT_ADDR,
+    State, LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY, LIQ_POOL_SOL_LEG_PUBKEY, MSOL_MINT_ADDR,
     MSOL_MINT_AUTHORITY_PUBKEY, RESERVE_PUBKEY, STAKE_DEPOSIT_AUTHORITY_PUBKEY,
-    STAKE_WITHDRAW_AUTHORITY_PUBKEY, SYSTEM_PROGRAM, TOKEN_PROGRAM,
+    STAKE_WITHDRAW_AUTHORITY_PUBKEY, STATE_PUBKEY, SYSTEM_PROGRAM, TOKEN_PROGRAM,
 };
 use serde::{Deserialize, Serialize};
 use solana_account::Account;
@@ -41,6 +41,20 @@ impl KeyedUiAccount {
     }
 }
 
+/// The [`State`] of the `marinade-state` test fixture
+pub fn mainnet_state() -> State {
+    let state_account = KeyedUiAccount::from_test_fixtures_file("marinade-state");
+    State::borsh_de(state_account.account_data().as_slice()).unwrap()
+}
+
+/// mSOL balance of the mainnet liquidity pool mSOL leg fixture
+pub fn mainnet_msol_leg_balance(state: &State) -> u64 {
+    let msol_leg = KeyedUiAccount::from_test_fixtures_file("marinade-liq_pool_msol_leg");
+    state
+        .msol_leg_balance(&STATE_PUBKEY, &msol_leg.account_data())
+        .unwrap()
+}
+
 pub fn payer_account(lamports: u64) -> Account {
     Account::new(lamports, 0, &Pubkey::new_from_array(SYSTEM_PROGRAM))
 }
@@ -64,6 +78,10 @@ pub fn token_acc_balance(account: &Account) -> u64 {
     u64::from_le_bytes(account.data[64..72].try_into().unwrap())
 }
 
+pub fn set_token_acc_balance(account: &mut Account, amt: u64) {
+    account.data[64..72].copy_from_slice(&amt.to_le_bytes());
+}
+
 fn test_fixtures_accounts<'a>(
     fnames: &'a [&'a str],
 ) -> impl Iterator<Item = (Pubkey, Account)> + 'a {
@@ -16,3 +16,47 @@ pub fn metas_from_keys_signer_writer<const N: usize>(
         })
         .collect()
 }
+
+pub fn ix_from_sdk<const N: usize, const D: usize>(
+    ix: &sanctum_marinade_liquid_staking_core::Instruction<N, D>,
+) -> solana_instruction::Instruction {
+    solana_instruction::Instruction {
+        program_id: Pubkey::new_from_array(ix.program_id),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: Pubkey::new_from_array(meta.pubkey),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: ix.data.to_vec(),
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub fn ix_from_dyn(
+    ix: &sanctum_marinade_liquid_staking_core::DynInstruction,
+) -> solana_instruction::Instruction {
+    solana_instruction::Instruction {
+        program_id: Pubkey::new_from_array(ix.program_id),
+        accounts: ix
+            .accounts
+            .iter()
+            .map(|meta| AccountMeta {
+                pubkey: Pubkey::new_from_array(meta.pubkey),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: ix.data.clone(),
+    }
+}
+
+/// Serialized size of a legacy transaction of `ixs` paid for by `payer`
+#[cfg(feature = "alloc")]
+pub fn legacy_transaction_size(ixs: &[solana_instruction::Instruction], payer: [u8; 32]) -> usize {
+    let message = solana_sdk::message::Message::new(ixs, Some(&Pubkey::new_from_array(payer)));
+    1 + 64 * usize::from(message.header.num_required_signatures) + message.serialize().len()
+}
@@ -3,9 +3,11 @@ mod consts;
 mod instruction;
 mod mollusk;
 mod path;
+mod prop;
 
 pub use accounts::*;
 pub use consts::*;
 pub use instruction::*;
 pub use mollusk::*;
 pub use path::*;
+pub use prop::*;
@@ -0,0 +1,185 @@
+use proptest::{collection::SizeRange, prelude::*};
+use sanctum_marinade_liquid_staking_core::{Fee, FeeCents, StakeRecord, State, ValidatorRecord};
+
+/// Upper bound for each of the lamport fields summed by `total_lamports_under_control`
+/// so that the sum never overflows u64
+const MAX_BALANCE: u64 = u64::MAX / 8;
+
+prop_compose! {
+    /// A [`State`] with randomized exchange rate and withdraw stake fee,
+    /// all other fields left at their defaults
+    pub fn any_state()
+        (
+            msol_supply in 0..=MAX_BALANCE,
+            total_active_balance in 0..=MAX_BALANCE,
+            available_reserve_balance in 0..=MAX_BALANCE,
+            delayed_unstake_cooling_down in 0..=MAX_BALANCE,
+            emergency_cooling_down in 0..=MAX_BALANCE,
+            circulating_ticket_balance in 0..=MAX_BALANCE,
+            withdraw_stake_account_fee_bp_cents in 0..=1_000_000u32,
+        ) -> State {
+            let mut state = State::DEFAULT;
+            state.msol_supply = msol_supply;
+            state.validator_system.total_active_balance = total_active_balance;
+            state.available_reserve_balance = available_reserve_balance;
+            state.stake_system.delayed_unstake_cooling_down = delayed_unstake_cooling_down;
+            state.emergency_cooling_down = emergency_cooling_down;
+            state.circulating_ticket_balance = circulating_ticket_balance;
+            state.withdraw_stake_account_fee = FeeCents {
+                bp_cents: withdraw_stake_account_fee_bp_cents,
+            };
+            state
+        }
+}
+
+/// Upper bound for `min_deposit` and `min_stake`
+const MAX_MIN_AMOUNT: u64 = 1_000_000_000_000;
+
+prop_compose! {
+    /// An [`any_state()`] that is not paused and has withdraw stake account enabled,
+    /// with randomized caps and minimum amounts
+    pub fn any_quotable_state()
+        (
+            state in any_state(),
+            staking_sol_cap: u64,
+            min_deposit in 0..=MAX_MIN_AMOUNT,
+            min_stake in 0..=MAX_MIN_AMOUNT,
+        ) -> State {
+            let mut state = state;
+            state.staking_sol_cap = staking_sol_cap;
+            state.min_deposit = min_deposit;
+            state.stake_system.min_stake = min_stake;
+            state.withdraw_stake_account_enabled = true;
+            state
+        }
+}
+
+/// Zero, one, `u64::MAX` or any value in between,
+/// biased towards realistic lamport amounts
+pub fn any_amount() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        Just(0),
+        Just(1),
+        Just(u64::MAX),
+        0..=MAX_MIN_AMOUNT * 1_000_000,
+        any::<u64>(),
+    ]
+}
+
+/// Zero, any valid fee or any invalid fee above 100%
+fn any_fee_numerator(max_valid: u32) -> impl Strategy<Value = u32> {
+    prop_oneof![Just(0), 0..=max_valid, any::<u32>()]
+}
+
+prop_compose! {
+    /// A [`State`] with every field used by quoting randomized,
+    /// including overflowing balances and invalid fees, for no-panic tests
+    pub fn arbitrary_state()
+        (
+            balances in proptest::array::uniform12(any_amount()),
+            total_validator_score: u32,
+            reward_fee in any_fee_numerator(10_000),
+            lp_fees in proptest::array::uniform3(any_fee_numerator(10_000)),
+            withdraw_stake_account_fee in any_fee_numerator(1_000_000),
+            delayed_unstake_fee in any_fee_numerator(1_000_000),
+            bumps: [u8; 6],
+            paused: bool,
+            withdraw_stake_account_enabled: bool,
+        ) -> State {
+            let [
+                msol_supply,
+                total_active_balance,
+                available_reserve_balance,
+                delayed_unstake_cooling_down,
+                emergency_cooling_down,
+                circulating_ticket_balance,
+                staking_sol_cap,
+                min_deposit,
+                min_stake,
+                rent_exempt_for_token_acc,
+                lp_liquidity_target,
+                liquidity_sol_cap,
+            ] = balances;
+            let mut state = State::DEFAULT;
+            state.msol_supply = msol_supply;
+            state.validator_system.total_active_balance = total_active_balance;
+            state.validator_system.total_validator_score = total_validator_score;
+            state.available_reserve_balance = available_reserve_balance;
+            state.stake_system.delayed_unstake_cooling_down = delayed_unstake_cooling_down;
+            state.emergency_cooling_down = emergency_cooling_down;
+            state.circulating_ticket_balance = circulating_ticket_balance;
+            state.staking_sol_cap = staking_sol_cap;
+            state.min_deposit = min_deposit;
+            state.stake_system.min_stake = min_stake;
+            state.rent_exempt_for_token_acc = rent_exempt_for_token_acc;
+            state.liq_pool.lp_liquidity_target = lp_liquidity_target;
+            state.liq_pool.liquidity_sol_cap = liquidity_sol_cap;
+            state.reward_fee = Fee { basis_points: reward_fee };
+            [
+                state.liq_pool.lp_max_fee,
+                state.liq_pool.lp_min_fee,
+                state.liq_pool.treasury_cut,
+            ] = lp_fees.map(|basis_points| Fee { basis_points });
+            state.withdraw_stake_account_fee = FeeCents {
+                bp_cents: withdraw_stake_account_fee,
+            };
+            state.delayed_unstake_fee = FeeCents {
+                bp_cents: delayed_unstake_fee,
+            };
+            [
+                state.reserve_bump_seed,
+                state.msol_mint_authority_bump_seed,
+                state.liq_pool.sol_leg_bump_seed,
+                state.liq_pool.msol_leg_authority_bump_seed,
+                state.stake_system.stake_withdraw_bump_seed,
+                state.stake_system.stake_deposit_bump_seed,
+            ] = bumps;
+            state.paused = paused;
+            state.withdraw_stake_account_enabled = withdraw_stake_account_enabled;
+            state
+        }
+}
+
+/// A [`StakeRecord`] for any stake account with [`any_amount()`] delegated lamports
+pub fn any_stake_record() -> impl Strategy<Value = StakeRecord> {
+    (any::<[u8; 32]>(), any_amount(), any::<u64>(), any::<bool>()).prop_map(
+        |(stake_account, lamports, epoch, is_emergency_unstaking)| {
+            StakeRecord::new(stake_account, lamports, epoch, is_emergency_unstaking)
+        },
+    )
+}
+
+/// [`StakeRecord`]s for stake accounts `[100; 32], [101; 32], ...`
+/// with delegated lamports drawn from `lamports`
+pub fn any_stake_records(
+    lamports: impl Strategy<Value = u64>,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<StakeRecord>> {
+    prop::collection::vec((lamports, any::<u64>(), any::<bool>()), len).prop_map(|stakes| {
+        stakes
+            .into_iter()
+            .enumerate()
+            .map(|(i, (lamports, epoch, is_emergency_unstaking))| {
+                StakeRecord::new([100 + i as u8; 32], lamports, epoch, is_emergency_unstaking)
+            })
+            .collect()
+    })
+}
+
+/// Upper bound for validator scores so that the scores of
+/// at most 255 validators sum without overflowing u32
+const MAX_SCORE: u32 = u32::MAX / 255;
+
+/// [`ValidatorRecord`]s for vote accounts `[1; 32], [2; 32], ...`
+/// with [`any_amount()`] active balances
+pub fn any_validators(len: impl Into<SizeRange>) -> impl Strategy<Value = Vec<ValidatorRecord>> {
+    prop::collection::vec((any_amount(), 0..=MAX_SCORE, any::<u8>()), len).prop_map(|validators| {
+        validators
+            .into_iter()
+            .enumerate()
+            .map(|(i, (active_balance, score, bump))| {
+                ValidatorRecord::new([i as u8 + 1; 32], active_balance, score, 0, bump)
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,113 @@
+use mollusk_svm::sysvar::Sysvars;
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    activation_status, Delegation, StakeActivationStatus, StakeHistory, StakeHistoryEntry,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    stake::state::Delegation as SolanaDelegation,
+    stake_history::{
+        StakeHistory as SolanaStakeHistory, StakeHistoryEntry as SolanaStakeHistoryEntry,
+    },
+};
+
+const MAX_EPOCH: u64 = 64;
+
+/// Upper bound for stake amounts and stake history entries.
+///
+/// solana weighs the cluster's newly effective stake by `stake / activating`
+/// without overflow checks, so keep `stake * effective` within u64
+const MAX_STAKE: u64 = u32::MAX as u64;
+
+fn history_account_data(history: &SolanaStakeHistory) -> Vec<u8> {
+    Sysvars {
+        stake_history: history.clone(),
+        ..Default::default()
+    }
+    .keyed_account_for_stake_history_sysvar()
+    .1
+    .data
+}
+
+fn any_history() -> impl Strategy<Value = SolanaStakeHistory> {
+    prop::collection::vec(
+        (0..=MAX_EPOCH, 0..=MAX_STAKE, 0..=MAX_STAKE, 0..=MAX_STAKE),
+        0..=MAX_EPOCH as usize,
+    )
+    .prop_map(|entries| {
+        let mut history = SolanaStakeHistory::default();
+        for (epoch, effective, activating, deactivating) in entries {
+            history.add(
+                epoch,
+                SolanaStakeHistoryEntry {
+                    effective,
+                    activating,
+                    deactivating,
+                },
+            );
+        }
+        history
+    })
+}
+
+fn any_epoch() -> impl Strategy<Value = u64> {
+    prop_oneof![0..=MAX_EPOCH, Just(u64::MAX)]
+}
+
+#[test]
+fn stake_history_sysvar_data() {
+    let mut history = SolanaStakeHistory::default();
+    for epoch in [3, 1, 2] {
+        history.add(
+            epoch,
+            SolanaStakeHistoryEntry {
+                effective: epoch * 10,
+                activating: epoch * 100,
+                deactivating: epoch * 1000,
+            },
+        );
+    }
+    let data = history_account_data(&history);
+    let parsed = StakeHistory::try_from_acc_data(&data).unwrap();
+
+    assert_eq!(
+        parsed.0,
+        [3, 2, 1].map(|e| StakeHistoryEntry::new(e, e * 10, e * 100, e * 1000))
+    );
+    assert_eq!(
+        parsed.get(2),
+        Some(&StakeHistoryEntry::new(2, 20, 200, 2000))
+    );
+    assert_eq!(parsed.get(4), None);
+
+    let empty = history_account_data(&SolanaStakeHistory::default());
+    assert_eq!(StakeHistory::try_from_acc_data(&empty).unwrap().0, []);
+}
+
+proptest! {
+    #[test]
+    fn activation_status_matches_solana(
+        history in any_history(),
+        stake in 0..=MAX_STAKE,
+        activation_epoch in any_epoch(),
+        deactivation_epoch in any_epoch(),
+        current_epoch in 0..=MAX_EPOCH + 2,
+        new_rate_activation_epoch in prop::option::of(0..=MAX_EPOCH),
+    ) {
+        let data = history_account_data(&history);
+        let parsed = StakeHistory::try_from_acc_data(&data).unwrap();
+
+        let delegation = Delegation::new([1; 32], stake, activation_epoch, deactivation_epoch);
+        let mut solana_delegation =
+            SolanaDelegation::new(&Pubkey::new_from_array([1; 32]), stake, activation_epoch);
+        solana_delegation.deactivation_epoch = deactivation_epoch;
+
+        let SolanaStakeHistoryEntry { effective, activating, deactivating } = solana_delegation
+            .stake_activating_and_deactivating(current_epoch, &history, new_rate_activation_epoch);
+
+        prop_assert_eq!(
+            activation_status(&delegation, current_epoch, &parsed, new_rate_activation_epoch),
+            StakeActivationStatus { effective, activating, deactivating }
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use borsh::BorshSerialize;
+use const_crypto::bs58;
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, stake_record_update_kind, stale_stake_records, StakeHistory,
+    StakeRecordUpdateKind, StakeStateV2,
+};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+use crate::common::{mainnet_state, KeyedUiAccount};
+
+/// All records of the mainnet fixture were last updated before this epoch
+pub(super) const CURRENT_EPOCH: u64 = 773;
+
+/// Stake index whose stake account is replaced with a deactivated one
+pub(super) const DEACTIVATED_STAKE_INDEX: u32 = 5;
+
+pub(super) struct Fixtures {
+    pub state: marinade_staking_sdk::State,
+    pub stake_list_data: Vec<u8>,
+    pub validator_list_data: Vec<u8>,
+    pub active_stake_account: ([u8; 32], StakeStateV2),
+    pub deactivated_stake_account: ([u8; 32], StakeStateV2, Account),
+}
+
+/// Mainnet fixtures, with the stake account at [`DEACTIVATED_STAKE_INDEX`] replaced by
+/// a fully deactivated one that the state counts as cooling down
+pub(super) fn fixtures() -> Fixtures {
+    let mut state = mainnet_state();
+    let stake_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-stake_list").account_data();
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("withdraw_stake_account");
+    let active = StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap();
+
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let deactivated_record = stake_list.0[DEACTIVATED_STAKE_INDEX as usize];
+    let mut deactivated = active;
+    let StakeStateV2::Stake(meta, stake, _) = &mut deactivated else {
+        unreachable!()
+    };
+    let rent_exempt_reserve = meta.rent_exempt_reserve;
+    // deactivating stake is counted as cooling down
+    state.stake_system.delayed_unstake_cooling_down +=
+        deactivated_record.last_update_delegated_lamports();
+    stake.delegation.stake = deactivated_record.last_update_delegated_lamports();
+    stake.delegation.deactivation_epoch = CURRENT_EPOCH - 10;
+    let mut data = vec![0; StakeStateV2::ACCOUNT_LEN];
+    deactivated.serialize(&mut data.as_mut_slice()).unwrap();
+    let deactivated_account = Account {
+        lamports: deactivated_record.last_update_delegated_lamports() + rent_exempt_reserve,
+        data,
+        owner: Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+        executable: false,
+        rent_epoch: u64::MAX,
+    };
+
+    Fixtures {
+        state,
+        stake_list_data,
+        validator_list_data,
+        active_stake_account: (bs58::decode_pubkey(&stake_account.pubkey), active),
+        deactivated_stake_account: (
+            *deactivated_record.stake_account(),
+            deactivated,
+            deactivated_account,
+        ),
+    }
+}
+
+#[test]
+fn stake_record_update_kinds() {
+    let Fixtures {
+        state,
+        stake_list_data,
+        validator_list_data,
+        active_stake_account: (_, active),
+        deactivated_stake_account: (_, deactivated, _),
+    } = fixtures();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    let stake_history = StakeHistory(&[]);
+
+    assert_eq!(
+        stale_stake_records(&stake_list, CURRENT_EPOCH).count(),
+        stake_list.0.len()
+    );
+    assert!(stale_stake_records(&stake_list, 0).next().is_none());
+
+    let kind = |stake_state: &StakeStateV2, epoch| {
+        stake_record_update_kind(stake_state, &validator_list, epoch, &stake_history, None)
+    };
+    assert_eq!(
+        kind(&active, CURRENT_EPOCH),
+        Ok(StakeRecordUpdateKind::Active {
+            validator_index: 589
+        })
+    );
+    assert_eq!(
+        kind(&deactivated, CURRENT_EPOCH),
+        Ok(StakeRecordUpdateKind::Deactivated)
+    );
+    assert_eq!(
+        kind(&deactivated, CURRENT_EPOCH - 10),
+        Ok(StakeRecordUpdateKind::CoolingDown)
+    );
+    assert_eq!(
+        kind(&StakeStateV2::Uninitialized, CURRENT_EPOCH),
+        Err(marinade_staking_sdk::MarinadeError::RequiredDelegatedStake)
+    );
+
+    let mut unknown_voter = active;
+    if let StakeStateV2::Stake(_, stake, _) = &mut unknown_voter {
+        stake.delegation.voter_pubkey = [0; 32];
+    }
+    assert_eq!(
+        kind(&unknown_voter, CURRENT_EPOCH),
+        Err(marinade_staking_sdk::MarinadeError::WrongValidatorAccountOrIndex)
+    );
+}
@@ -0,0 +1,250 @@
+use mollusk_svm::{
+    program::{create_keyed_account_for_builtin_program, keyed_account_for_system_program},
+    result::InstructionResult,
+};
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, stake_record_update_kind, stale_stake_records, CrankPlan,
+    StakeHistory, StakeRecord, StakeRecordUpdate, StakeRecordUpdateKind, UpdateActiveIxKeysOwned,
+    UpdateDeactivatedIxKeysOwned, PACKET_DATA_SIZE,
+};
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    ix_from_sdk, legacy_transaction_size, marinade_mainnet_accounts, mollusk_marinade_prog,
+    payer_account, warp_to_epoch,
+};
+
+use super::crank::{fixtures, Fixtures, CURRENT_EPOCH, DEACTIVATED_STAKE_INDEX};
+
+/// Stake index of the `withdraw_stake_account` fixture
+const ACTIVE_STAKE_INDEX: u32 = 10;
+
+#[test]
+fn crank_plan_mollusk() {
+    let Fixtures {
+        state,
+        stake_list_data,
+        validator_list_data,
+        active_stake_account,
+        deactivated_stake_account,
+    } = fixtures();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    let mut mollusk = mollusk_marinade_prog();
+    warp_to_epoch(&mut mollusk, CURRENT_EPOCH);
+    let (_, stake_history_account) = mollusk.sysvars.keyed_account_for_stake_history_sysvar();
+    let stake_history = StakeHistory::try_from_acc_data(&stake_history_account.data).unwrap();
+
+    // only these 2 stake accounts are known, the rest are skipped
+    let stake_state_of = |pk: &[u8; 32]| {
+        if *pk == active_stake_account.0 {
+            Some(active_stake_account.1)
+        } else if *pk == deactivated_stake_account.0 {
+            Some(deactivated_stake_account.1)
+        } else {
+            None
+        }
+    };
+    let updates = stale_stake_records(&stake_list, CURRENT_EPOCH).filter_map(
+        |(stake_index, stake_record)| {
+            let stake_state = stake_state_of(stake_record.stake_account())?;
+            let kind = stake_record_update_kind(
+                &stake_state,
+                &validator_list,
+                CURRENT_EPOCH,
+                &stake_history,
+                None,
+            )
+            .unwrap();
+            Some(StakeRecordUpdate {
+                stake_index,
+                stake_record,
+                kind,
+            })
+        },
+    );
+    let plan = CrankPlan::new(
+        updates,
+        &UpdateActiveIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state),
+        &UpdateDeactivatedIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state),
+    );
+    assert_eq!(plan.update_active.len(), 1);
+    assert_eq!(plan.update_active_transactions.len(), 1);
+    assert_eq!(plan.update_deactivated.len(), 1);
+    assert_eq!(plan.update_deactivated_transactions.len(), 1);
+    assert!(plan.cooling_down.is_empty());
+
+    let ixs: Vec<_> = plan
+        .update_active
+        .iter()
+        .map(ix_from_sdk)
+        .chain(plan.update_deactivated.iter().map(ix_from_sdk))
+        .collect();
+
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .map(|(pk, mut account)| {
+            // the program checks that the reserve holds what the state says
+            if pk.as_array() == &marinade_staking_sdk::RESERVE_PUBKEY {
+                account.lamports =
+                    state.available_reserve_balance + state.rent_exempt_for_token_acc;
+            }
+            if pk.as_array() == &marinade_staking_sdk::STATE_PUBKEY {
+                state.borsh_ser(&mut account.data[..]).unwrap();
+            }
+            (pk, account)
+        })
+        .chain([
+            keyed_account_for_system_program(),
+            create_keyed_account_for_builtin_program(
+                &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+                "solana_stake_program",
+            ),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            (
+                Pubkey::new_from_array(marinade_staking_sdk::SYSVAR_STAKE_HISTORY),
+                stake_history_account.clone(),
+            ),
+            mollusk_svm_programs_token::token::keyed_account(),
+            (
+                Pubkey::new_from_array(state.operational_sol_account),
+                payer_account(1_000_000_000),
+            ),
+            (
+                Pubkey::new_from_array(deactivated_stake_account.0),
+                deactivated_stake_account.2.clone(),
+            ),
+        ])
+        .collect();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&ixs, &accounts);
+    raw_result.unwrap();
+
+    let resulting_account = |pubkey: &[u8; 32]| {
+        &resulting_accounts
+            .iter()
+            .find(|(pk, _)| pk.as_array() == pubkey)
+            .unwrap()
+            .1
+    };
+    let state_after = marinade_staking_sdk::State::borsh_de(
+        resulting_account(&marinade_staking_sdk::STATE_PUBKEY)
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    let stake_list_after = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &resulting_account(&marinade_staking_sdk::STAKE_LIST_PUBKEY).data,
+        state_after.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+
+    // update_deactivated swap-removed its record
+    assert_eq!(stake_list_after.0.len(), stake_list.0.len() - 1);
+    assert_eq!(
+        stake_list_after.0[DEACTIVATED_STAKE_INDEX as usize],
+        *stake_list.0.last().unwrap()
+    );
+    assert_eq!(resulting_account(&deactivated_stake_account.0).lamports, 0);
+
+    // update_active brought its record up to date
+    let active_record = stake_list_after.0[ACTIVE_STAKE_INDEX as usize];
+    assert_eq!(active_record.stake_account(), &active_stake_account.0);
+    assert_eq!(active_record.last_update_epoch(), CURRENT_EPOCH);
+    assert_eq!(
+        stale_stake_records(&stake_list_after, CURRENT_EPOCH).count(),
+        stake_list.0.len() - 2
+    );
+}
+
+#[test]
+fn crank_plan_transactions_fit() {
+    let state = fixtures().state;
+    let kinds = [
+        StakeRecordUpdateKind::Active { validator_index: 3 },
+        StakeRecordUpdateKind::Deactivated,
+        StakeRecordUpdateKind::CoolingDown,
+    ];
+    let updates = (0..120u32).map(|stake_index| StakeRecordUpdate {
+        stake_index,
+        stake_record: StakeRecord::new([stake_index as u8; 32], 1_000_000_000, 0, false),
+        kind: kinds[stake_index as usize % kinds.len()],
+    });
+    let plan = CrankPlan::new(
+        updates,
+        &UpdateActiveIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state),
+        &UpdateDeactivatedIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state),
+    );
+
+    assert_eq!(plan.update_active.len(), 40);
+    assert_eq!(plan.update_deactivated.len(), 40);
+    assert_eq!(plan.cooling_down, (2..120).step_by(3).collect::<Vec<_>>());
+    let stake_index = |data: &[u8]| u32::from_le_bytes(data[8..12].try_into().unwrap());
+    assert!(plan
+        .update_active
+        .windows(2)
+        .all(|w| stake_index(&w[0].data) < stake_index(&w[1].data)));
+    assert!(plan
+        .update_deactivated
+        .windows(2)
+        .all(|w| stake_index(&w[0].data) > stake_index(&w[1].data)));
+
+    fn assert_fit(
+        ixs: &[solana_instruction::Instruction],
+        transactions: &[core::ops::Range<usize>],
+    ) {
+        let payer = [255; 32];
+        assert!(transactions.len() > 1);
+        assert_eq!(transactions.first().unwrap().start, 0);
+        assert_eq!(transactions.last().unwrap().end, ixs.len());
+        for (i, tx) in transactions.iter().enumerate() {
+            assert!(legacy_transaction_size(&ixs[tx.clone()], payer) <= PACKET_DATA_SIZE);
+            if let Some(next) = transactions.get(i + 1) {
+                assert_eq!(tx.end, next.start);
+                assert!(
+                    legacy_transaction_size(&ixs[tx.start..tx.end + 1], payer) > PACKET_DATA_SIZE
+                );
+            }
+        }
+    }
+    assert_fit(
+        &plan
+            .update_active
+            .iter()
+            .map(ix_from_sdk)
+            .collect::<Vec<_>>(),
+        &plan.update_active_transactions,
+    );
+    assert_fit(
+        &plan
+            .update_deactivated
+            .iter()
+            .map(ix_from_sdk)
+            .collect::<Vec<_>>(),
+        &plan.update_deactivated_transactions,
+    );
+}
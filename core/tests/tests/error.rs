@@ -0,0 +1,206 @@
+use const_crypto::bs58;
+use mollusk_svm::program::{
+    create_keyed_account_for_builtin_program, keyed_account_for_system_program,
+};
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, deposit_sol_ix, find_msol_ata, withdraw_stake_ix,
+    DepositSolQuoteArgs, MarinadeError, StakeStateV2, State, MARINADE_ERROR_CODE_OFFSET,
+    STATE_PUBKEY,
+};
+use solana_account::Account;
+use solana_instruction::error::InstructionError;
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    ix_from_sdk, mainnet_msol_leg_balance, mainnet_state, marinade_mainnet_accounts,
+    mollusk_marinade_prog, msol_token_acc, payer_account, KeyedUiAccount,
+};
+
+/// Mainnet accounts with the state account's data replaced by `state`
+fn accounts_with_state(state: &State) -> impl Iterator<Item = (Pubkey, Account)> {
+    let mut data = Vec::new();
+    state.borsh_ser(&mut data).unwrap();
+    marinade_mainnet_accounts().map(move |(pubkey, mut account)| {
+        if pubkey.to_bytes() == STATE_PUBKEY {
+            account.data = data.clone();
+        }
+        (pubkey, account)
+    })
+}
+
+/// The program error of a failed instruction
+fn program_error(raw_result: Result<(), InstructionError>) -> MarinadeError {
+    let Err(InstructionError::Custom(code)) = raw_result else {
+        panic!("expected custom error, got {raw_result:?}");
+    };
+    MarinadeError::from_custom_code(code).unwrap()
+}
+
+#[test]
+fn custom_codes_round_trip() {
+    for code in MARINADE_ERROR_CODE_OFFSET..=6086 {
+        let err = MarinadeError::from_custom_code(code).unwrap();
+        assert_eq!(err.to_custom_code(), Some(code));
+    }
+    for code in [0, MARINADE_ERROR_CODE_OFFSET - 1, 6087, u32::MAX] {
+        assert_eq!(MarinadeError::from_custom_code(code), None);
+    }
+
+    for (err, code) in [
+        (MarinadeError::WrongReserveOwner, 6000),
+        (MarinadeError::CalculationFailure, 6013),
+        (MarinadeError::StakingIsCapped, 6034),
+        (MarinadeError::DepositAmountIsTooLow, 6039),
+        (MarinadeError::ProgramIsPaused, 6077),
+        (MarinadeError::WithdrawStakeAccountIsNotEnabled, 6081),
+        (MarinadeError::ShrinkingListWithDeletingContents, 6086),
+    ] {
+        assert_eq!(err.to_custom_code(), Some(code));
+    }
+
+    for sdk_only in [
+        MarinadeError::WrongStakeAuthority,
+        MarinadeError::InvalidTokenAccount,
+        MarinadeError::WrongTokenMint,
+        MarinadeError::WrongTokenOwner,
+        MarinadeError::AccountIndexOverflow,
+        MarinadeError::AddressTableIndexOverflow,
+    ] {
+        assert_eq!(sdk_only.to_custom_code(), None);
+    }
+}
+
+#[test]
+fn deposit_sol_errors_match_program() {
+    let state = mainnet_state();
+    let args = DepositSolQuoteArgs {
+        msol_leg_balance: mainnet_msol_leg_balance(&state),
+    };
+    let lamports = 2_000_000_000;
+    let user = Pubkey::new_unique();
+    let (ix, _) = deposit_sol_ix(&state, user.to_bytes(), lamports, args).unwrap();
+
+    let mut paused = state.clone();
+    paused.paused = true;
+    let mut capped = state.clone();
+    capped.staking_sol_cap = 1;
+    let mut min_deposit = state.clone();
+    min_deposit.min_deposit = lamports + 1;
+
+    let mollusk = mollusk_marinade_prog();
+    for (state, expected) in [
+        (paused, MarinadeError::ProgramIsPaused),
+        (capped, MarinadeError::StakingIsCapped),
+        (min_deposit, MarinadeError::DepositAmountIsTooLow),
+    ] {
+        let accounts: Vec<_> = accounts_with_state(&state)
+            .chain([
+                keyed_account_for_system_program(),
+                mollusk_svm_programs_token::token::keyed_account(),
+                (user, payer_account(10_000_000_000)),
+                (
+                    Pubkey::new_from_array(find_msol_ata(user.as_array()).0),
+                    msol_token_acc(0, user),
+                ),
+            ])
+            .collect();
+        let result = mollusk.process_instruction(&ix_from_sdk(&ix), &accounts);
+
+        assert_eq!(program_error(result.raw_result), expected);
+        assert_eq!(
+            deposit_sol_ix(&state, user.to_bytes(), lamports, args).unwrap_err(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn withdraw_stake_errors_match_program() {
+    let state = mainnet_state();
+    let pool_tokens = 2_000_000_000;
+
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("withdraw_stake_account");
+    let stake_account_pubkey = bs58::decode_pubkey(&stake_account.pubkey);
+    let StakeStateV2::Stake(_, stake, _) =
+        StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap()
+    else {
+        panic!("fixture should be delegated");
+    };
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    let stake_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-stake_list").account_data();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let candidate = state
+        .withdraw_stake_candidates(
+            &validator_list,
+            &stake_list,
+            |pk| (*pk == stake_account_pubkey).then_some(stake.delegation.voter_pubkey),
+            pool_tokens,
+        )
+        .next()
+        .unwrap();
+
+    let user = Pubkey::new_unique();
+    let split_stake_account = Pubkey::new_unique();
+    let (ix, _) = withdraw_stake_ix(
+        &state,
+        &candidate,
+        user.to_bytes(),
+        split_stake_account.to_bytes(),
+        pool_tokens,
+    )
+    .unwrap();
+
+    let mut paused = state.clone();
+    paused.paused = true;
+    let mut disabled = state.clone();
+    disabled.withdraw_stake_account_enabled = false;
+
+    let mollusk = mollusk_marinade_prog();
+    for (state, expected) in [
+        (paused, MarinadeError::ProgramIsPaused),
+        (disabled, MarinadeError::WithdrawStakeAccountIsNotEnabled),
+    ] {
+        let accounts: Vec<_> = accounts_with_state(&state)
+            .chain([
+                keyed_account_for_system_program(),
+                create_keyed_account_for_builtin_program(
+                    &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+                    "solana_stake_program",
+                ),
+                mollusk.sysvars.keyed_account_for_clock_sysvar(),
+                mollusk_svm_programs_token::token::keyed_account(),
+                (user, payer_account(5_000_000_000)),
+                (
+                    Pubkey::new_from_array(find_msol_ata(user.as_array()).0),
+                    msol_token_acc(5_000_000_000, user),
+                ),
+                (split_stake_account, Account::default()),
+            ])
+            .collect();
+        let result = mollusk.process_instruction(&ix_from_sdk(&ix), &accounts);
+
+        assert_eq!(program_error(result.raw_result), expected);
+        assert_eq!(
+            withdraw_stake_ix(
+                &state,
+                &candidate,
+                user.to_bytes(),
+                split_stake_account.to_bytes(),
+                pool_tokens,
+            )
+            .unwrap_err(),
+            expected
+        );
+    }
+}
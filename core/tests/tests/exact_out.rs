@@ -0,0 +1,103 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{DepositSolQuoteArgs, StakeAccountLamports};
+
+use crate::common::{any_state, mainnet_state};
+
+const MAX_TARGET: u64 = 1_000_000_000_000_000_000;
+
+proptest! {
+    #[test]
+    fn deposit_sol_exact_out_meets_target(
+        state in any_state(),
+        msol_out in 0..=MAX_TARGET,
+        msol_leg_balance: u64,
+    ) {
+        let args = DepositSolQuoteArgs { msol_leg_balance };
+        if let Some(quote) = state.quote_deposit_sol_exact_out_unchecked(msol_out, args) {
+            prop_assert!(quote.out_amount >= msol_out);
+            prop_assert_eq!(
+                Some(quote),
+                state.quote_deposit_sol_unchecked(quote.in_amount, args)
+            );
+            // only minimal if not partially swapped from the liq pool
+            let is_partially_swapped = quote.msol_from_liq_pool > 0 && quote.msol_minted > 0;
+            if quote.in_amount > 0 && !is_partially_swapped {
+                let less = state
+                    .quote_deposit_sol_unchecked(quote.in_amount - 1, args)
+                    .unwrap();
+                prop_assert!(less.out_amount < msol_out);
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn deposit_stake_exact_out_meets_target(
+        state in any_state(),
+        tokens_out in 0..=MAX_TARGET,
+        unstaked: u32,
+    ) {
+        let unstaked = u64::from(unstaked);
+        if let Some(quote) = state.quote_deposit_stake_exact_out_unchecked(tokens_out, unstaked) {
+            prop_assert!(quote.tokens_out >= tokens_out);
+            prop_assert_eq!(quote.stake_account_lamports_in.unstaked, unstaked);
+            prop_assert_eq!(
+                Some(quote),
+                state.quote_deposit_stake_unchecked(quote.stake_account_lamports_in)
+            );
+            let StakeAccountLamports { staked, .. } = quote.stake_account_lamports_in;
+            if staked > 0 {
+                let less = state
+                    .quote_deposit_stake_unchecked(StakeAccountLamports {
+                        staked: staked - 1,
+                        unstaked,
+                    })
+                    .unwrap();
+                prop_assert!(less.tokens_out < tokens_out);
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn withdraw_stake_exact_out_meets_target(
+        state in any_state(),
+        lamports_staked in 0..=MAX_TARGET,
+    ) {
+        if let Some(quote) = state.quote_withdraw_stake_exact_out_unchecked(lamports_staked) {
+            prop_assert!(quote.lamports_staked >= lamports_staked);
+            prop_assert_eq!(
+                Some(quote),
+                state.quote_withdraw_stake_unchecked(quote.tokens_in)
+            );
+            if quote.tokens_in > 0 {
+                let less = state.quote_withdraw_stake_unchecked(quote.tokens_in - 1).unwrap();
+                prop_assert!(less.lamports_staked < lamports_staked);
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn deposit_sol_exact_out_mainnet(
+        msol_out in 0..=1_000_000_000_000_000u64,
+        msol_leg_balance: u64,
+    ) {
+        let state = mainnet_state();
+        let args = DepositSolQuoteArgs { msol_leg_balance };
+
+        match state.quote_deposit_sol_exact_out(msol_out, args) {
+            Ok(quote) => {
+                prop_assert!(quote.out_amount >= msol_out);
+                prop_assert_eq!(Ok(quote), state.quote_deposit_sol(quote.in_amount, args));
+            }
+            Err(e) => {
+                let lamports = state.pool_tokens_to_lamports_ceil(msol_out).unwrap();
+                prop_assert_eq!(Err(e), state.quote_deposit_sol(lamports, args));
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use mollusk_svm::{program::keyed_account_for_system_program, result::InstructionResult};
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, CreateAtaIdempotentIxData, CreateAtaIdempotentIxKeysOwned,
+    DepositSolQuoteArgs, TokenAccount,
+};
+use solana_account::Account;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    ix_from_sdk, mainnet_msol_leg_balance, mainnet_state, marinade_mainnet_accounts,
+    metas_from_keys_signer_writer, mollusk_marinade_prog, payer_account,
+};
+
+#[test]
+fn create_msol_ata_then_deposit() {
+    let state = mainnet_state();
+    let msol_leg_balance = mainnet_msol_leg_balance(&state);
+
+    let deposit_amount = 1_000_000_000;
+    let quote = state
+        .quote_deposit_sol_unchecked(deposit_amount, DepositSolQuoteArgs { msol_leg_balance })
+        .unwrap();
+
+    let wallet = Pubkey::new_unique();
+    let (ata, _bump) = marinade_staking_sdk::find_msol_ata(wallet.as_array());
+    let ata = Pubkey::new_from_array(ata);
+
+    let create_ata = ix_from_sdk(
+        &CreateAtaIdempotentIxKeysOwned::default()
+            .with_consts()
+            .with_funding(wallet.to_bytes())
+            .with_ata_of(wallet.to_bytes(), state.msol_mint)
+            .to_ix(&CreateAtaIdempotentIxData::new()),
+    );
+    assert_eq!(create_ata.accounts[1].pubkey, ata);
+
+    let deposit_keys = marinade_staking_sdk::DepositIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(&state)
+        .with_transfer_from(wallet.to_bytes())
+        .with_mint_to_wallet(wallet.to_bytes());
+    assert_eq!(deposit_keys.mint_to(), ata.as_array());
+
+    let deposit = Instruction {
+        program_id: Pubkey::new_from_array(marinade_staking_sdk::MARINADE_STAKING_PROGRAM),
+        accounts: metas_from_keys_signer_writer(
+            deposit_keys.0,
+            marinade_staking_sdk::DEPOSIT_IX_PREFIX_IS_SIGNER.0,
+            marinade_staking_sdk::DEPOSIT_IX_PREFIX_IS_WRITER.0,
+        ),
+        data: marinade_staking_sdk::DepositIxData::new(deposit_amount)
+            .to_buf()
+            .into(),
+    };
+
+    let mut mollusk = mollusk_marinade_prog();
+    mollusk_svm_programs_token::associated_token::add_program(&mut mollusk);
+
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .chain([
+            keyed_account_for_system_program(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            mollusk_svm_programs_token::associated_token::keyed_account(),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            (wallet, payer_account(10_000_000_000)),
+            (ata, Account::default()),
+        ])
+        .collect();
+
+    // creating twice must succeed
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&[create_ata.clone(), create_ata, deposit], &accounts);
+    raw_result.unwrap();
+
+    let ata_account = &resulting_accounts
+        .iter()
+        .find(|(pubkey, _)| pubkey == &ata)
+        .unwrap()
+        .1;
+    assert_eq!(
+        ata_account.owner.to_bytes(),
+        marinade_staking_sdk::TOKEN_PROGRAM
+    );
+    let token_account = TokenAccount::try_from_acc_data(&ata_account.data).unwrap();
+    assert_eq!(token_account.mint, state.msol_mint);
+    assert_eq!(token_account.owner, wallet.to_bytes());
+    assert_eq!(token_account.amount, quote.out_amount);
+}
@@ -2,23 +2,26 @@ use mollusk_svm::{
     program::{create_keyed_account_for_builtin_program, keyed_account_for_system_program},
     result::InstructionResult,
 };
-use sanctum_marinade_liquid_staking_core::{self as marinade_staking_sdk};
+use sanctum_marinade_liquid_staking_core::{self as marinade_staking_sdk, DepositSolQuoteArgs};
 use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
 
 use crate::common::{
-    marinade_mainnet_accounts, metas_from_keys_signer_writer, mollusk_marinade_prog,
-    msol_token_acc, payer_account, token_acc_balance, KeyedUiAccount,
+    mainnet_state, marinade_mainnet_accounts, metas_from_keys_signer_writer, mollusk_marinade_prog,
+    msol_token_acc, payer_account, set_token_acc_balance, token_acc_balance, KeyedUiAccount,
 };
 
 #[test]
 fn deposit_ix() {
-    let state_account = KeyedUiAccount::from_test_fixtures_file("marinade-state");
-    let state =
-        marinade_staking_sdk::State::borsh_de(state_account.account_data().as_slice()).unwrap();
+    let state = mainnet_state();
+
+    let msol_leg = KeyedUiAccount::from_test_fixtures_file("marinade-liq_pool_msol_leg");
+    let msol_leg_balance = u64::from_le_bytes(msol_leg.account_data()[64..72].try_into().unwrap());
 
     let deposit_amount: u64 = 1_000;
-    let quote = state.quote_deposit_sol_unchecked(deposit_amount).unwrap();
+    let quote = state
+        .quote_deposit_sol_unchecked(deposit_amount, DepositSolQuoteArgs { msol_leg_balance })
+        .unwrap();
 
     let transfer_from = Pubkey::new_unique();
     let mint_to = Pubkey::new_unique();
@@ -83,3 +86,119 @@ fn deposit_ix() {
     let msol_amount = token_acc_balance(&mint_to_account.1);
     assert_eq!(msol_amount, 1_000_000 + quote.out_amount);
 }
+
+#[test]
+fn deposit_ix_partially_swapped() {
+    let state = mainnet_state();
+
+    let msol_leg_balance = 1_000_000_000;
+    let deposit_amount: u64 = 5_000_000_000;
+    let quote = state
+        .quote_deposit_sol_unchecked(deposit_amount, DepositSolQuoteArgs { msol_leg_balance })
+        .unwrap();
+
+    assert_eq!(quote.msol_from_liq_pool, msol_leg_balance);
+    assert!(quote.msol_minted > 0);
+    assert_eq!(
+        quote.lamports_to_liq_pool + quote.lamports_to_reserve,
+        deposit_amount
+    );
+
+    let transfer_from = Pubkey::new_unique();
+    let mint_to = Pubkey::new_unique();
+
+    let mollusk = mollusk_marinade_prog();
+
+    let keys = marinade_staking_sdk::DepositIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(&state)
+        .with_transfer_from(transfer_from.to_bytes())
+        .with_mint_to(mint_to.to_bytes());
+
+    let metas = metas_from_keys_signer_writer(
+        keys.0,
+        marinade_staking_sdk::DEPOSIT_IX_PREFIX_IS_SIGNER.0,
+        marinade_staking_sdk::DEPOSIT_IX_PREFIX_IS_WRITER.0,
+    );
+
+    let ix = Instruction {
+        program_id: Pubkey::new_from_array(marinade_staking_sdk::MARINADE_STAKING_PROGRAM),
+        accounts: metas,
+        data: marinade_staking_sdk::DepositIxData::new(deposit_amount)
+            .to_buf()
+            .into(),
+    };
+
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .map(|(pubkey, mut account)| {
+            if pubkey.to_bytes() == state.liq_pool.msol_leg {
+                set_token_acc_balance(&mut account, msol_leg_balance);
+            }
+            (pubkey, account)
+        })
+        .chain([
+            keyed_account_for_system_program(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            (transfer_from, payer_account(10_000_000_000)),
+            (mint_to, msol_token_acc(0, transfer_from)),
+        ])
+        .collect();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&[ix], &accounts);
+
+    raw_result.unwrap();
+
+    let account = |pubkey: &[u8; 32]| {
+        let before = &accounts
+            .iter()
+            .find(|(pk, _)| pk.as_array() == pubkey)
+            .unwrap()
+            .1;
+        let after = &resulting_accounts
+            .iter()
+            .find(|(pk, _)| pk.as_array() == pubkey)
+            .unwrap()
+            .1;
+        (before.clone(), after.clone())
+    };
+
+    let (_, mint_to_after) = account(&mint_to.to_bytes());
+    assert_eq!(token_acc_balance(&mint_to_after), quote.out_amount);
+
+    let (leg_before, leg_after) = account(&state.liq_pool.msol_leg);
+    assert_eq!(
+        token_acc_balance(&leg_before) - token_acc_balance(&leg_after),
+        quote.msol_from_liq_pool
+    );
+
+    let (sol_leg_before, sol_leg_after) = account(&marinade_staking_sdk::LIQ_POOL_SOL_LEG_PUBKEY);
+    assert_eq!(
+        sol_leg_after.lamports - sol_leg_before.lamports,
+        quote.lamports_to_liq_pool
+    );
+
+    let (reserve_before, reserve_after) = account(&marinade_staking_sdk::RESERVE_PUBKEY);
+    assert_eq!(
+        reserve_after.lamports - reserve_before.lamports,
+        quote.lamports_to_reserve
+    );
+
+    let (expected_state, expected_args) = state
+        .apply_deposit_sol(&quote, DepositSolQuoteArgs { msol_leg_balance })
+        .unwrap();
+    let (_, state_after) = account(&marinade_staking_sdk::STATE_PUBKEY);
+    assert_eq!(
+        marinade_staking_sdk::State::borsh_de(state_after.data.as_slice()).unwrap(),
+        expected_state
+    );
+    assert_eq!(
+        token_acc_balance(&leg_after),
+        expected_args.msol_leg_balance
+    );
+}
@@ -1,30 +1,23 @@
-use borsh::BorshDeserialize;
 use const_crypto::bs58;
 use mollusk_svm::{
     program::{create_keyed_account_for_builtin_program, keyed_account_for_system_program},
     result::InstructionResult,
 };
 use sanctum_marinade_liquid_staking_core::{
-    self as marinade_staking_sdk, StakeAccountLamports, MARINADE_STAKING_PROGRAM,
+    self as marinade_staking_sdk, StakeAccountLamports, StakeStateV2, MARINADE_STAKING_PROGRAM,
 };
 use solana_account::Account;
 use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
-use solana_sdk::stake::state::StakeStateV2;
 
 use crate::common::{
-    marinade_mainnet_accounts, metas_from_keys_signer_writer, mollusk_marinade_prog,
+    mainnet_state, marinade_mainnet_accounts, metas_from_keys_signer_writer, mollusk_marinade_prog,
     msol_token_acc, payer_account, token_acc_balance, KeyedUiAccount,
 };
 
 #[test]
 fn deposit_stake_account_ix() {
-    let state_account = KeyedUiAccount::from_test_fixtures_file("marinade-state");
-    let state =
-        marinade_staking_sdk::State::borsh_de(state_account.account_data().as_slice()).unwrap();
-
-    // This is the validator index for BLADE1...
-    let validator_index = 0;
+    let state = mainnet_state();
 
     // This is the pubkey of the stake account in the fixture
     let staker_pubkey = bs58::decode_pubkey("73mx3pb9AccyrfoY6Agx3baocyqPUNmLFuTM2nbfv1T8");
@@ -33,19 +26,44 @@ fn deposit_stake_account_ix() {
     let stake_account = KeyedUiAccount::from_test_fixtures_file("stake_account");
 
     let stake_account_pubkey = bs58::decode_pubkey(&stake_account.pubkey);
-    let stake_state =
-        StakeStateV2::deserialize(&mut stake_account.account_data().as_slice()).unwrap();
+    let stake_state = StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap();
+
+    let mollusk = mollusk_marinade_prog();
+
+    let validator_list_account = KeyedUiAccount::from_test_fixtures_file("marinade-validator_list");
+    let validator_list_data = validator_list_account.account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    let clock = &mollusk.sysvars.clock;
+    let validator_index = marinade_staking_sdk::preflight_deposit_stake(
+        &stake_state,
+        &staker_pubkey,
+        &validator_list,
+        &marinade_staking_sdk::Clock {
+            slot: clock.slot,
+            epoch_start_timestamp: clock.epoch_start_timestamp,
+            epoch: clock.epoch,
+            leader_schedule_epoch: clock.leader_schedule_epoch,
+            unix_timestamp: clock.unix_timestamp,
+        },
+        &marinade_staking_sdk::StakeHistory(&[]),
+        Some(0),
+    )
+    .unwrap();
+    // This is the validator index for BLADE1...
+    assert_eq!(validator_index, 0);
 
     let duplication_flag_pubkey = Pubkey::new_unique();
 
     let quote = state
-        .quote_deposit_stake_unchecked(StakeAccountLamports {
-            staked: stake_state.delegation().unwrap().stake,
-            unstaked: stake_account
-                .account
-                .lamports
-                .saturating_sub(stake_state.delegation().unwrap().stake),
-        })
+        .quote_deposit_stake_unchecked(
+            StakeAccountLamports::from_stake_account(stake_account.account.lamports, &stake_state)
+                .unwrap(),
+        )
         .unwrap();
 
     let mint_to = Pubkey::new_unique();
@@ -74,8 +92,6 @@ fn deposit_stake_account_ix() {
         data: data.to_buf().into(),
     };
 
-    let mollusk = mollusk_marinade_prog();
-
     let accounts: Vec<_> = marinade_mainnet_accounts()
         .chain([
             keyed_account_for_system_program(),
@@ -112,4 +128,46 @@ fn deposit_stake_account_ix() {
 
     let msol_amount = token_acc_balance(&mint_to_account.1);
     assert_eq!(msol_amount, 1_000_000 + quote.tokens_out);
+
+    let (expected_state, expected_stake_record, expected_validator_record) = state
+        .apply_deposit_stake(
+            &quote,
+            stake_account_pubkey,
+            &validator_list.0[validator_index as usize],
+            mollusk.sysvars.clock.epoch,
+        )
+        .unwrap();
+
+    let resulting_account = |pubkey: &[u8; 32]| {
+        &resulting_accounts
+            .iter()
+            .find(|(pk, _)| pk.as_array() == pubkey)
+            .unwrap()
+            .1
+    };
+
+    let state_after = marinade_staking_sdk::State::borsh_de(
+        resulting_account(&marinade_staking_sdk::STATE_PUBKEY)
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(state_after, expected_state);
+
+    let stake_list_after = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &resulting_account(&marinade_staking_sdk::STAKE_LIST_PUBKEY).data,
+        state_after.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    assert_eq!(stake_list_after.0.last(), Some(&expected_stake_record));
+
+    let validator_list_after = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &resulting_account(&marinade_staking_sdk::VALIDATOR_LIST_PUBKEY).data,
+        state_after.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    assert_eq!(
+        validator_list_after.0[validator_index as usize],
+        expected_validator_record
+    );
 }
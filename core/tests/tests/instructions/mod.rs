@@ -1,3 +1,4 @@
+mod create_ata_idempotent;
 mod deposit;
 mod deposit_stake_account;
 mod withdraw_stake_account;
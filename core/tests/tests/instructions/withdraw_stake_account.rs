@@ -1,6 +1,6 @@
 use crate::common::{
-    marinade_mainnet_accounts, metas_from_keys_signer_writer, mollusk_marinade_prog,
-    msol_token_acc, payer_account, token_acc_balance, KeyedUiAccount,
+    ix_from_sdk, mainnet_state, marinade_mainnet_accounts, mollusk_marinade_prog, msol_token_acc,
+    payer_account, token_acc_balance, KeyedUiAccount,
 };
 use borsh::BorshDeserialize;
 use const_crypto::bs58;
@@ -10,15 +10,12 @@ use mollusk_svm::{
 };
 use sanctum_marinade_liquid_staking_core::{self as marinade_staking_sdk};
 use solana_account::Account;
-use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
 use solana_sdk::stake::state::StakeStateV2;
 
 #[test]
 fn withdraw_stake_account_ix() {
-    let state_account = KeyedUiAccount::from_test_fixtures_file("marinade-state");
-    let state =
-        marinade_staking_sdk::State::borsh_de(state_account.account_data().as_slice()).unwrap();
+    let state = mainnet_state();
 
     // This is the validator index for BLADE1...
     let validator_index = 589;
@@ -54,12 +51,6 @@ fn withdraw_stake_account_ix() {
         .with_split_stake_account(split_stake_account.to_bytes())
         .with_split_stake_rent_payer(user.to_bytes());
 
-    let metas = metas_from_keys_signer_writer(
-        keys.0,
-        marinade_staking_sdk::WITHDRAW_STAKE_ACCOUNT_IX_PREFIX_IS_SIGNER.0,
-        marinade_staking_sdk::WITHDRAW_STAKE_ACCOUNT_IX_PREFIX_IS_WRITER.0,
-    );
-
     let data = marinade_staking_sdk::WithdrawStakeAccountIxData::new(
         stake_index,
         validator_index,
@@ -67,11 +58,7 @@ fn withdraw_stake_account_ix() {
         user.to_bytes(),
     );
 
-    let ix = Instruction {
-        program_id: Pubkey::new_from_array(marinade_staking_sdk::MARINADE_STAKING_PROGRAM),
-        accounts: metas,
-        data: data.to_buf().into(),
-    };
+    let ix = ix_from_sdk(&keys.to_ix(&data));
 
     let mollusk = mollusk_marinade_prog();
 
@@ -121,4 +108,63 @@ fn withdraw_stake_account_ix() {
 
     let fee_amount = token_acc_balance(&treasury_msol_account.1) - before_treasury_msol_balance;
     assert_eq!(fee_amount, quote.fee_amount);
+
+    let stake_list_account = KeyedUiAccount::from_test_fixtures_file("marinade-stake_list");
+    let stake_list_data = stake_list_account.account_data();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let validator_list_account = KeyedUiAccount::from_test_fixtures_file("marinade-validator_list");
+    let validator_list_data = validator_list_account.account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    let (expected_state, expected_stake_record, expected_validator_record) = state
+        .apply_withdraw_stake(
+            &quote,
+            &stake_list.0[stake_index as usize],
+            &validator_list.0[validator_index as usize],
+        )
+        .unwrap();
+
+    let resulting_account = |pubkey: &[u8; 32]| {
+        &resulting_accounts
+            .iter()
+            .find(|(pk, _)| pk.as_array() == pubkey)
+            .unwrap()
+            .1
+    };
+
+    let state_after = marinade_staking_sdk::State::borsh_de(
+        resulting_account(&marinade_staking_sdk::STATE_PUBKEY)
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(state_after, expected_state);
+
+    let stake_list_after = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &resulting_account(&marinade_staking_sdk::STAKE_LIST_PUBKEY).data,
+        state_after.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    assert_eq!(
+        stake_list_after.0[stake_index as usize],
+        expected_stake_record
+    );
+
+    let validator_list_after = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &resulting_account(&marinade_staking_sdk::VALIDATOR_LIST_PUBKEY).data,
+        state_after.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    assert_eq!(
+        validator_list_after.0[validator_index as usize],
+        expected_validator_record
+    );
 }
@@ -0,0 +1,188 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    DepositSolQuoteArgs, DepositStakeQuoteArgs, GeometricAmounts, LiquidUnstakeQuoteArgs,
+    MarinadeError, QuoteLadderPoint, StakeAccountLamports, StakeRecord, WithdrawStakeQuoteArgs,
+};
+use sanctum_u64_ratio::Ratio;
+
+use crate::common::{any_amount, any_quotable_state, arbitrary_state, mainnet_state};
+
+const ONE_SOL: u64 = 1_000_000_000;
+
+const DOUBLING: Ratio<u64, u64> = Ratio { n: 2, d: 1 };
+
+/// Errors of the ladder's points in order, with consecutive duplicates removed
+fn error_regions<Q>(
+    ladder: impl Iterator<Item = QuoteLadderPoint<Q>>,
+) -> Vec<Option<MarinadeError>> {
+    let mut regions: Vec<_> = ladder.map(|point| point.quote.err()).collect();
+    regions.dedup();
+    regions
+}
+
+#[test]
+fn geometric_amounts_basic() {
+    assert_eq!(
+        GeometricAmounts::new(ONE_SOL, DOUBLING, 5).collect::<Vec<_>>(),
+        [ONE_SOL, 2 * ONE_SOL, 4 * ONE_SOL, 8 * ONE_SOL, 16 * ONE_SOL]
+    );
+    // rounds up and always increases
+    assert_eq!(
+        GeometricAmounts::new(0, Ratio { n: 3, d: 2 }, 6).collect::<Vec<_>>(),
+        [0, 1, 2, 3, 5, 8]
+    );
+    // ends before overflowing
+    assert_eq!(
+        GeometricAmounts::new(u64::MAX / 2 + 1, DOUBLING, 5).collect::<Vec<_>>(),
+        [u64::MAX / 2 + 1]
+    );
+}
+
+proptest! {
+    #[test]
+    fn geometric_amounts_strictly_increasing(
+        start in any_amount(),
+        n in 1..=1_000u64,
+        d in 1..=1_000u64,
+        count in 0..=100usize,
+    ) {
+        let amounts: Vec<_> = GeometricAmounts::new(start, Ratio { n, d }, count).collect();
+        prop_assert!(amounts.len() <= count);
+        if count > 0 {
+            prop_assert_eq!(amounts[0], start);
+        }
+        prop_assert!(amounts.windows(2).all(|w| w[0] < w[1]));
+    }
+}
+
+proptest! {
+    #[test]
+    fn ladder_points_match_quotes(
+        // arbitrary states include those a QuoteContext cannot be built for
+        state in prop_oneof![any_quotable_state(), arbitrary_state()],
+        msol_leg_balance in any_amount(),
+        sol_leg_lamports in any_amount(),
+        unstaked in any_amount(),
+        delegated in any_amount(),
+        start in any_amount(),
+    ) {
+        let in_amounts = GeometricAmounts::new(start, DOUBLING, 8);
+        let sol_args = DepositSolQuoteArgs { msol_leg_balance };
+        let stake_args = DepositStakeQuoteArgs { msol_leg_balance };
+        let withdraw_args = WithdrawStakeQuoteArgs {
+            stake_record: StakeRecord::new([1; 32], delegated, 0, false),
+        };
+        let unstake_args = LiquidUnstakeQuoteArgs { sol_leg_lamports };
+
+        for (amount, point) in in_amounts.zip(state.deposit_sol_ladder(in_amounts, sol_args)) {
+            prop_assert_eq!(point.in_amount, amount);
+            prop_assert_eq!(point.quote, state.quote_deposit_sol(amount, sol_args));
+        }
+        for point in state.deposit_stake_ladder(in_amounts, unstaked, stake_args) {
+            prop_assert_eq!(
+                point.quote,
+                state.quote_deposit_stake(
+                    StakeAccountLamports { staked: point.in_amount, unstaked },
+                    stake_args
+                )
+            );
+        }
+        for point in state.withdraw_stake_ladder(in_amounts, withdraw_args) {
+            prop_assert_eq!(
+                point.quote,
+                state.quote_withdraw_stake(point.in_amount, withdraw_args)
+            );
+        }
+        for point in state.liquid_unstake_ladder(in_amounts, unstake_args) {
+            prop_assert_eq!(
+                point.quote,
+                state.quote_liquid_unstake(point.in_amount, unstake_args)
+            );
+        }
+
+        if let Some(ctx) = state.quote_context(msol_leg_balance) {
+            prop_assert!(ctx
+                .deposit_sol_ladder(in_amounts)
+                .eq(state.deposit_sol_ladder(in_amounts, sol_args)));
+            prop_assert!(ctx
+                .deposit_stake_ladder(in_amounts, unstaked)
+                .eq(state.deposit_stake_ladder(in_amounts, unstaked, stake_args)));
+            prop_assert!(ctx
+                .withdraw_stake_ladder(in_amounts, withdraw_args)
+                .eq(state.withdraw_stake_ladder(in_amounts, withdraw_args)));
+            prop_assert!(ctx
+                .liquid_unstake_ladder(in_amounts, unstake_args)
+                .eq(state.liquid_unstake_ladder(in_amounts, unstake_args)));
+        }
+    }
+}
+
+#[test]
+fn mainnet_ladders_show_where_each_path_stops() {
+    let mut state = mainnet_state();
+    state.min_deposit = ONE_SOL;
+    state.stake_system.min_stake = 20 * ONE_SOL;
+    state.min_withdraw = ONE_SOL;
+    // 1000 SOL headroom under the staking cap
+    state.staking_sol_cap = u64::MAX - 1;
+    let total_lamports_under_control =
+        state.staking_sol_cap - state.quote_context(0).unwrap().staking_cap_headroom();
+    state.staking_sol_cap = total_lamports_under_control + 1_000 * ONE_SOL;
+    // 10% steps from 0.5 to ~5000 SOL
+    let in_amounts = || GeometricAmounts::new(ONE_SOL / 2, Ratio { n: 11, d: 10 }, 100);
+
+    assert_eq!(
+        error_regions(state.deposit_sol_ladder(
+            in_amounts(),
+            DepositSolQuoteArgs {
+                msol_leg_balance: 0
+            }
+        )),
+        [
+            Some(MarinadeError::DepositAmountIsTooLow),
+            None,
+            Some(MarinadeError::StakingIsCapped)
+        ]
+    );
+    assert_eq!(
+        error_regions(state.deposit_stake_ladder(
+            in_amounts(),
+            ONE_SOL / 100,
+            DepositStakeQuoteArgs {
+                msol_leg_balance: 0
+            }
+        )),
+        [
+            Some(MarinadeError::TooLowDelegationInDepositingStake),
+            None,
+            Some(MarinadeError::StakingIsCapped)
+        ]
+    );
+    assert_eq!(
+        error_regions(state.withdraw_stake_ladder(
+            in_amounts(),
+            WithdrawStakeQuoteArgs {
+                stake_record: StakeRecord::new([1; 32], 100 * ONE_SOL, 0, false),
+            }
+        )),
+        [
+            Some(MarinadeError::WithdrawStakeLamportsIsTooLow),
+            None,
+            Some(MarinadeError::StakeAccountRemainderTooLow),
+            Some(MarinadeError::SelectedStakeAccountHasNotEnoughFunds),
+        ]
+    );
+    assert_eq!(
+        error_regions(state.liquid_unstake_ladder(
+            in_amounts(),
+            LiquidUnstakeQuoteArgs {
+                sol_leg_lamports: 1_000 * ONE_SOL
+            }
+        )),
+        [
+            Some(MarinadeError::WithdrawAmountIsTooLow),
+            None,
+            Some(MarinadeError::InsufficientLiquidity)
+        ]
+    );
+}
@@ -0,0 +1,117 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    DepositSolQuoteArgs, DepositStakeQuoteArgs, MarinadeError, StakeAccountLamports, StakeRecord,
+    WithdrawStakeQuoteArgs,
+};
+
+use crate::common::any_quotable_state;
+
+fn staked(staked: u64) -> StakeAccountLamports {
+    StakeAccountLamports {
+        staked,
+        unstaked: 0,
+    }
+}
+
+proptest! {
+    #[test]
+    fn deposit_sol_limits(state in any_quotable_state(), msol_leg_balance: u64) {
+        let args = DepositSolQuoteArgs { msol_leg_balance };
+
+        match (state.min_deposit_sol(msol_leg_balance), state.max_deposit_sol(msol_leg_balance)) {
+            (Ok(min), Ok(max)) => {
+                prop_assert!(min <= max);
+                prop_assert!(state.quote_deposit_sol(min, args).is_ok());
+                prop_assert!(state.quote_deposit_sol(max, args).is_ok());
+                if min > 0 {
+                    prop_assert!(state.quote_deposit_sol(min - 1, args).is_err());
+                }
+                if max < u64::MAX {
+                    // CalculationFailure if the amount is so large that the mSOL output overflows
+                    prop_assert!(matches!(
+                        state.quote_deposit_sol(max + 1, args),
+                        Err(MarinadeError::StakingIsCapped | MarinadeError::CalculationFailure)
+                    ));
+                }
+            }
+            (Err(_), Err(_)) => {
+                prop_assert!(state.quote_deposit_sol(state.min_deposit, args).is_err());
+            }
+            (min, max) => prop_assert!(false, "min: {min:?}, max: {max:?}"),
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn deposit_stake_limits(state in any_quotable_state(), msol_leg_balance: u64) {
+        let args = DepositStakeQuoteArgs { msol_leg_balance };
+
+        match (
+            state.min_deposit_stake(msol_leg_balance),
+            state.max_deposit_stake(msol_leg_balance),
+        ) {
+            (Ok(min), Ok(max)) => {
+                prop_assert!(min <= max);
+                prop_assert!(state.quote_deposit_stake(staked(min), args).is_ok());
+                prop_assert!(state.quote_deposit_stake(staked(max), args).is_ok());
+                if min > 0 {
+                    prop_assert!(state.quote_deposit_stake(staked(min - 1), args).is_err());
+                }
+                if max < u64::MAX {
+                    // CalculationFailure if the amount is so large that the mSOL output overflows
+                    prop_assert!(matches!(
+                        state.quote_deposit_stake(staked(max + 1), args),
+                        Err(MarinadeError::StakingIsCapped | MarinadeError::CalculationFailure)
+                    ));
+                }
+            }
+            (Err(_), Err(_)) => {
+                prop_assert!(state
+                    .quote_deposit_stake(staked(state.stake_system.min_stake), args)
+                    .is_err());
+            }
+            (min, max) => prop_assert!(false, "min: {min:?}, max: {max:?}"),
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn withdraw_stake_limits(
+        state in any_quotable_state(),
+        delegated in 0..=10_000_000_000_000u64,
+    ) {
+        let stake_record = StakeRecord::new([1u8; 32], delegated, 0, false);
+        let args = WithdrawStakeQuoteArgs { stake_record };
+
+        match (
+            state.min_withdraw_stake(&stake_record),
+            state.max_withdraw_stake(&stake_record),
+        ) {
+            (Ok(min), Ok(max)) => {
+                prop_assert!(min <= max);
+                prop_assert!(state.quote_withdraw_stake(min, args).is_ok());
+                prop_assert!(state.quote_withdraw_stake(max, args).is_ok());
+                if min > 0 {
+                    prop_assert_eq!(
+                        state.quote_withdraw_stake(min - 1, args),
+                        Err(MarinadeError::WithdrawStakeLamportsIsTooLow)
+                    );
+                }
+                if max < u64::MAX {
+                    prop_assert!(state.quote_withdraw_stake(max + 1, args).is_err());
+                }
+            }
+            (Err(_), Err(_)) => {
+                // no amount passes, so the amounts around the min split must all fail
+                let min_split_quote = state
+                    .quote_withdraw_stake_exact_out_unchecked(state.stake_system.min_stake);
+                if let Some(quote) = min_split_quote {
+                    prop_assert!(state.quote_withdraw_stake(quote.tokens_in, args).is_err());
+                }
+            }
+            (min, max) => prop_assert!(false, "min: {min:?}, max: {max:?}"),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use mollusk_svm::{program::keyed_account_for_system_program, result::InstructionResult};
+use sanctum_marinade_liquid_staking_core::{
+    find_msol_ata, DepositSolQuoteArgs, LiquidUnstakeQuoteArgs, MarinadeError, State,
+    LIQ_POOL_MSOL_LEG_PUBKEY, LIQ_POOL_SOL_LEG_PUBKEY, MARINADE_STAKING_PROGRAM, MSOL_MINT_ADDR,
+    STATE_PUBKEY, SYSTEM_PROGRAM, TOKEN_PROGRAM,
+};
+use solana_account::Account;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    mainnet_state, marinade_mainnet_accounts, mollusk_marinade_prog, msol_token_acc, payer_account,
+    token_acc_balance,
+};
+
+const LIQUID_UNSTAKE_IX_DISCM: [u8; 8] = [30, 30, 119, 240, 191, 227, 12, 16];
+
+const USER_MSOL_BALANCE: u64 = 1_000_000_000_000_000;
+
+/// The SDK does not build liquid unstake instructions,
+/// so this is built by hand to check quotes against the program
+fn liquid_unstake_ix(state: &State, user: Pubkey, msol_amount: u64) -> Instruction {
+    let user_msol_ata = Pubkey::new_from_array(find_msol_ata(user.as_array()).0);
+    let mut data = LIQUID_UNSTAKE_IX_DISCM.to_vec();
+    data.extend_from_slice(&msol_amount.to_le_bytes());
+    Instruction {
+        program_id: Pubkey::new_from_array(MARINADE_STAKING_PROGRAM),
+        accounts: vec![
+            AccountMeta::new(Pubkey::new_from_array(STATE_PUBKEY), false),
+            AccountMeta::new(Pubkey::new_from_array(MSOL_MINT_ADDR), false),
+            AccountMeta::new(Pubkey::new_from_array(LIQ_POOL_SOL_LEG_PUBKEY), false),
+            AccountMeta::new(Pubkey::new_from_array(LIQ_POOL_MSOL_LEG_PUBKEY), false),
+            AccountMeta::new(Pubkey::new_from_array(state.treasury_msol_account), false),
+            AccountMeta::new(user_msol_ata, false),
+            AccountMeta::new_readonly(user, true),
+            AccountMeta::new(user, true),
+            AccountMeta::new_readonly(Pubkey::new_from_array(SYSTEM_PROGRAM), false),
+            AccountMeta::new_readonly(Pubkey::new_from_array(TOKEN_PROGRAM), false),
+        ],
+        data,
+    }
+}
+
+/// Mainnet accounts with `sol_leg_lamports` in the liquidity pool's SOL leg
+/// and a `user` holding [`USER_MSOL_BALANCE`]
+fn liquid_unstake_accounts(
+    sol_leg_lamports: u64,
+    user: Pubkey,
+    user_lamports: u64,
+) -> Vec<(Pubkey, Account)> {
+    marinade_mainnet_accounts()
+        .map(|(pk, acc)| {
+            if pk.as_array() == &LIQ_POOL_SOL_LEG_PUBKEY {
+                (pk, payer_account(sol_leg_lamports))
+            } else {
+                (pk, acc)
+            }
+        })
+        .chain([
+            keyed_account_for_system_program(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            (user, payer_account(user_lamports)),
+            (
+                Pubkey::new_from_array(find_msol_ata(user.as_array()).0),
+                msol_token_acc(USER_MSOL_BALANCE, user),
+            ),
+        ])
+        .collect()
+}
+
+fn account<'a>(accounts: &'a [(Pubkey, Account)], pubkey: &[u8; 32]) -> &'a Account {
+    &accounts
+        .iter()
+        .find(|(pk, _)| pk.as_array() == pubkey)
+        .unwrap()
+        .1
+}
+
+#[test]
+fn liquid_unstake_quote_matches_program() {
+    let state = mainnet_state();
+    let mollusk = mollusk_marinade_prog();
+    let user = Pubkey::new_unique();
+    let user_lamports = 1_000_000_000;
+
+    // (sol leg lamports, mSOL in)
+    let cases = [
+        // well above the liquidity target after unstaking
+        (100_000_000_000_000, 1_000_000_000),
+        // within the linear fee region
+        (state.liq_pool.lp_liquidity_target / 2, 1_000_000_000_000),
+        (state.liq_pool.lp_liquidity_target, 10_000_000_000_000),
+        // removing all the liquidity
+        (10_000_000_000, 9_000_000_000),
+        (10_000_000_000, 100_000_000_000),
+        // too small
+        (state.liq_pool.lp_liquidity_target, 1),
+    ];
+
+    for (sol_leg_lamports, msol_amount) in cases {
+        let args = LiquidUnstakeQuoteArgs { sol_leg_lamports };
+        let quote = state.quote_liquid_unstake(msol_amount, args);
+
+        let accounts = liquid_unstake_accounts(sol_leg_lamports, user, user_lamports);
+
+        let InstructionResult {
+            raw_result,
+            resulting_accounts,
+            ..
+        } = mollusk
+            .process_instruction_chain(&[liquid_unstake_ix(&state, user, msol_amount)], &accounts);
+
+        let quote = match (raw_result, quote) {
+            (Ok(()), Ok(quote)) => quote,
+            (Err(err), Err(expected)) => {
+                assert_eq!(
+                    err,
+                    solana_instruction::error::InstructionError::Custom(
+                        expected.to_custom_code().unwrap()
+                    ),
+                    "{sol_leg_lamports} {msol_amount}"
+                );
+                continue;
+            }
+            (res, quote) => panic!("{sol_leg_lamports} {msol_amount}: {res:?} {quote:?}"),
+        };
+
+        assert_eq!(
+            account(&resulting_accounts, user.as_array()).lamports,
+            user_lamports + quote.lamports_out
+        );
+        assert_eq!(
+            token_acc_balance(account(&resulting_accounts, &state.treasury_msol_account)),
+            token_acc_balance(account(&accounts, &state.treasury_msol_account))
+                + quote.treasury_msol_cut
+        );
+        assert_eq!(
+            token_acc_balance(account(&resulting_accounts, &LIQ_POOL_MSOL_LEG_PUBKEY)),
+            token_acc_balance(account(&accounts, &LIQ_POOL_MSOL_LEG_PUBKEY)) + msol_amount
+                - quote.treasury_msol_cut
+        );
+    }
+}
+
+#[test]
+fn apply_liquid_unstake_matches_program() {
+    let state = mainnet_state();
+    let mollusk = mollusk_marinade_prog();
+    let user = Pubkey::new_unique();
+    let sol_leg_lamports = state.liq_pool.lp_liquidity_target;
+    let msol_amount = 1_000_000_000_000;
+
+    let accounts = liquid_unstake_accounts(sol_leg_lamports, user, 1_000_000_000);
+    let unstake_args = LiquidUnstakeQuoteArgs { sol_leg_lamports };
+    let deposit_args = DepositSolQuoteArgs {
+        msol_leg_balance: token_acc_balance(account(&accounts, &LIQ_POOL_MSOL_LEG_PUBKEY)),
+    };
+    let quote = state
+        .quote_liquid_unstake(msol_amount, unstake_args)
+        .unwrap();
+    let (applied, unstake_args, deposit_args) = state
+        .apply_liquid_unstake(&quote, unstake_args, deposit_args)
+        .unwrap();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk
+        .process_instruction_chain(&[liquid_unstake_ix(&state, user, msol_amount)], &accounts);
+    raw_result.unwrap();
+
+    let program_state =
+        State::borsh_de(account(&resulting_accounts, &STATE_PUBKEY).data.as_slice()).unwrap();
+    assert_eq!(applied.liq_pool, program_state.liq_pool);
+    assert_eq!(applied.msol_supply, program_state.msol_supply);
+    assert_eq!(
+        account(&resulting_accounts, &LIQ_POOL_SOL_LEG_PUBKEY).lamports,
+        unstake_args.sol_leg_lamports
+    );
+    assert_eq!(
+        token_acc_balance(account(&resulting_accounts, &LIQ_POOL_MSOL_LEG_PUBKEY)),
+        deposit_args.msol_leg_balance
+    );
+}
+
+#[test]
+fn liquid_unstake_fee_is_linear_in_remaining_liquidity() {
+    let state = mainnet_state();
+    let target = state.liq_pool.lp_liquidity_target;
+    assert_eq!(state.liquid_unstake_fee(0), Some(state.liq_pool.lp_max_fee));
+    assert_eq!(
+        state.liquid_unstake_fee(target),
+        Some(state.liq_pool.lp_min_fee)
+    );
+    let mid = state.liquid_unstake_fee(target / 2).unwrap().basis_points;
+    assert_eq!(
+        mid,
+        state.liq_pool.lp_max_fee.basis_points
+            - (state.liq_pool.lp_max_fee.basis_points - state.liq_pool.lp_min_fee.basis_points) / 2
+    );
+
+    let mut paused = state.clone();
+    paused.paused = true;
+    assert_eq!(
+        paused.quote_liquid_unstake(
+            1_000_000_000,
+            LiquidUnstakeQuoteArgs {
+                sol_leg_lamports: target
+            }
+        ),
+        Err(MarinadeError::ProgramIsPaused)
+    );
+}
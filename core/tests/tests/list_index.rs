@@ -0,0 +1,114 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, ListIndex, MarinadeListIndex,
+};
+
+use crate::common::{mainnet_state, KeyedUiAccount};
+
+#[test]
+fn list_index_mainnet() {
+    let state = mainnet_state();
+
+    let stake_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-stake_list").account_data();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    let index = MarinadeListIndex::new(&validator_list, &stake_list);
+
+    assert_eq!(index.validators.len(), validator_list.0.len());
+    for (i, v) in validator_list.0.iter().enumerate() {
+        assert_eq!(index.validator_index(v.validator_account()), Some(i as u32));
+        assert_eq!(index.vote_account(i as u32), Some(v.validator_account()));
+    }
+    assert_eq!(index.stakes.len(), stake_list.0.len());
+    for (i, s) in stake_list.0.iter().enumerate() {
+        assert_eq!(index.stake_index(s.stake_account()), Some(i as u32));
+        assert_eq!(index.stake_account(i as u32), Some(s.stake_account()));
+    }
+    assert_eq!(index.vote_account(validator_list.0.len() as u32), None);
+    assert_eq!(index.stake_index(&[0; 32]), None);
+
+    // reloading the same lists is a no-op
+    let mut reloaded = index.clone();
+    reloaded.reload(&validator_list, &stake_list);
+    assert_eq!(reloaded, index);
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    Push(u8),
+    SwapRemove(u32),
+}
+
+fn any_op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..24u8).prop_map(Op::Push),
+        (0..20u32).prop_map(Op::SwapRemove),
+    ]
+}
+
+/// Distinct accounts from a small pool so that lists overlap
+fn any_accounts() -> impl Strategy<Value = Vec<[u8; 32]>> {
+    prop::collection::btree_set(0..24u8, 0..16)
+        .prop_map(|set| set.into_iter().map(|b| [b; 32]).collect::<Vec<_>>())
+        .prop_shuffle()
+}
+
+fn assert_matches(index: &ListIndex, accounts: &[[u8; 32]]) {
+    assert_eq!(index, &ListIndex::from_accounts(accounts));
+    for (i, a) in accounts.iter().enumerate() {
+        assert_eq!(index.index_of(a), Some(i as u32));
+        assert_eq!(index.account_at(i as u32), Some(a));
+    }
+}
+
+proptest! {
+    #[test]
+    fn list_index_ops_match_list(
+        accounts in any_accounts(),
+        ops in prop::collection::vec(any_op(), 0..32),
+    ) {
+        let mut model = accounts.clone();
+        let mut index = ListIndex::from_accounts(&accounts);
+        for op in ops {
+            match op {
+                Op::Push(b) => {
+                    if model.contains(&[b; 32]) {
+                        continue;
+                    }
+                    model.push([b; 32]);
+                    prop_assert_eq!(index.push([b; 32]) as usize, model.len() - 1);
+                }
+                Op::SwapRemove(i) => {
+                    let expected = ((i as usize) < model.len()).then(|| model.swap_remove(i as usize));
+                    prop_assert_eq!(index.swap_remove(i), expected);
+                    if let Some(removed) = expected {
+                        prop_assert_eq!(index.index_of(&removed), None);
+                    }
+                }
+            }
+            assert_matches(&index, &model);
+        }
+    }
+
+    #[test]
+    fn list_index_reload_matches_fresh(
+        old in any_accounts(),
+        new in any_accounts(),
+    ) {
+        let mut index = ListIndex::from_accounts(&old);
+        index.reload(&new);
+        assert_matches(&index, &new);
+    }
+}
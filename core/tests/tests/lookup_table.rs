@@ -0,0 +1,174 @@
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, AddressLookupTable, DepositIxKeysOwned,
+    DepositStakeAccountIxKeysOwned, StakeRecord, UpdateActiveIxKeysOwned,
+    UpdateDeactivatedIxKeysOwned, WithdrawStakeAccountIxKeysOwned, WithdrawStakeCandidate,
+    LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY, LIQ_POOL_MSOL_LEG_PUBKEY, LIQ_POOL_SOL_LEG_PUBKEY,
+    MARINADE_LOOKUP_TABLE_LEN, MSOL_MINT_ADDR, MSOL_MINT_AUTHORITY_PUBKEY, PACKET_DATA_SIZE,
+    RESERVE_PUBKEY, STAKE_DEPOSIT_AUTHORITY_PUBKEY, STAKE_LIST_PUBKEY,
+    STAKE_WITHDRAW_AUTHORITY_PUBKEY, STATE_PUBKEY, VALIDATOR_LIST_PUBKEY,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    hash::Hash,
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
+};
+
+use crate::common::{ix_from_sdk, mainnet_state, KeyedUiAccount};
+
+#[test]
+fn mainnet_lookup_table_addresses() {
+    let state = mainnet_state();
+    let addresses = state.lookup_table_addresses(&STATE_PUBKEY).unwrap();
+
+    for (i, a) in addresses.iter().enumerate() {
+        assert!(!addresses[..i].contains(a));
+    }
+    for expected in [
+        STATE_PUBKEY,
+        MSOL_MINT_ADDR,
+        VALIDATOR_LIST_PUBKEY,
+        STAKE_LIST_PUBKEY,
+        LIQ_POOL_MSOL_LEG_PUBKEY,
+        RESERVE_PUBKEY,
+        MSOL_MINT_AUTHORITY_PUBKEY,
+        LIQ_POOL_SOL_LEG_PUBKEY,
+        LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY,
+        STAKE_WITHDRAW_AUTHORITY_PUBKEY,
+        STAKE_DEPOSIT_AUTHORITY_PUBKEY,
+    ] {
+        assert!(addresses.contains(&expected));
+    }
+
+    // every key the builders fill in without user input is in the table
+    let builder_keys = DepositIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(&state)
+        .0
+        .into_iter()
+        .chain(
+            DepositStakeAccountIxKeysOwned::default()
+                .with_consts()
+                .with_mainnet_consts()
+                .with_keys_from_stake_pool(&state)
+                .0,
+        )
+        .chain(
+            WithdrawStakeAccountIxKeysOwned::default()
+                .with_consts()
+                .with_mainnet_consts()
+                .with_keys_from_stake_pool(&state)
+                .0,
+        )
+        .chain(
+            UpdateActiveIxKeysOwned::default()
+                .with_consts()
+                .with_mainnet_consts()
+                .with_keys_from_stake_pool(&state)
+                .0,
+        )
+        .chain(
+            UpdateDeactivatedIxKeysOwned::default()
+                .with_consts()
+                .with_mainnet_consts()
+                .with_keys_from_stake_pool(&state)
+                .0,
+        );
+    let mut n_builder_keys = 0;
+    for key in builder_keys.filter(|key| *key != [0; 32]) {
+        assert!(
+            addresses.contains(&key),
+            "{} not in lookup table",
+            Pubkey::new_from_array(key)
+        );
+        n_builder_keys += 1;
+    }
+    assert!(n_builder_keys > MARINADE_LOOKUP_TABLE_LEN);
+
+    let mut bad_bump = state.clone();
+    bad_bump.reserve_bump_seed = bad_bump.reserve_bump_seed.wrapping_add(1);
+    assert_ne!(
+        bad_bump.lookup_table_addresses(&STATE_PUBKEY),
+        state.lookup_table_addresses(&STATE_PUBKEY)
+    );
+}
+
+#[test]
+fn withdraw_stake_plan_v0_messages() {
+    const RECORD_LAMPORTS: u64 = 3_000_000_000;
+    const N_CANDIDATES: u32 = 12;
+
+    let state = mainnet_state();
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    let (validator_index, validator_record) = validator_list
+        .0
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, v)| v.active_balance())
+        .unwrap();
+    let validator_index = validator_index as u32;
+    let validator_record = *validator_record;
+
+    // many small stake records so that the withdrawal is split across many steps
+    let candidates = (0..N_CANDIDATES).map(|stake_index| WithdrawStakeCandidate {
+        stake_index,
+        validator_index,
+        stake_record: StakeRecord::new(Pubkey::new_unique().to_bytes(), RECORD_LAMPORTS, 0, false),
+        validator_record,
+        quote: state.quote_withdraw_stake_unchecked(0).unwrap(),
+    });
+    let user = Pubkey::new_unique().to_bytes();
+    let keys = WithdrawStakeAccountIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(&state)
+        .with_burn_msol_from_wallet(user)
+        .with_split_stake_rent_payer(user);
+    let plan = state
+        .plan_withdraw_stake(candidates, 10_000_000_000, &keys, user, |_| {
+            Pubkey::new_unique().to_bytes()
+        })
+        .unwrap();
+    assert!(plan.steps.len() >= 6);
+
+    let addresses = state.lookup_table_addresses(&STATE_PUBKEY).unwrap();
+    let alt = AddressLookupTable {
+        key: Pubkey::new_unique().to_bytes(),
+        addresses: &addresses,
+    };
+    let blockhash = [7; 32];
+    let messages = plan.v0_messages(&user, &blockhash, &[alt]).unwrap();
+    assert!(messages.len() < plan.transactions.len());
+
+    let mut steps = plan.steps.iter();
+    for message in messages {
+        assert!(message.transaction_len() <= PACKET_DATA_SIZE);
+
+        let ixs: Vec<_> = steps
+            .by_ref()
+            .take(message.instructions.len())
+            .map(|step| ix_from_sdk(&step.ix))
+            .collect();
+        let expected = VersionedMessage::V0(
+            v0::Message::try_compile(
+                &Pubkey::new_from_array(user),
+                &ixs,
+                &[AddressLookupTableAccount {
+                    key: Pubkey::new_from_array(alt.key),
+                    addresses: addresses.map(Pubkey::new_from_array).to_vec(),
+                }],
+                Hash::new_from_array(blockhash),
+            )
+            .unwrap(),
+        )
+        .serialize();
+        assert_eq!(message.serialize(), expected);
+    }
+    assert_eq!(steps.len(), 0);
+}
@@ -0,0 +1,219 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    AccountMeta, AddressLookupTable, DynInstruction, MarinadeError, Message, SYSTEM_PROGRAM,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    hash::Hash,
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
+};
+
+use crate::common::ix_from_dyn;
+
+fn solana_legacy_bytes(
+    ixs: &[DynInstruction],
+    fee_payer: &[u8; 32],
+    blockhash: &[u8; 32],
+) -> Vec<u8> {
+    solana_sdk::message::Message::new_with_blockhash(
+        &ixs.iter().map(ix_from_dyn).collect::<Vec<_>>(),
+        Some(&Pubkey::new_from_array(*fee_payer)),
+        &Hash::new_from_array(*blockhash),
+    )
+    .serialize()
+}
+
+fn solana_v0_bytes(
+    ixs: &[DynInstruction],
+    fee_payer: &[u8; 32],
+    blockhash: &[u8; 32],
+    alts: &[AddressLookupTable],
+) -> Vec<u8> {
+    VersionedMessage::V0(
+        v0::Message::try_compile(
+            &Pubkey::new_from_array(*fee_payer),
+            &ixs.iter().map(ix_from_dyn).collect::<Vec<_>>(),
+            &alts
+                .iter()
+                .map(|alt| AddressLookupTableAccount {
+                    key: Pubkey::new_from_array(alt.key),
+                    addresses: alt
+                        .addresses
+                        .iter()
+                        .copied()
+                        .map(Pubkey::new_from_array)
+                        .collect(),
+                })
+                .collect::<Vec<_>>(),
+            Hash::new_from_array(*blockhash),
+        )
+        .unwrap(),
+    )
+    .serialize()
+}
+
+/// Transaction size with `n` signatures
+fn transaction_len(message_bytes: &[u8], n: usize) -> usize {
+    1 + 64 * n + message_bytes.len()
+}
+
+/// Instructions over a small pool of keys so that keys are shared between
+/// instructions, programs and the fee payer
+fn any_ixs_over(pool: [[u8; 32]; 8]) -> impl Strategy<Value = Vec<DynInstruction>> {
+    prop::collection::vec(
+        (
+            0..pool.len(),
+            prop::collection::vec((0..pool.len(), any::<bool>(), any::<bool>()), 0..6),
+            prop::collection::vec(any::<u8>(), 0..16),
+        ),
+        0..5,
+    )
+    .prop_map(move |ixs| {
+        ixs.into_iter()
+            .map(|(program, accounts, data)| DynInstruction {
+                program_id: pool[program],
+                accounts: accounts
+                    .into_iter()
+                    .map(|(i, is_signer, is_writable)| AccountMeta {
+                        pubkey: pool[i],
+                        is_signer,
+                        is_writable,
+                    })
+                    .collect(),
+                data,
+            })
+            .collect()
+    })
+}
+
+fn any_message_inputs(
+) -> impl Strategy<Value = ([[u8; 32]; 8], Vec<DynInstruction>, [u8; 32], [u8; 32])> {
+    any::<[[u8; 32]; 8]>().prop_flat_map(|pool| {
+        (
+            Just(pool),
+            any_ixs_over(pool),
+            prop_oneof![
+                (0..pool.len()).prop_map(move |i| pool[i]),
+                any::<[u8; 32]>()
+            ],
+            any::<[u8; 32]>(),
+        )
+    })
+}
+
+proptest! {
+    #[test]
+    fn legacy_matches_solana((_pool, ixs, fee_payer, blockhash) in any_message_inputs()) {
+        let message = Message::compile_legacy(&ixs, &fee_payer, &blockhash).unwrap();
+        let bytes = message.serialize();
+        prop_assert_eq!(&bytes, &solana_legacy_bytes(&ixs, &fee_payer, &blockhash));
+        prop_assert_eq!(message.serialized_len(), bytes.len());
+        prop_assert_eq!(
+            message.transaction_len(),
+            transaction_len(&bytes, message.header.num_required_signatures.into())
+        );
+    }
+
+    #[test]
+    fn v0_matches_solana(
+        (pool, ixs, fee_payer, blockhash) in any_message_inputs(),
+        alt_keys: [[u8; 32]; 2],
+        alt_members in prop::collection::vec(
+            prop::collection::vec((any::<bool>(), any::<[u8; 32]>()), 0..10),
+            2,
+        ),
+    ) {
+        // lookup tables with some of the pool's keys in random positions
+        let alt_addresses: Vec<Vec<[u8; 32]>> = alt_members
+            .into_iter()
+            .map(|members| {
+                members
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (from_pool, key))| if from_pool { pool[i % pool.len()] } else { key })
+                    .collect()
+            })
+            .collect();
+        let alts: Vec<_> = alt_keys
+            .iter()
+            .zip(alt_addresses.iter())
+            .map(|(key, addresses)| AddressLookupTable { key: *key, addresses })
+            .collect();
+
+        let message = Message::compile_v0(&ixs, &fee_payer, &blockhash, &alts).unwrap();
+        let bytes = message.serialize();
+        prop_assert_eq!(&bytes, &solana_v0_bytes(&ixs, &fee_payer, &blockhash, &alts));
+        prop_assert_eq!(message.serialized_len(), bytes.len());
+        prop_assert_eq!(
+            message.transaction_len(),
+            transaction_len(&bytes, message.header.num_required_signatures.into())
+        );
+
+        let no_alts = Message::compile_v0(&ixs, &fee_payer, &blockhash, &[]).unwrap();
+        prop_assert_eq!(no_alts.serialize(), solana_v0_bytes(&ixs, &fee_payer, &blockhash, &[]));
+    }
+}
+
+#[test]
+fn nonce_account_not_looked_up() {
+    let nonce = [1; 32];
+    let fee_payer = [2; 32];
+    let ixs = [
+        DynInstruction {
+            program_id: SYSTEM_PROGRAM,
+            accounts: vec![AccountMeta {
+                pubkey: nonce,
+                is_signer: false,
+                is_writable: true,
+            }],
+            data: vec![4, 0, 0, 0],
+        },
+        DynInstruction {
+            program_id: [3; 32],
+            accounts: vec![AccountMeta {
+                pubkey: [4; 32],
+                is_signer: false,
+                is_writable: true,
+            }],
+            data: vec![],
+        },
+    ];
+    let addresses = [nonce, [4; 32]];
+    let alts = [AddressLookupTable {
+        key: [5; 32],
+        addresses: &addresses,
+    }];
+    let message = Message::compile_v0(&ixs, &fee_payer, &[6; 32], &alts).unwrap();
+    assert!(message.account_keys.contains(&nonce));
+    assert!(!message.account_keys.contains(&[4; 32]));
+    assert_eq!(
+        message.serialize(),
+        solana_v0_bytes(&ixs, &fee_payer, &[6; 32], &alts)
+    );
+}
+
+#[test]
+fn too_many_accounts() {
+    let ixs: Vec<_> = (0..=255u8)
+        .map(|i| {
+            let mut pubkey = [1; 32];
+            pubkey[0] = i;
+            DynInstruction {
+                program_id: [0; 32],
+                accounts: vec![AccountMeta {
+                    pubkey,
+                    is_signer: false,
+                    is_writable: false,
+                }],
+                data: vec![],
+            }
+        })
+        .collect();
+    // 256 accounts + the program + the fee payer
+    assert_eq!(
+        Message::compile_legacy(&ixs, &[2; 32], &[0; 32]),
+        Err(MarinadeError::AccountIndexOverflow)
+    );
+    // exactly 256
+    assert!(Message::compile_legacy(&ixs[..254], &[2; 32], &[0; 32]).is_ok());
+}
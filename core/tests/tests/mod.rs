@@ -1,2 +1,32 @@
+mod activation;
+mod crank;
+#[cfg(feature = "alloc")]
+mod crank_plan;
+mod error;
+mod exact_out;
 mod instructions;
+mod ladder;
+mod limits;
+mod liquid_unstake;
+#[cfg(feature = "alloc")]
+mod list_index;
+#[cfg(feature = "alloc")]
+mod lookup_table;
+#[cfg(feature = "alloc")]
+mod message;
+mod no_panic;
+mod preflight;
+mod quote_context;
+mod quoter;
+mod reconcile;
+mod stake_state;
+mod stake_targets;
 mod state;
+mod token;
+#[cfg(feature = "std")]
+mod transaction;
+mod user;
+mod validate;
+mod withdraw_candidates;
+#[cfg(feature = "alloc")]
+mod withdraw_plan;
@@ -0,0 +1,141 @@
+//! Random, possibly corrupted `State`s must never panic the public API,
+//! only return errors
+
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    deposit_sol_ix, list::ListAccount, DepositSolQuoteArgs, DepositStakeQuoteArgs,
+    LiquidUnstakeQuoteArgs, ReconcileArgs, StakeAccountLamports, WithdrawStakeQuoteArgs,
+    STATE_PUBKEY,
+};
+
+#[cfg(feature = "alloc")]
+use sanctum_marinade_liquid_staking_core::{withdraw_stake_ix, WithdrawStakeCandidate};
+
+use crate::common::{any_amount, any_stake_record, any_validators, arbitrary_state};
+
+#[cfg(feature = "alloc")]
+use crate::common::any_stake_records;
+
+proptest! {
+    #[test]
+    fn quotes_never_panic(
+        state in arbitrary_state(),
+        amount in any_amount(),
+        unstaked in any_amount(),
+        msol_leg_balance in any_amount(),
+        stake_record in any_stake_record(),
+        validator_record in any_validators(0..=1).prop_map(|v| v.first().copied().unwrap_or_default()),
+        epoch: u64,
+    ) {
+        let sol_args = DepositSolQuoteArgs { msol_leg_balance };
+        let stake_args = DepositStakeQuoteArgs { msol_leg_balance };
+        let withdraw_args = WithdrawStakeQuoteArgs { stake_record };
+        let unstake_args = LiquidUnstakeQuoteArgs { sol_leg_lamports: unstaked };
+        let stake_account_lamports = StakeAccountLamports { staked: amount, unstaked };
+
+        let _ = stake_account_lamports.total();
+        let _ = state.supply_over_lamports();
+        let _ = state.lamports_over_supply();
+        let _ = state.lamports_to_pool_tokens(amount);
+        let _ = state.pool_tokens_to_lamports(amount);
+        let _ = state.lamports_to_pool_tokens_ceil(amount);
+        let _ = state.pool_tokens_to_lamports_ceil(amount);
+        let _ = state.is_sol_deposit_too_low(amount);
+        let _ = state.is_stake_deposit_too_low(amount);
+        let _ = state.will_deposit_exceed_staking_cap(amount, msol_leg_balance);
+
+        let _ = state.quote_deposit_sol_unchecked(amount, sol_args);
+        let _ = state.quote_deposit_stake_unchecked(stake_account_lamports);
+        let _ = state.quote_withdraw_stake_unchecked(amount);
+        let _ = state.liquid_unstake_fee(amount);
+        let _ = state.quote_liquid_unstake(amount, unstake_args);
+        let _ = state.quote_deposit_sol_exact_out_unchecked(amount, sol_args);
+        let _ = state.quote_deposit_stake_exact_out_unchecked(amount, unstaked);
+        let _ = state.quote_withdraw_stake_exact_out_unchecked(amount);
+        let _ = state.quote_deposit_sol_exact_out(amount, sol_args);
+        let _ = state.quote_deposit_stake_exact_out(amount, unstaked, stake_args);
+        let _ = state.quote_withdraw_stake_exact_out(amount, withdraw_args);
+
+        let _ = state.max_deposit_sol(msol_leg_balance);
+        let _ = state.min_deposit_sol(msol_leg_balance);
+        let _ = state.max_deposit_stake(msol_leg_balance);
+        let _ = state.min_deposit_stake(msol_leg_balance);
+        let _ = state.max_withdraw_stake(&stake_record);
+        let _ = state.min_withdraw_stake(&stake_record);
+
+        if let Ok(quote) = state.quote_deposit_sol(amount, sol_args) {
+            let _ = state.apply_deposit_sol(&quote, sol_args);
+        }
+        if let Ok(quote) = state.quote_deposit_stake(stake_account_lamports, stake_args) {
+            let _ = state.apply_deposit_stake(&quote, [1; 32], &validator_record, epoch);
+        }
+        if let Ok(quote) = state.quote_withdraw_stake(amount, withdraw_args) {
+            let _ = state.apply_withdraw_stake(&quote, &stake_record, &validator_record);
+        }
+        if let Ok(quote) = state.quote_liquid_unstake(amount, unstake_args) {
+            let _ = state.apply_liquid_unstake(&quote, unstake_args, sol_args);
+        }
+
+        let _ = deposit_sol_ix(&state, [1; 32], amount, sol_args);
+    }
+
+    #[test]
+    fn pool_accounting_never_panics(
+        state in arbitrary_state(),
+        reserve_lamports in any_amount(),
+        msol_mint_supply in any_amount(),
+        validators in any_validators(0..=8),
+    ) {
+        let args = ReconcileArgs { reserve_lamports, msol_mint_supply };
+        let _ = state.reconcile(args);
+        let state = state.reconciled(args);
+
+        let _ = state.stake_delta(reserve_lamports);
+        let total_stake_target = state.total_stake_target(reserve_lamports);
+        let validator_list = ListAccount(&validators);
+        for v in &validators {
+            let _ = state.validator_stake_target(v, total_stake_target);
+            let _ = state.validator_overstake(v);
+        }
+        let _ = state.validator_stake_targets(&validator_list, reserve_lamports).count();
+
+        let _ = state.lookup_table_addresses(&STATE_PUBKEY);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn withdraw_planning_never_panics(
+        state in arbitrary_state(),
+        pool_tokens in any_amount(),
+        validators in any_validators(0..=8),
+        stake_records in any_stake_records(any_amount(), 0..=8),
+    ) {
+        let validator_list = ListAccount(&validators);
+        let stake_list = ListAccount(&stake_records);
+        // stake record i is delegated to validator i
+        let voter_of = |stake_account: &[u8; 32]| {
+            let i = stake_records.iter().position(|r| r.stake_account() == stake_account)?;
+            validators.get(i).map(|v| *v.validator_account())
+        };
+
+        let candidates: Vec<WithdrawStakeCandidate> = state
+            .withdraw_stake_candidates(&validator_list, &stake_list, voter_of, pool_tokens)
+            .collect();
+        let _ = state
+            .max_withdraw_stake_candidates(&validator_list, &stake_list, voter_of)
+            .count();
+        let ranked = state.ranked_withdraw_stake_candidates(
+            &validator_list,
+            &stake_list,
+            voter_of,
+            pool_tokens,
+            WithdrawStakeCandidate::prefer_overstaked(&state),
+        );
+        for candidate in candidates.iter().chain(&ranked) {
+            let _ = withdraw_stake_ix(&state, candidate, [1; 32], [2; 32], pool_tokens);
+        }
+
+        let keys = Default::default();
+        let _ = state.plan_withdraw_stake(ranked, pool_tokens, &keys, [1; 32], |i| [i as u8; 32]);
+    }
+}
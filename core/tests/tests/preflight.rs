@@ -0,0 +1,141 @@
+use const_crypto::bs58;
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, preflight_deposit_stake, Clock, MarinadeError, StakeHistory,
+    StakeHistoryEntry, StakeStateV2,
+};
+
+use crate::common::{mainnet_state, KeyedUiAccount};
+
+const STAKER: &str = "73mx3pb9AccyrfoY6Agx3baocyqPUNmLFuTM2nbfv1T8";
+
+const CLOCK: Clock = Clock {
+    slot: 432_000 * 760 + 69,
+    epoch_start_timestamp: 0,
+    epoch: 760,
+    leader_schedule_epoch: 761,
+    unix_timestamp: 1_700_000_000,
+};
+
+const EMPTY_STAKE_HISTORY: StakeHistory = StakeHistory(&[]);
+
+fn fixtures() -> (StakeStateV2, KeyedUiAccount, marinade_staking_sdk::State) {
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("stake_account");
+    let stake_state = StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap();
+    let validator_list = KeyedUiAccount::from_test_fixtures_file("marinade-validator_list");
+    let state = mainnet_state();
+    (stake_state, validator_list, state)
+}
+
+fn preflight(
+    stake_state: &StakeStateV2,
+    stake_authority: &[u8; 32],
+    stake_history: &StakeHistory,
+) -> Result<u32, MarinadeError> {
+    let (_, validator_list_account, state) = fixtures();
+    let data = validator_list_account.account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    preflight_deposit_stake(
+        stake_state,
+        stake_authority,
+        &validator_list,
+        &CLOCK,
+        stake_history,
+        Some(0),
+    )
+}
+
+#[test]
+fn preflight_deposit_stake_fixture() {
+    let (stake_state, _, _) = fixtures();
+    let staker = bs58::decode_pubkey(STAKER);
+
+    assert_eq!(
+        preflight(&stake_state, &staker, &EMPTY_STAKE_HISTORY),
+        Ok(0)
+    );
+}
+
+#[test]
+fn preflight_deposit_stake_errors() {
+    let (stake_state, _, _) = fixtures();
+    let staker = bs58::decode_pubkey(STAKER);
+    let StakeStateV2::Stake(meta, stake, flags) = stake_state else {
+        panic!("fixture should be delegated");
+    };
+
+    let initialized = StakeStateV2::Initialized(meta);
+    assert_eq!(
+        preflight(&initialized, &staker, &EMPTY_STAKE_HISTORY),
+        Err(MarinadeError::RequiredDelegatedStake)
+    );
+
+    let mut deactivating = stake;
+    deactivating.delegation.deactivation_epoch = CLOCK.epoch;
+    assert_eq!(
+        preflight(
+            &StakeStateV2::Stake(meta, deactivating, flags),
+            &staker,
+            &EMPTY_STAKE_HISTORY
+        ),
+        Err(MarinadeError::RequiredActiveStake)
+    );
+
+    let mut locked = meta;
+    locked.lockup.epoch = CLOCK.epoch + 1;
+    assert_eq!(
+        preflight(
+            &StakeStateV2::Stake(locked, stake, flags),
+            &staker,
+            &EMPTY_STAKE_HISTORY
+        ),
+        Err(MarinadeError::StakeAccountWithLockup)
+    );
+
+    let mut activating = stake;
+    activating.delegation.activation_epoch = CLOCK.epoch;
+    assert_eq!(
+        preflight(
+            &StakeStateV2::Stake(meta, activating, flags),
+            &staker,
+            &EMPTY_STAKE_HISTORY
+        ),
+        Err(MarinadeError::DepositingNotActivatedStake)
+    );
+
+    // activated last epoch, but the cluster had more activating stake than could warm up
+    activating.delegation.activation_epoch = CLOCK.epoch - 1;
+    let history = [StakeHistoryEntry::new(
+        CLOCK.epoch - 1,
+        1_000_000_000_000,
+        1_000_000_000_000,
+        0,
+    )];
+    assert_eq!(
+        preflight(
+            &StakeStateV2::Stake(meta, activating, flags),
+            &staker,
+            &StakeHistory(&history)
+        ),
+        Err(MarinadeError::DepositingNotActivatedStake)
+    );
+
+    assert_eq!(
+        preflight(&stake_state, &[1; 32], &EMPTY_STAKE_HISTORY),
+        Err(MarinadeError::WrongStakeAuthority)
+    );
+
+    let mut unlisted = stake;
+    unlisted.delegation.voter_pubkey = [1; 32];
+    assert_eq!(
+        preflight(
+            &StakeStateV2::Stake(meta, unlisted, flags),
+            &staker,
+            &EMPTY_STAKE_HISTORY
+        ),
+        Err(MarinadeError::WrongValidatorAccountOrIndex)
+    );
+}
@@ -0,0 +1,170 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    DepositSolQuoteArgs, DepositStakeQuoteArgs, LiquidUnstakeQuoteArgs, MarinadeError,
+    StakeAccountLamports, State, WithdrawStakeQuoteArgs,
+};
+
+use crate::common::{any_amount, any_quotable_state, any_stake_record, arbitrary_state};
+
+fn any_quote_state() -> impl Strategy<Value = State> {
+    prop_oneof![arbitrary_state(), any_quotable_state()]
+}
+
+/// Reference implementation of the total lamports under control, `None` on overflow
+fn total_lamports_under_control(state: &State) -> Option<u64> {
+    [
+        state.stake_system.delayed_unstake_cooling_down,
+        state.emergency_cooling_down,
+        state.available_reserve_balance,
+    ]
+    .into_iter()
+    .try_fold(
+        state.validator_system.total_active_balance,
+        u64::checked_add,
+    )
+}
+
+/// Reference implementation of converting `amount` at `n / d` in u128,
+/// 1:1 if the ratio is zero
+fn convert(amount: u64, n: u64, d: u64, ceil: bool) -> Option<u64> {
+    if n == 0 || d == 0 {
+        return Some(amount);
+    }
+    let xn = u128::from(amount) * u128::from(n);
+    let d = u128::from(d);
+    u64::try_from(if ceil { xn.div_ceil(d) } else { xn / d }).ok()
+}
+
+proptest! {
+    #[test]
+    fn quote_context_matches_state(
+        state in any_quote_state(),
+        amount in any_amount(),
+        unstaked in any_amount(),
+        msol_leg_balance in any_amount(),
+        stake_record in any_stake_record(),
+        sol_leg_lamports in any_amount(),
+    ) {
+        let sol_args = DepositSolQuoteArgs { msol_leg_balance };
+        let stake_args = DepositStakeQuoteArgs { msol_leg_balance };
+        let withdraw_args = WithdrawStakeQuoteArgs { stake_record };
+        let stake_account_lamports = StakeAccountLamports { staked: amount, unstaked };
+        let unstake_args = LiquidUnstakeQuoteArgs { sol_leg_lamports };
+
+        let total_lamports_under_control = total_lamports_under_control(&state);
+        let staked = total_lamports_under_control
+            .map(|total| total.saturating_sub(state.circulating_ticket_balance));
+        let supply = state.msol_supply;
+        for (converted, expected) in [
+            (
+                state.lamports_to_pool_tokens(amount),
+                staked.and_then(|staked| convert(amount, supply, staked, false)),
+            ),
+            (
+                state.pool_tokens_to_lamports(amount),
+                staked.and_then(|staked| convert(amount, staked, supply, false)),
+            ),
+            (
+                state.lamports_to_pool_tokens_ceil(amount),
+                staked.and_then(|staked| convert(amount, supply, staked, true)),
+            ),
+            (
+                state.pool_tokens_to_lamports_ceil(amount),
+                staked.and_then(|staked| convert(amount, staked, supply, true)),
+            ),
+        ] {
+            prop_assert_eq!(converted, expected);
+        }
+        // https://github.com/marinade-finance/liquid-staking-program/blob/main/programs/marinade-finance/src/state/mod.rs#L196
+        let exceeds_staking_cap = state
+            .quote_deposit_sol_unchecked(amount, sol_args)
+            .zip(total_lamports_under_control)
+            .map(|(quote, total)| {
+                quote.lamports_to_reserve != 0
+                    && total.saturating_add(quote.lamports_to_reserve) > state.staking_sol_cap
+            })
+            .ok_or(MarinadeError::CalculationFailure);
+        prop_assert_eq!(
+            state.will_deposit_exceed_staking_cap(amount, msol_leg_balance),
+            exceeds_staking_cap
+        );
+
+        let Some(ctx) = state.quote_context(msol_leg_balance) else {
+            prop_assert_eq!(state.lamports_to_pool_tokens(amount), None);
+            prop_assert_eq!(state.pool_tokens_to_lamports(amount), None);
+            return Ok(());
+        };
+
+        prop_assert_eq!(ctx.lamports_to_pool_tokens(amount), state.lamports_to_pool_tokens(amount));
+        prop_assert_eq!(ctx.pool_tokens_to_lamports(amount), state.pool_tokens_to_lamports(amount));
+        prop_assert_eq!(
+            ctx.lamports_to_pool_tokens_ceil(amount),
+            state.lamports_to_pool_tokens_ceil(amount)
+        );
+        prop_assert_eq!(
+            ctx.pool_tokens_to_lamports_ceil(amount),
+            state.pool_tokens_to_lamports_ceil(amount)
+        );
+        prop_assert_eq!(
+            ctx.will_deposit_exceed_staking_cap(amount),
+            state.will_deposit_exceed_staking_cap(amount, msol_leg_balance)
+        );
+
+        prop_assert_eq!(
+            ctx.quote_deposit_sol_unchecked(amount),
+            state.quote_deposit_sol_unchecked(amount, sol_args)
+        );
+        prop_assert_eq!(ctx.quote_deposit_sol(amount), state.quote_deposit_sol(amount, sol_args));
+        prop_assert_eq!(
+            ctx.quote_deposit_stake_unchecked(stake_account_lamports),
+            state.quote_deposit_stake_unchecked(stake_account_lamports)
+        );
+        prop_assert_eq!(
+            ctx.quote_deposit_stake(stake_account_lamports),
+            state.quote_deposit_stake(stake_account_lamports, stake_args)
+        );
+        prop_assert_eq!(
+            ctx.quote_withdraw_stake_unchecked(amount),
+            state.quote_withdraw_stake_unchecked(amount)
+        );
+        prop_assert_eq!(
+            ctx.quote_withdraw_stake(amount, withdraw_args),
+            state.quote_withdraw_stake(amount, withdraw_args)
+        );
+        prop_assert_eq!(
+            ctx.quote_liquid_unstake_unchecked(amount, unstake_args),
+            state.quote_liquid_unstake_unchecked(amount, unstake_args)
+        );
+        prop_assert_eq!(
+            ctx.quote_liquid_unstake(amount, unstake_args),
+            state.quote_liquid_unstake(amount, unstake_args)
+        );
+
+        prop_assert_eq!(
+            ctx.quote_deposit_sol_exact_out(amount),
+            state.quote_deposit_sol_exact_out(amount, sol_args)
+        );
+        prop_assert_eq!(
+            ctx.quote_deposit_stake_exact_out(amount, unstaked),
+            state.quote_deposit_stake_exact_out(amount, unstaked, stake_args)
+        );
+        prop_assert_eq!(
+            ctx.quote_withdraw_stake_exact_out(amount, withdraw_args),
+            state.quote_withdraw_stake_exact_out(amount, withdraw_args)
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn staking_cap_headroom_is_exact(state in any_quotable_state()) {
+        // with an empty mSOL leg, all deposited lamports go to the reserve.
+        // Large amounts may fail with CalculationFailure instead
+        let empty_leg = state.quote_context(0).unwrap();
+        let headroom = empty_leg.staking_cap_headroom();
+        prop_assert_ne!(empty_leg.will_deposit_exceed_staking_cap(headroom), Ok(true));
+        if let Some(over) = headroom.checked_add(1) {
+            prop_assert_ne!(empty_leg.will_deposit_exceed_staking_cap(over), Ok(false));
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    DepositSolQuoteArgs, DepositStakeQuoteArgs, LstQuote, LstQuoter, StakeAccountLamports,
+    StakeRecord, WithdrawStakeQuoteArgs, STATE_PUBKEY,
+};
+
+use crate::common::{any_amount, any_quotable_state, mainnet_state, KeyedUiAccount};
+
+/// Stands in for routing code that knows nothing about Marinade:
+/// the LST out of a SOL deposit and the SOL out of withdrawing it back as stake
+fn round_trip<Q: LstQuoter>(
+    quoter: &Q,
+    lamports: u64,
+    deposit_args: Q::DepositSolArgs,
+    withdraw_args: Q::WithdrawStakeArgs,
+) -> Result<(u64, u64), Q::Error> {
+    let deposit = quoter.quote_deposit_sol(lamports, deposit_args)?;
+    let withdraw = quoter.quote_withdraw_stake(deposit.out_amount(), withdraw_args)?;
+    Ok((deposit.out_amount(), withdraw.out_amount()))
+}
+
+#[test]
+fn generic_round_trip_mainnet() {
+    let state = mainnet_state();
+    let msol_leg = KeyedUiAccount::from_test_fixtures_file("marinade-liq_pool_msol_leg");
+    let deposit_args =
+        DepositSolQuoteArgs::try_from_msol_leg(&state, &STATE_PUBKEY, &msol_leg.account_data())
+            .unwrap();
+    let withdraw_args = WithdrawStakeQuoteArgs {
+        stake_record: StakeRecord::new([1; 32], 1_000_000_000_000, 0, false),
+    };
+    let lamports = 10_000_000_000;
+
+    let (msol, lamports_out) = round_trip(&state, lamports, deposit_args, withdraw_args).unwrap();
+    let ctx = state.quote_context(deposit_args.msol_leg_balance).unwrap();
+    assert_eq!(
+        round_trip(&ctx, lamports, (), withdraw_args),
+        Ok((msol, lamports_out))
+    );
+
+    assert_eq!(msol, state.sol_to_lst(lamports).unwrap());
+    // withdraw stake account fee
+    assert!(lamports_out < lamports);
+    assert!(lamports_out <= state.lst_to_sol(msol).unwrap());
+}
+
+proptest! {
+    #[test]
+    fn lst_quoter_matches_inherent(
+        state in any_quotable_state(),
+        amount in any_amount(),
+        unstaked in any_amount(),
+        msol_leg_balance in any_amount(),
+        delegated in any_amount(),
+    ) {
+        let stake_account_lamports = StakeAccountLamports { staked: amount, unstaked };
+        let sol_args = DepositSolQuoteArgs { msol_leg_balance };
+        let stake_args = DepositStakeQuoteArgs { msol_leg_balance };
+        let withdraw_args = WithdrawStakeQuoteArgs {
+            stake_record: StakeRecord::new([1; 32], delegated, 0, false),
+        };
+
+        prop_assert_eq!(
+            LstQuoter::lst_to_sol(&state, amount),
+            state.pool_tokens_to_lamports(amount)
+        );
+        prop_assert_eq!(
+            LstQuoter::sol_to_lst(&state, amount),
+            state.lamports_to_pool_tokens(amount)
+        );
+
+        let deposit_sol = LstQuoter::quote_deposit_sol(&state, amount, sol_args);
+        prop_assert_eq!(deposit_sol, state.quote_deposit_sol(amount, sol_args));
+        if let Ok(quote) = deposit_sol {
+            prop_assert_eq!(quote.in_amount(), amount);
+            prop_assert_eq!(quote.out_amount(), quote.out_amount);
+        }
+
+        let deposit_stake =
+            LstQuoter::quote_deposit_stake(&state, stake_account_lamports, stake_args);
+        prop_assert_eq!(
+            deposit_stake,
+            state.quote_deposit_stake(stake_account_lamports, stake_args)
+        );
+        if let Ok(quote) = deposit_stake {
+            prop_assert_eq!(Some(quote.in_amount()), stake_account_lamports.total());
+            prop_assert_eq!(quote.out_amount(), quote.tokens_out);
+        }
+
+        let withdraw_stake = LstQuoter::quote_withdraw_stake(&state, amount, withdraw_args);
+        prop_assert_eq!(withdraw_stake, state.quote_withdraw_stake(amount, withdraw_args));
+        if let Ok(quote) = withdraw_stake {
+            prop_assert_eq!(quote.in_amount(), amount);
+            prop_assert_eq!(quote.out_amount(), quote.lamports_staked);
+        }
+
+        let ctx = state.quote_context(msol_leg_balance).unwrap();
+        prop_assert_eq!(LstQuoter::lst_to_sol(&ctx, amount), ctx.pool_tokens_to_lamports(amount));
+        prop_assert_eq!(LstQuoter::sol_to_lst(&ctx, amount), ctx.lamports_to_pool_tokens(amount));
+        prop_assert_eq!(LstQuoter::quote_deposit_sol(&ctx, amount, ()), deposit_sol);
+        prop_assert_eq!(
+            LstQuoter::quote_deposit_stake(&ctx, stake_account_lamports, ()),
+            deposit_stake
+        );
+        prop_assert_eq!(
+            LstQuoter::quote_withdraw_stake(&ctx, amount, withdraw_args),
+            withdraw_stake
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use const_crypto::bs58;
+use mollusk_svm::{
+    program::{create_keyed_account_for_builtin_program, keyed_account_for_system_program},
+    result::InstructionResult,
+};
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, DepositSolQuoteArgs, MarinadeError, ReconcileArgs, StakeStateV2,
+    UpdateActiveIxData, UpdateActiveIxKeysOwned,
+};
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    ix_from_sdk, mainnet_state, marinade_mainnet_accounts, mollusk_marinade_prog, payer_account,
+    KeyedUiAccount,
+};
+
+/// Runs `update_active` on the `withdraw_stake_account` fixture
+/// with the reserve and mSOL mint drifted from the state's virtual values
+/// and checks the resulting state against [`marinade_staking_sdk::State::reconcile`]
+fn assert_update_reconciles(reserve_drift: i64, msol_supply_drift: i64) {
+    let state = mainnet_state();
+
+    let args = ReconcileArgs {
+        reserve_lamports: (state.available_reserve_balance + state.rent_exempt_for_token_acc)
+            .checked_add_signed(reserve_drift)
+            .unwrap(),
+        msol_mint_supply: state
+            .msol_supply
+            .checked_add_signed(msol_supply_drift)
+            .unwrap(),
+    };
+    let reconciliation = state.reconcile(args);
+    assert_eq!(reconciliation.reserve_drift, reserve_drift.into());
+    assert_eq!(reconciliation.msol_supply_drift, msol_supply_drift.into());
+    assert_eq!(
+        reconciliation.is_drifted(),
+        reserve_drift != 0 || msol_supply_drift != 0
+    );
+
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("withdraw_stake_account");
+    let stake_account_lamports = stake_account.account.lamports;
+    let stake_state = StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap();
+    // moved to the reserve by the update
+    let extra_lamports = stake_account_lamports
+        - stake_state.delegation().unwrap().stake
+        - stake_state.meta().unwrap().rent_exempt_reserve;
+
+    let ix = ix_from_sdk(
+        &UpdateActiveIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state)
+            .with_stake_account(bs58::decode_pubkey(&stake_account.pubkey))
+            .to_ix(&UpdateActiveIxData::new(10, 589)),
+    );
+
+    let mollusk = mollusk_marinade_prog();
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .map(|(pk, mut account)| {
+            if pk.as_array() == &marinade_staking_sdk::RESERVE_PUBKEY {
+                account.lamports = args.reserve_lamports;
+            }
+            if pk.as_array() == &marinade_staking_sdk::MSOL_MINT_ADDR {
+                account.data[36..44].copy_from_slice(&args.msol_mint_supply.to_le_bytes());
+            }
+            (pk, account)
+        })
+        .chain([
+            keyed_account_for_system_program(),
+            create_keyed_account_for_builtin_program(
+                &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+                "solana_stake_program",
+            ),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            mollusk.sysvars.keyed_account_for_stake_history_sysvar(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            (
+                Pubkey::new_from_array(state.operational_sol_account),
+                payer_account(1_000_000_000),
+            ),
+        ])
+        .collect();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&[ix], &accounts);
+    raw_result.unwrap();
+
+    let state_after = marinade_staking_sdk::State::borsh_de(
+        resulting_accounts
+            .iter()
+            .find(|(pk, _)| pk.as_array() == &marinade_staking_sdk::STATE_PUBKEY)
+            .unwrap()
+            .1
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+
+    let reconciled = state.reconciled(args);
+    assert_eq!(
+        state_after.available_reserve_balance,
+        reconciled.available_reserve_balance + extra_lamports
+    );
+    assert_eq!(state_after.msol_supply, reconciled.msol_supply);
+    assert_eq!(state_after.staking_sol_cap, reconciled.staking_sol_cap);
+}
+
+#[test]
+fn reconcile_no_drift() {
+    assert_update_reconciles(0, 0);
+}
+
+#[test]
+fn reconcile_reserve_transfer_and_msol_burn() {
+    assert_update_reconciles(1_234_567, -7_654_321);
+}
+
+#[test]
+fn reconcile_msol_minted_outside_program() {
+    assert_update_reconciles(-1_000, 1);
+}
+
+#[test]
+fn quote_against_reconciled_state() {
+    let state = mainnet_state();
+    let deposit_args = DepositSolQuoteArgs {
+        msol_leg_balance: 0,
+    };
+
+    let burnt = state.reconciled(ReconcileArgs {
+        reserve_lamports: state.available_reserve_balance + state.rent_exempt_for_token_acc,
+        msol_mint_supply: state.msol_supply - 1_000_000_000_000,
+    });
+    // fewer mSOL for the same lamports means each mSOL is worth more
+    assert!(
+        burnt
+            .quote_deposit_sol(1_000_000_000, deposit_args)
+            .unwrap()
+            .msol_minted
+            < state
+                .quote_deposit_sol(1_000_000_000, deposit_args)
+                .unwrap()
+                .msol_minted
+    );
+
+    let minted_outside = state.reconciled(ReconcileArgs {
+        reserve_lamports: state.available_reserve_balance + state.rent_exempt_for_token_acc,
+        msol_mint_supply: state.msol_supply + 1,
+    });
+    assert_eq!(
+        minted_outside.quote_deposit_sol(1_000_000_000, deposit_args),
+        Err(MarinadeError::StakingIsCapped)
+    );
+}
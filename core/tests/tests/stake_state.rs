@@ -0,0 +1,112 @@
+use borsh::BorshDeserialize;
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, StakeAccountLamports, StakeStateV2,
+};
+use solana_sdk::stake::state::StakeStateV2 as SolanaStakeStateV2;
+
+use crate::common::KeyedUiAccount;
+
+fn assert_matches_solana(fixture: &str) {
+    let stake_account = KeyedUiAccount::from_test_fixtures_file(fixture);
+    let data = stake_account.account_data();
+    assert_eq!(data.len(), StakeStateV2::ACCOUNT_LEN);
+
+    let sdk = StakeStateV2::borsh_de(data.as_slice()).unwrap();
+    let solana = SolanaStakeStateV2::deserialize(&mut data.as_slice()).unwrap();
+
+    let (
+        StakeStateV2::Stake(meta, stake, flags),
+        SolanaStakeStateV2::Stake(s_meta, s_stake, s_flags),
+    ) = (sdk, solana)
+    else {
+        panic!("{fixture} should be a delegated stake account");
+    };
+
+    assert_eq!(meta.rent_exempt_reserve, s_meta.rent_exempt_reserve);
+    assert_eq!(meta.authorized.staker, s_meta.authorized.staker.to_bytes());
+    assert_eq!(
+        meta.authorized.withdrawer,
+        s_meta.authorized.withdrawer.to_bytes()
+    );
+    assert_eq!(meta.lockup.unix_timestamp, s_meta.lockup.unix_timestamp);
+    assert_eq!(meta.lockup.epoch, s_meta.lockup.epoch);
+    assert_eq!(meta.lockup.custodian, s_meta.lockup.custodian.to_bytes());
+
+    let (delegation, s_delegation) = (stake.delegation, s_stake.delegation);
+    assert_eq!(
+        delegation.voter_pubkey,
+        s_delegation.voter_pubkey.to_bytes()
+    );
+    assert_eq!(delegation.stake, s_delegation.stake);
+    assert_eq!(delegation.activation_epoch, s_delegation.activation_epoch);
+    assert_eq!(
+        delegation.deactivation_epoch,
+        s_delegation.deactivation_epoch
+    );
+    #[allow(deprecated)]
+    let s_rate = s_delegation.warmup_cooldown_rate;
+    assert_eq!(
+        delegation.warmup_cooldown_rate().to_bits(),
+        s_rate.to_bits()
+    );
+    assert_eq!(stake.credits_observed, s_stake.credits_observed);
+    assert_eq!(
+        flags.bits,
+        borsh::to_vec(&s_flags).unwrap()[0],
+        "{fixture} flags"
+    );
+
+    let mut reserialized = Vec::new();
+    sdk.borsh_ser(&mut reserialized).unwrap();
+    assert_eq!(reserialized, data[..reserialized.len()]);
+
+    assert_eq!(
+        StakeAccountLamports::from_stake_account(stake_account.account.lamports, &sdk),
+        Some(StakeAccountLamports {
+            staked: s_delegation.stake,
+            unstaked: stake_account.account.lamports - s_delegation.stake,
+        })
+    );
+}
+
+#[test]
+fn stake_account_fixture_matches_solana() {
+    assert_matches_solana("stake_account");
+}
+
+#[test]
+fn withdraw_stake_account_fixture_matches_solana() {
+    assert_matches_solana("withdraw_stake_account");
+}
+
+#[test]
+fn non_delegated_stake_account_lamports() {
+    let meta = marinade_staking_sdk::Meta {
+        rent_exempt_reserve: 2_282_880,
+        ..Default::default()
+    };
+    assert_eq!(
+        StakeAccountLamports::from_stake_account(1_000_000_000, &StakeStateV2::Initialized(meta)),
+        None
+    );
+    let delegated = StakeStateV2::Stake(
+        meta,
+        marinade_staking_sdk::Stake {
+            delegation: marinade_staking_sdk::Delegation::new([1; 32], 1_000_000_000, 0, u64::MAX),
+            credits_observed: 0,
+        },
+        marinade_staking_sdk::StakeFlags::EMPTY,
+    );
+    // lamports must cover rent on top of delegated stake
+    assert_eq!(
+        StakeAccountLamports::from_stake_account(1_000_000_000 + 2_282_879, &delegated),
+        None
+    );
+    assert_eq!(
+        StakeAccountLamports::from_stake_account(1_000_000_000 + 2_282_880, &delegated),
+        Some(StakeAccountLamports {
+            staked: 1_000_000_000,
+            unstaked: 2_282_880,
+        })
+    );
+}
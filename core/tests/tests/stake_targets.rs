@@ -0,0 +1,129 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{self as marinade_staking_sdk, list::ListAccount};
+
+use crate::common::{any_state, any_validators, mainnet_state, KeyedUiAccount};
+
+#[test]
+fn validator_stake_targets_mainnet() {
+    let state = mainnet_state();
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    // reserve lamports as last recorded by the state
+    let reserve_balance = state.available_reserve_balance + state.rent_exempt_for_token_acc;
+    let stake_delta = state.stake_delta(reserve_balance);
+    assert_eq!(
+        stake_delta,
+        i128::from(state.available_reserve_balance)
+            + i128::from(state.stake_system.delayed_unstake_cooling_down)
+            - i128::from(state.circulating_ticket_balance)
+    );
+    let total_stake_target = state.total_stake_target(reserve_balance);
+
+    let targets: Vec<_> = state
+        .validator_stake_targets(&validator_list, reserve_balance)
+        .collect();
+    assert_eq!(targets.len(), validator_list.0.len());
+
+    let total_score: u32 = validator_list.0.iter().map(|v| v.score()).sum();
+    assert_eq!(total_score, state.validator_system.total_validator_score);
+
+    let sum: u64 = targets.iter().map(|t| t.target).sum();
+    assert!(sum <= total_stake_target);
+    assert!(total_stake_target - sum < targets.len() as u64);
+
+    for (t, v) in targets.iter().zip(validator_list.0) {
+        assert_eq!(t.active_balance, v.active_balance());
+        if v.score() == 0 {
+            assert_eq!(t.target, 0);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn validator_stake_targets_split_total(
+        state in any_state(),
+        validators in any_validators(0..=8),
+        reserve_balance: u64,
+    ) {
+        let mut state = state;
+        state.validator_system.total_validator_score = validators.iter().map(|v| v.score()).sum();
+        let validator_list = ListAccount(validators.as_slice());
+        let total_stake_target = state.total_stake_target(reserve_balance);
+
+        let targets: Vec<_> = state
+            .validator_stake_targets(&validator_list, reserve_balance)
+            .collect();
+        let sum: u128 = targets.iter().map(|t| u128::from(t.target)).sum();
+
+        if state.validator_system.total_validator_score == 0 {
+            prop_assert_eq!(sum, 0);
+        } else {
+            prop_assert!(sum <= u128::from(total_stake_target));
+            prop_assert!(u128::from(total_stake_target) - sum < targets.len() as u128);
+        }
+        for (i, (t, v)) in targets.iter().zip(&validators).enumerate() {
+            prop_assert_eq!(t.validator_index, i as u32);
+            prop_assert_eq!(t.active_balance, v.active_balance());
+            prop_assert_eq!(t.delta, i128::from(t.active_balance) - i128::from(t.target));
+        }
+    }
+
+    #[test]
+    fn stake_delta_moves_total_stake_target(
+        state in any_state(),
+        reserve_balance: u64,
+    ) {
+        let stake_delta = state.stake_delta(reserve_balance);
+        let total_active_balance = i128::from(state.validator_system.total_active_balance);
+        let total_stake_target = i128::from(state.total_stake_target(reserve_balance));
+        prop_assert_eq!(
+            total_stake_target,
+            (total_active_balance + stake_delta).clamp(0, i128::from(u64::MAX))
+        );
+
+        // emergency cooling down is never staked
+        let without_emergency = i128::from(
+            reserve_balance.saturating_sub(state.rent_exempt_for_token_acc),
+        ) + i128::from(state.stake_system.delayed_unstake_cooling_down)
+            - i128::from(state.circulating_ticket_balance);
+        if without_emergency >= 0 {
+            prop_assert_eq!(stake_delta, without_emergency);
+        } else {
+            prop_assert!(stake_delta <= 0);
+            prop_assert!(stake_delta >= without_emergency);
+        }
+    }
+
+    #[test]
+    fn validator_overstake_matches_stake_target(
+        state in any_state(),
+        validators in any_validators(0..=8),
+    ) {
+        let mut state = state;
+        state.validator_system.total_validator_score = validators.iter().map(|v| v.score()).sum();
+        let total_active_balance = state.validator_system.total_active_balance;
+        for v in &validators {
+            prop_assert_eq!(
+                state.validator_stake_target(v, total_active_balance),
+                u64::try_from(
+                    (u128::from(total_active_balance) * u128::from(v.score()))
+                        .checked_div(u128::from(state.validator_system.total_validator_score))
+                        .unwrap_or(0)
+                )
+                .unwrap()
+            );
+            prop_assert_eq!(
+                state.validator_overstake(v),
+                i128::from(v.active_balance())
+                    - i128::from(state.validator_stake_target(v, total_active_balance))
+            );
+        }
+    }
+}
@@ -2,16 +2,13 @@ use const_crypto::bs58;
 use sanctum_marinade_liquid_staking_core::{self as marinade_staking_sdk};
 use solana_pubkey::Pubkey;
 
-use crate::common::KeyedUiAccount;
+use crate::common::{mainnet_state, KeyedUiAccount};
 
 const EMPTY_PUBKEY: [u8; 32] = [0; 32];
 
 #[test]
 fn test_state_serde() {
-    let state_account = KeyedUiAccount::from_test_fixtures_file("marinade-state");
-
-    let stake_pool =
-        marinade_staking_sdk::State::borsh_de(state_account.account_data().as_slice()).unwrap();
+    let stake_pool = mainnet_state();
 
     assert_eq!(stake_pool.msol_supply, 3564176058141121);
     assert_eq!(stake_pool.available_reserve_balance, 260659593335447);
@@ -53,10 +50,7 @@ fn test_validator_list_serde() {
     let validator_list_account = KeyedUiAccount::from_test_fixtures_file("marinade-validator_list");
     let validator_list_data = validator_list_account.account_data();
 
-    let state_account = KeyedUiAccount::from_test_fixtures_file("marinade-state");
-
-    let stake_pool =
-        marinade_staking_sdk::State::borsh_de(state_account.account_data().as_slice()).unwrap();
+    let stake_pool = mainnet_state();
 
     let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
         &validator_list_data,
@@ -114,10 +108,7 @@ fn test_validator_list_serde() {
 
 #[test]
 fn test_stake_list_serde() {
-    let state_account = KeyedUiAccount::from_test_fixtures_file("marinade-state");
-
-    let stake_pool =
-        marinade_staking_sdk::State::borsh_de(state_account.account_data().as_slice()).unwrap();
+    let stake_pool = mainnet_state();
 
     let stake_list_account: KeyedUiAccount =
         KeyedUiAccount::from_test_fixtures_file("marinade-stake_list");
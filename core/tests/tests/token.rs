@@ -0,0 +1,185 @@
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    create_ata_address, create_program_address, find_ata, find_lp_ata, find_msol_ata,
+    liq_pool_msol_leg_authority, DepositIxKeysOwned, DepositSolQuoteArgs,
+    DepositStakeAccountIxKeysOwned, DepositStakeQuoteArgs, MarinadeError, Mint, TokenAccount,
+    TokenAccountState, WithdrawStakeAccountIxKeysOwned, ASSOCIATED_TOKEN_PROGRAM,
+    LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY, LP_MINT_ADDR, MSOL_MINT_ADDR, MSOL_MINT_AUTHORITY_PUBKEY,
+    STATE_PUBKEY, TOKEN_PROGRAM,
+};
+use solana_pubkey::Pubkey;
+
+use crate::common::{mainnet_state, msol_token_acc, token_acc_balance, KeyedUiAccount};
+
+#[test]
+fn decode_mainnet_token_accounts() {
+    let state = mainnet_state();
+
+    let msol_leg_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-liq_pool_msol_leg").account_data();
+    let msol_leg = TokenAccount::try_from_acc_data(&msol_leg_data).unwrap();
+    assert_eq!(msol_leg.mint, MSOL_MINT_ADDR);
+    assert_eq!(msol_leg.owner, LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY);
+    assert_eq!(
+        msol_leg.amount,
+        u64::from_le_bytes(msol_leg_data[64..72].try_into().unwrap())
+    );
+    assert_eq!(msol_leg.delegate, None);
+    assert_eq!(msol_leg.state, TokenAccountState::Initialized);
+
+    let treasury_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-treasury_msol_account").account_data();
+    let treasury = TokenAccount::try_from_acc_data(&treasury_data).unwrap();
+    assert_eq!(treasury.mint, state.msol_mint);
+
+    let mint_data = KeyedUiAccount::from_test_fixtures_file("marinade-msol_mint").account_data();
+    let mint = Mint::try_from_acc_data(&mint_data).unwrap();
+    assert_eq!(mint.mint_authority, Some(MSOL_MINT_AUTHORITY_PUBKEY));
+    assert_eq!(mint.decimals, 9);
+    assert_eq!(
+        mint.supply,
+        u64::from_le_bytes(mint_data[36..44].try_into().unwrap())
+    );
+
+    // mints are not token accounts and vice versa
+    assert_eq!(TokenAccount::try_from_acc_data(&mint_data), None);
+    assert_eq!(Mint::try_from_acc_data(&[0; 82]), None);
+}
+
+#[test]
+fn quote_args_from_msol_leg() {
+    let state = mainnet_state();
+    let msol_leg_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-liq_pool_msol_leg").account_data();
+
+    assert_eq!(
+        liq_pool_msol_leg_authority(&STATE_PUBKEY, state.liq_pool.msol_leg_authority_bump_seed),
+        Some(LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY)
+    );
+
+    let expected = u64::from_le_bytes(msol_leg_data[64..72].try_into().unwrap());
+    assert_eq!(
+        DepositSolQuoteArgs::try_from_msol_leg(&state, &STATE_PUBKEY, &msol_leg_data)
+            .unwrap()
+            .msol_leg_balance,
+        expected
+    );
+    assert_eq!(
+        DepositStakeQuoteArgs::try_from_msol_leg(&state, &STATE_PUBKEY, &msol_leg_data)
+            .unwrap()
+            .msol_leg_balance,
+        expected
+    );
+
+    let msol_leg_authority = Pubkey::new_from_array(LIQ_POOL_MSOL_LEG_AUTHORITY_PUBKEY);
+    let other = msol_token_acc(1_000, msol_leg_authority);
+    assert_eq!(token_acc_balance(&other), 1_000);
+    assert_eq!(
+        state.msol_leg_balance(&STATE_PUBKEY, &other.data),
+        Ok(1_000)
+    );
+
+    let wrong_owner = msol_token_acc(1_000, Pubkey::new_unique());
+    assert_eq!(
+        state.msol_leg_balance(&STATE_PUBKEY, &wrong_owner.data),
+        Err(MarinadeError::WrongTokenOwner)
+    );
+    // owner is a PDA of the state account
+    assert_eq!(
+        state.msol_leg_balance(&[1; 32], &other.data),
+        Err(MarinadeError::WrongTokenOwner)
+    );
+
+    let mut wrong_mint = other.clone();
+    wrong_mint.data[0..32].copy_from_slice(&[1; 32]);
+    assert_eq!(
+        state.msol_leg_balance(&STATE_PUBKEY, &wrong_mint.data),
+        Err(MarinadeError::WrongTokenMint)
+    );
+
+    let mut uninitialized = other.clone();
+    uninitialized.data[108] = 0;
+    assert_eq!(
+        state.msol_leg_balance(&STATE_PUBKEY, &uninitialized.data),
+        Err(MarinadeError::InvalidTokenAccount)
+    );
+    assert_eq!(
+        state.msol_leg_balance(&STATE_PUBKEY, &other.data[..164]),
+        Err(MarinadeError::InvalidTokenAccount)
+    );
+}
+
+proptest! {
+    #[test]
+    fn create_program_address_matches_solana(
+        seed_a in prop::collection::vec(any::<u8>(), 0..=32),
+        seed_b: [u8; 32],
+        bump: u8,
+        program: [u8; 32],
+    ) {
+        let expected = Pubkey::create_program_address(
+            &[&seed_a, &seed_b, &[bump]],
+            &Pubkey::new_from_array(program),
+        )
+        .ok()
+        .map(|pk| pk.to_bytes());
+        prop_assert_eq!(create_program_address(&[&seed_a, &seed_b], bump, &program), expected);
+    }
+
+    #[test]
+    fn token_account_decode_never_panics(data in prop::collection::vec(any::<u8>(), 0..=200)) {
+        if let Some(account) = TokenAccount::try_from_acc_data(&data) {
+            prop_assert_eq!(account.amount, u64::from_le_bytes(data[64..72].try_into().unwrap()));
+        }
+        if let Some(mint) = Mint::try_from_acc_data(&data) {
+            prop_assert_eq!(mint.decimals, data[44]);
+        }
+    }
+}
+
+#[test]
+fn mainnet_mints() {
+    let state = mainnet_state();
+    assert_eq!(state.msol_mint, MSOL_MINT_ADDR);
+    assert_eq!(state.liq_pool.lp_mint, LP_MINT_ADDR);
+}
+
+#[test]
+fn wallet_ata_key_builders() {
+    let state = mainnet_state();
+    let wallet = Pubkey::new_unique().to_bytes();
+    let (msol_ata, _) = find_msol_ata(&wallet);
+
+    let deposit = DepositIxKeysOwned::default()
+        .with_keys_from_stake_pool(&state)
+        .with_mint_to_wallet(wallet);
+    assert_eq!(*deposit.mint_to(), msol_ata);
+
+    let deposit_stake = DepositStakeAccountIxKeysOwned::default()
+        .with_keys_from_stake_pool(&state)
+        .with_mint_to_wallet(wallet);
+    assert_eq!(*deposit_stake.mint_to(), msol_ata);
+
+    let withdraw = WithdrawStakeAccountIxKeysOwned::default()
+        .with_keys_from_stake_pool(&state)
+        .with_burn_msol_from_wallet(wallet);
+    assert_eq!(*withdraw.burn_msol_from(), msol_ata);
+    assert_eq!(*withdraw.burn_msol_authority(), wallet);
+}
+
+proptest! {
+    #[test]
+    fn find_ata_matches_solana(wallet: [u8; 32], mint: [u8; 32]) {
+        let (expected, expected_bump) = Pubkey::find_program_address(
+            &[&wallet, &TOKEN_PROGRAM, &mint],
+            &Pubkey::new_from_array(ASSOCIATED_TOKEN_PROGRAM),
+        );
+        let (ata, bump) = find_ata(&wallet, &mint);
+        prop_assert_eq!(ata, expected.to_bytes());
+        prop_assert_eq!(bump, expected_bump);
+        prop_assert_eq!(create_ata_address(&wallet, &mint, bump), Some(ata));
+
+        prop_assert_eq!(find_msol_ata(&wallet), find_ata(&wallet, &MSOL_MINT_ADDR));
+        prop_assert_eq!(find_lp_ata(&wallet), find_ata(&wallet, &LP_MINT_ADDR));
+    }
+}
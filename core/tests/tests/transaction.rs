@@ -0,0 +1,335 @@
+use const_crypto::bs58;
+use mollusk_svm::{
+    program::{create_keyed_account_for_builtin_program, keyed_account_for_system_program},
+    result::InstructionResult,
+    Mollusk,
+};
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, find_msol_ata, Clock, DepositSolQuoteArgs, DepositStakeIxArgs,
+    MarinadeIntent, MarinadeIntentQuote, MarinadeTransaction, MarinadeTransactionBuilder,
+    StakeHistory, StakeStateV2, ASSOCIATED_TOKEN_PROGRAM, COMPUTE_BUDGET_PROGRAM,
+    MARINADE_STAKING_PROGRAM,
+};
+use solana_account::Account;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    ix_from_dyn, mainnet_msol_leg_balance, mainnet_state, marinade_mainnet_accounts,
+    mollusk_marinade_prog, msol_token_acc, payer_account, token_acc_balance, KeyedUiAccount,
+};
+
+fn program_ids(tx: &MarinadeTransaction) -> Vec<[u8; 32]> {
+    tx.instructions.iter().map(|ix| ix.program_id).collect()
+}
+
+/// Runs the non compute budget instructions of `tx` within its compute unit limit,
+/// since compute budget instructions are processed by the runtime before execution
+fn process_transaction(
+    mut mollusk: Mollusk,
+    tx: &MarinadeTransaction,
+    accounts: &[(Pubkey, Account)],
+) -> Vec<(Pubkey, Account)> {
+    let (compute_budget, ixs): (Vec<_>, Vec<_>) = tx
+        .instructions
+        .iter()
+        .partition(|ix| ix.program_id == COMPUTE_BUDGET_PROGRAM);
+    let [limit, _price] = compute_budget.as_slice() else {
+        panic!("expected compute unit limit and price instructions");
+    };
+    let limit = u32::from_le_bytes(limit.data[1..5].try_into().unwrap());
+    mollusk.compute_budget.compute_unit_limit = limit.into();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        compute_units_consumed,
+        ..
+    } = mollusk.process_instruction_chain(
+        &ixs.into_iter().map(ix_from_dyn).collect::<Vec<_>>(),
+        accounts,
+    );
+    raw_result.unwrap();
+    assert!(compute_units_consumed <= limit.into());
+    resulting_accounts
+}
+
+fn find(accounts: &[(Pubkey, Account)], pubkey: &[u8; 32]) -> Account {
+    accounts
+        .iter()
+        .find(|(pk, _)| pk.as_array() == pubkey)
+        .unwrap()
+        .1
+        .clone()
+}
+
+#[test]
+fn compute_budget_ixs_match_solana() {
+    let state = mainnet_state();
+    let tx = MarinadeTransactionBuilder::new(Pubkey::new_unique().to_bytes())
+        .with_compute_unit_limit(123_456)
+        .with_compute_unit_price(7_890)
+        .build(
+            &state,
+            MarinadeIntent::DepositSol {
+                lamports: 1_000_000_000,
+                args: DepositSolQuoteArgs {
+                    msol_leg_balance: mainnet_msol_leg_balance(&state),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        ix_from_dyn(&tx.instructions[0]),
+        ComputeBudgetInstruction::set_compute_unit_limit(123_456)
+    );
+    assert_eq!(
+        ix_from_dyn(&tx.instructions[1]),
+        ComputeBudgetInstruction::set_compute_unit_price(7_890)
+    );
+}
+
+#[test]
+fn deposit_sol_transaction_with_fee_payer() {
+    let state = mainnet_state();
+    let user = Pubkey::new_unique();
+    let fee_payer = Pubkey::new_unique();
+    let tx = MarinadeTransactionBuilder::new(user.to_bytes())
+        .with_fee_payer(fee_payer.to_bytes())
+        .build(
+            &state,
+            MarinadeIntent::DepositSol {
+                lamports: 1_000_000_000,
+                args: DepositSolQuoteArgs {
+                    msol_leg_balance: mainnet_msol_leg_balance(&state),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        program_ids(&tx),
+        [
+            COMPUTE_BUDGET_PROGRAM,
+            COMPUTE_BUDGET_PROGRAM,
+            ASSOCIATED_TOKEN_PROGRAM,
+            MARINADE_STAKING_PROGRAM
+        ]
+    );
+    assert_eq!(tx.signers, [fee_payer.to_bytes(), user.to_bytes()]);
+    assert_eq!(tx.fee_payer(), Some(fee_payer.as_array()));
+    let MarinadeIntentQuote::DepositSol(quote) = tx.quote else {
+        panic!("expected deposit SOL quote");
+    };
+
+    let mut mollusk = mollusk_marinade_prog();
+    mollusk_svm_programs_token::associated_token::add_program(&mut mollusk);
+    let msol_ata = find_msol_ata(user.as_array()).0;
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .chain([
+            keyed_account_for_system_program(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            mollusk_svm_programs_token::associated_token::keyed_account(),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            (user, payer_account(2_000_000_000)),
+            (fee_payer, payer_account(1_000_000_000)),
+            (Pubkey::new_from_array(msol_ata), Account::default()),
+        ])
+        .collect();
+
+    let resulting_accounts = process_transaction(mollusk, &tx, &accounts);
+
+    let ata = find(&resulting_accounts, &msol_ata);
+    assert_eq!(token_acc_balance(&ata), quote.out_amount);
+    assert_eq!(
+        find(&resulting_accounts, fee_payer.as_array()).lamports,
+        1_000_000_000 - ata.lamports
+    );
+    assert_eq!(
+        find(&resulting_accounts, user.as_array()).lamports,
+        1_000_000_000
+    );
+}
+
+#[test]
+fn deposit_stake_transaction() {
+    let state = mainnet_state();
+    let user = Pubkey::new_from_array(bs58::decode_pubkey(
+        "73mx3pb9AccyrfoY6Agx3baocyqPUNmLFuTM2nbfv1T8",
+    ));
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("stake_account");
+    let stake_account_pubkey = bs58::decode_pubkey(&stake_account.pubkey);
+    let stake_state = StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap();
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    let mut mollusk = mollusk_marinade_prog();
+    mollusk_svm_programs_token::associated_token::add_program(&mut mollusk);
+    let clock = &mollusk.sysvars.clock;
+    let clock = Clock {
+        slot: clock.slot,
+        epoch_start_timestamp: clock.epoch_start_timestamp,
+        epoch: clock.epoch,
+        leader_schedule_epoch: clock.leader_schedule_epoch,
+        unix_timestamp: clock.unix_timestamp,
+    };
+
+    let tx = MarinadeTransactionBuilder::new(user.to_bytes())
+        .build(
+            &state,
+            MarinadeIntent::DepositStake {
+                validator_list: &validator_list,
+                stake_account: stake_account_pubkey,
+                args: DepositStakeIxArgs {
+                    stake_account_lamports: stake_account.account.lamports,
+                    stake_state: &stake_state,
+                    clock: &clock,
+                    stake_history: &StakeHistory(&[]),
+                    new_rate_activation_epoch: Some(0),
+                    msol_leg_balance: mainnet_msol_leg_balance(&state),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(tx.signers, [user.to_bytes()]);
+    let MarinadeIntentQuote::DepositStake(quote) = tx.quote else {
+        panic!("expected deposit stake quote");
+    };
+
+    let msol_ata = find_msol_ata(user.as_array()).0;
+    let duplication_flag = tx.instructions[3].accounts[5].pubkey;
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .chain([
+            keyed_account_for_system_program(),
+            create_keyed_account_for_builtin_program(
+                &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+                "solana_stake_program",
+            ),
+            mollusk_svm_programs_token::token::keyed_account(),
+            mollusk_svm_programs_token::associated_token::keyed_account(),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            mollusk.sysvars.keyed_account_for_rent_sysvar(),
+            (user, payer_account(1_000_000_000)),
+            (Pubkey::new_from_array(msol_ata), Account::default()),
+            (
+                Pubkey::new_from_array(duplication_flag),
+                Account::new(
+                    1_000_000_000,
+                    0,
+                    &Pubkey::new_from_array(MARINADE_STAKING_PROGRAM),
+                ),
+            ),
+        ])
+        .collect();
+
+    let resulting_accounts = process_transaction(mollusk, &tx, &accounts);
+    assert_eq!(
+        token_acc_balance(&find(&resulting_accounts, &msol_ata)),
+        quote.tokens_out
+    );
+}
+
+#[test]
+fn withdraw_stake_transaction() {
+    let state = mainnet_state();
+    let pool_tokens = 2_000_000_000;
+
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("withdraw_stake_account");
+    let stake_account_pubkey = bs58::decode_pubkey(&stake_account.pubkey);
+    let StakeStateV2::Stake(_, stake, _) =
+        StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap()
+    else {
+        panic!("fixture should be delegated");
+    };
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    let stake_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-stake_list").account_data();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let candidate = state
+        .withdraw_stake_candidates(
+            &validator_list,
+            &stake_list,
+            |pk| (*pk == stake_account_pubkey).then_some(stake.delegation.voter_pubkey),
+            pool_tokens,
+        )
+        .next()
+        .unwrap();
+
+    let user = Pubkey::new_unique();
+    let split_stake_account = Pubkey::new_unique();
+    let tx = MarinadeTransactionBuilder::new(user.to_bytes())
+        .with_compute_unit_price(1_000)
+        .build(
+            &state,
+            MarinadeIntent::WithdrawStake {
+                candidate,
+                pool_tokens,
+                split_stake_account: split_stake_account.to_bytes(),
+            },
+        )
+        .unwrap();
+    // no ATA creation for withdrawals
+    assert_eq!(
+        program_ids(&tx),
+        [
+            COMPUTE_BUDGET_PROGRAM,
+            COMPUTE_BUDGET_PROGRAM,
+            MARINADE_STAKING_PROGRAM
+        ]
+    );
+    assert_eq!(
+        tx.signers,
+        [user.to_bytes(), split_stake_account.to_bytes()]
+    );
+    assert_eq!(
+        tx.quote,
+        MarinadeIntentQuote::WithdrawStake(candidate.quote)
+    );
+
+    let mollusk = mollusk_marinade_prog();
+    let msol_ata = find_msol_ata(user.as_array()).0;
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .chain([
+            keyed_account_for_system_program(),
+            create_keyed_account_for_builtin_program(
+                &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+                "solana_stake_program",
+            ),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            (user, payer_account(5_000_000_000)),
+            (
+                Pubkey::new_from_array(msol_ata),
+                msol_token_acc(pool_tokens, user),
+            ),
+            (split_stake_account, Account::default()),
+        ])
+        .collect();
+
+    let resulting_accounts = process_transaction(mollusk, &tx, &accounts);
+    assert_eq!(token_acc_balance(&find(&resulting_accounts, &msol_ata)), 0);
+    let split_stake = StakeStateV2::borsh_de(
+        find(&resulting_accounts, split_stake_account.as_array())
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(
+        split_stake.delegation().unwrap().stake,
+        candidate.quote.lamports_staked
+    );
+}
@@ -0,0 +1,305 @@
+use const_crypto::bs58;
+use mollusk_svm::{
+    program::{create_keyed_account_for_builtin_program, keyed_account_for_system_program},
+    result::InstructionResult,
+    Mollusk,
+};
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, deposit_sol_ix, deposit_stake_ix, duplication_flag,
+    find_msol_ata, withdraw_stake_ix, Clock, DepositSolQuoteArgs, DepositStakeIxArgs,
+    DepositStakeQuoteArgs, MarinadeError, StakeAccountLamports, StakeHistory, StakeStateV2,
+    MARINADE_STAKING_PROGRAM, STATE_PUBKEY,
+};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    ix_from_sdk, mainnet_msol_leg_balance, mainnet_state, marinade_mainnet_accounts,
+    mollusk_marinade_prog, msol_token_acc, payer_account, token_acc_balance, KeyedUiAccount,
+};
+
+fn mollusk_clock(mollusk: &Mollusk) -> Clock {
+    let clock = &mollusk.sysvars.clock;
+    Clock {
+        slot: clock.slot,
+        epoch_start_timestamp: clock.epoch_start_timestamp,
+        epoch: clock.epoch,
+        leader_schedule_epoch: clock.leader_schedule_epoch,
+        unix_timestamp: clock.unix_timestamp,
+    }
+}
+
+fn user_msol_ata(user: Pubkey, balance: u64) -> (Pubkey, Account) {
+    (
+        Pubkey::new_from_array(find_msol_ata(user.as_array()).0),
+        msol_token_acc(balance, user),
+    )
+}
+
+fn resulting_msol_balance(resulting_accounts: &[(Pubkey, Account)], user: Pubkey) -> u64 {
+    let ata = find_msol_ata(user.as_array()).0;
+    token_acc_balance(
+        &resulting_accounts
+            .iter()
+            .find(|(pk, _)| pk.as_array() == &ata)
+            .unwrap()
+            .1,
+    )
+}
+
+#[test]
+fn deposit_sol_ix_mollusk() {
+    let state = mainnet_state();
+    let args = DepositSolQuoteArgs {
+        msol_leg_balance: mainnet_msol_leg_balance(&state),
+    };
+    let user = Pubkey::new_unique();
+    let lamports = 2_000_000_000;
+
+    let (ix, quote) = deposit_sol_ix(&state, user.to_bytes(), lamports, args).unwrap();
+    assert_eq!(quote, state.quote_deposit_sol(lamports, args).unwrap());
+
+    let mollusk = mollusk_marinade_prog();
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .chain([
+            keyed_account_for_system_program(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            (user, payer_account(10_000_000_000)),
+            user_msol_ata(user, 0),
+        ])
+        .collect();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&[ix_from_sdk(&ix)], &accounts);
+    raw_result.unwrap();
+
+    assert_eq!(
+        resulting_msol_balance(&resulting_accounts, user),
+        quote.out_amount
+    );
+
+    let mut paused = state.clone();
+    paused.paused = true;
+    assert_eq!(
+        deposit_sol_ix(&paused, user.to_bytes(), lamports, args).unwrap_err(),
+        MarinadeError::ProgramIsPaused
+    );
+}
+
+#[test]
+fn deposit_stake_ix_mollusk() {
+    let state = mainnet_state();
+    let msol_leg_balance = mainnet_msol_leg_balance(&state);
+
+    // withdraw authority of the fixture stake account
+    let user = Pubkey::new_from_array(bs58::decode_pubkey(
+        "73mx3pb9AccyrfoY6Agx3baocyqPUNmLFuTM2nbfv1T8",
+    ));
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("stake_account");
+    let stake_account_pubkey = bs58::decode_pubkey(&stake_account.pubkey);
+    let stake_state = StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap();
+
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    let mollusk = mollusk_marinade_prog();
+    let clock = mollusk_clock(&mollusk);
+    let args = DepositStakeIxArgs {
+        stake_account_lamports: stake_account.account.lamports,
+        stake_state: &stake_state,
+        clock: &clock,
+        stake_history: &StakeHistory(&[]),
+        new_rate_activation_epoch: Some(0),
+        msol_leg_balance,
+    };
+
+    let (ix, quote) = deposit_stake_ix(
+        &state,
+        &validator_list,
+        user.to_bytes(),
+        stake_account_pubkey,
+        args,
+    )
+    .unwrap();
+    assert_eq!(
+        quote,
+        state
+            .quote_deposit_stake(
+                StakeAccountLamports::from_stake_account(
+                    stake_account.account.lamports,
+                    &stake_state
+                )
+                .unwrap(),
+                DepositStakeQuoteArgs { msol_leg_balance }
+            )
+            .unwrap()
+    );
+
+    // stored bump derives the same duplication flag as the bump search
+    let validator_record = &validator_list.0[0];
+    let (expected_duplication_flag, _) = Pubkey::find_program_address(
+        &[
+            &STATE_PUBKEY,
+            b"unique_validator",
+            validator_record.validator_account(),
+        ],
+        &Pubkey::new_from_array(MARINADE_STAKING_PROGRAM),
+    );
+    assert_eq!(
+        duplication_flag(
+            &STATE_PUBKEY,
+            validator_record.validator_account(),
+            validator_record.duplication_flag_bump_seed()
+        ),
+        Some(expected_duplication_flag.to_bytes())
+    );
+
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .chain([
+            keyed_account_for_system_program(),
+            create_keyed_account_for_builtin_program(
+                &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+                "solana_stake_program",
+            ),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            mollusk.sysvars.keyed_account_for_rent_sysvar(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            (user, payer_account(1_000_000_000)),
+            user_msol_ata(user, 0),
+            (
+                expected_duplication_flag,
+                Account::new(
+                    1_000_000_000,
+                    0,
+                    &Pubkey::new_from_array(MARINADE_STAKING_PROGRAM),
+                ),
+            ),
+        ])
+        .collect();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&[ix_from_sdk(&ix)], &accounts);
+    raw_result.unwrap();
+
+    assert_eq!(
+        resulting_msol_balance(&resulting_accounts, user),
+        quote.tokens_out
+    );
+
+    assert_eq!(
+        deposit_stake_ix(
+            &state,
+            &validator_list,
+            Pubkey::new_unique().to_bytes(),
+            stake_account_pubkey,
+            args,
+        )
+        .unwrap_err(),
+        MarinadeError::WrongStakeAuthority
+    );
+}
+
+#[test]
+fn withdraw_stake_ix_mollusk() {
+    let state = mainnet_state();
+    let pool_tokens = 2_000_000_000;
+
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("withdraw_stake_account");
+    let stake_account_pubkey = bs58::decode_pubkey(&stake_account.pubkey);
+    let StakeStateV2::Stake(_, stake, _) =
+        StakeStateV2::borsh_de(stake_account.account_data().as_slice()).unwrap()
+    else {
+        panic!("fixture should be delegated");
+    };
+
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+    let stake_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-stake_list").account_data();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let candidate = state
+        .withdraw_stake_candidates(
+            &validator_list,
+            &stake_list,
+            |pk| (*pk == stake_account_pubkey).then_some(stake.delegation.voter_pubkey),
+            pool_tokens,
+        )
+        .next()
+        .unwrap();
+
+    let user = Pubkey::new_unique();
+    let split_stake_account = Pubkey::new_unique();
+    let (ix, quote) = withdraw_stake_ix(
+        &state,
+        &candidate,
+        user.to_bytes(),
+        split_stake_account.to_bytes(),
+        pool_tokens,
+    )
+    .unwrap();
+    assert_eq!(quote, candidate.quote);
+
+    let mollusk = mollusk_marinade_prog();
+    let accounts: Vec<_> = marinade_mainnet_accounts()
+        .chain([
+            keyed_account_for_system_program(),
+            create_keyed_account_for_builtin_program(
+                &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+                "solana_stake_program",
+            ),
+            mollusk.sysvars.keyed_account_for_clock_sysvar(),
+            mollusk_svm_programs_token::token::keyed_account(),
+            (user, payer_account(5_000_000_000)),
+            user_msol_ata(user, 5_000_000_000),
+            (split_stake_account, Account::default()),
+        ])
+        .collect();
+
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&[ix_from_sdk(&ix)], &accounts);
+    raw_result.unwrap();
+
+    assert_eq!(
+        resulting_msol_balance(&resulting_accounts, user),
+        5_000_000_000 - pool_tokens
+    );
+    let split_stake = StakeStateV2::borsh_de(
+        resulting_accounts
+            .iter()
+            .find(|(pk, _)| pk == &split_stake_account)
+            .unwrap()
+            .1
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    let StakeStateV2::Stake(meta, split_stake, _) = split_stake else {
+        panic!("split stake account should be delegated");
+    };
+    assert_eq!(split_stake.delegation.stake, quote.lamports_staked);
+    assert_eq!(meta.authorized.withdrawer, user.to_bytes());
+}
@@ -0,0 +1,187 @@
+use sanctum_marinade_liquid_staking_core::{
+    find_ata, msol_mint_authority, reserve_pda, stake_withdraw_authority,
+    CreateAtaIdempotentIxKeysOwned, DepositIxKeysOwned, DepositStakeAccountIxKeysOwned,
+    KeyMismatch, UpdateActiveIxKeysOwned, UpdateDeactivatedIxKeysOwned,
+    WithdrawStakeAccountIxKeysOwned, MSOL_MINT_ADDR, RESERVE_PUBKEY,
+    STAKE_WITHDRAW_AUTHORITY_PUBKEY, STATE_PUBKEY, SYSTEM_PROGRAM,
+};
+
+use crate::common::mainnet_state;
+
+fn assert_no_mismatches<const N: usize>(mismatches: [Option<KeyMismatch>; N]) {
+    assert_eq!(mismatches, [None; N]);
+}
+
+#[test]
+fn mainnet_keys_are_valid() {
+    let state = mainnet_state();
+    let user = [1; 32];
+
+    assert_no_mismatches(
+        DepositIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state)
+            .with_transfer_from(user)
+            .with_mint_to_wallet(user)
+            .validate(&state, &STATE_PUBKEY)
+            .0,
+    );
+    assert_no_mismatches(
+        DepositStakeAccountIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state)
+            .with_stake_account([2; 32])
+            .with_stake_authority(user)
+            .with_rent_payer(user)
+            .with_mint_to_wallet(user)
+            .validate(&state, &STATE_PUBKEY)
+            .0,
+    );
+    assert_no_mismatches(
+        WithdrawStakeAccountIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state)
+            .with_burn_msol_from_wallet(user)
+            .with_stake_account([2; 32])
+            .with_split_stake_account([3; 32])
+            .with_split_stake_rent_payer(user)
+            .validate(&state, &STATE_PUBKEY)
+            .0,
+    );
+    assert_no_mismatches(
+        UpdateActiveIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state)
+            .with_stake_account([2; 32])
+            .validate(&state, &STATE_PUBKEY)
+            .0,
+    );
+    assert_no_mismatches(
+        UpdateDeactivatedIxKeysOwned::default()
+            .with_consts()
+            .with_mainnet_consts()
+            .with_keys_from_stake_pool(&state)
+            .with_stake_account([2; 32])
+            .validate(&state, &STATE_PUBKEY)
+            .0,
+    );
+}
+
+#[test]
+fn hand_edited_keys_are_reported() {
+    let state = mainnet_state();
+    let keys = WithdrawStakeAccountIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(&state)
+        .with_treasury_msol_account([4; 32])
+        .with_stake_list(state.validator_system.validator_list.account);
+
+    let mismatches = keys.validate(&state, &STATE_PUBKEY);
+    assert_eq!(
+        *mismatches.treasury_msol_account(),
+        Some(KeyMismatch {
+            expected: Some(state.treasury_msol_account),
+            actual: [4; 32],
+        })
+    );
+    assert_eq!(
+        *mismatches.stake_list(),
+        Some(KeyMismatch {
+            expected: Some(state.stake_system.stake_list.account),
+            actual: state.validator_system.validator_list.account,
+        })
+    );
+    assert_eq!(mismatches.0.iter().flatten().count(), 2);
+}
+
+#[test]
+fn wrong_cluster_reports_state_and_pdas() {
+    let state = mainnet_state();
+    let keys = UpdateActiveIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(&state)
+        .with_stake_account([2; 32]);
+
+    // some state account whose PDAs are valid with the mainnet bumps
+    let other_state_pubkey = (1..=u8::MAX)
+        .map(|i| [i; 32])
+        .find(|pk| {
+            *pk != STATE_PUBKEY
+                && stake_withdraw_authority(pk, state.stake_system.stake_withdraw_bump_seed)
+                    .is_some()
+                && reserve_pda(pk, state.reserve_bump_seed).is_some()
+                && msol_mint_authority(pk, state.msol_mint_authority_bump_seed).is_some()
+        })
+        .unwrap();
+    let mismatches = keys.validate(&state, &other_state_pubkey);
+    assert_eq!(
+        *mismatches.state(),
+        Some(KeyMismatch {
+            expected: Some(other_state_pubkey),
+            actual: STATE_PUBKEY,
+        })
+    );
+    assert_eq!(
+        mismatches.stake_withdraw_authority().unwrap().actual,
+        STAKE_WITHDRAW_AUTHORITY_PUBKEY
+    );
+    assert_eq!(mismatches.reserve_pda().unwrap().actual, RESERVE_PUBKEY);
+    assert!(mismatches.msol_mint_authority().is_some());
+    assert!(mismatches.stake_list().is_none());
+    assert!(mismatches.msol_mint().is_none());
+}
+
+#[test]
+fn invalid_bump_reports_pda_without_expected_key() {
+    let mut state = mainnet_state();
+    state.reserve_bump_seed = (0..=u8::MAX)
+        .find(|bump| reserve_pda(&STATE_PUBKEY, *bump).is_none())
+        .unwrap();
+    let keys = UpdateDeactivatedIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(&state)
+        .with_stake_account([2; 32]);
+
+    let mismatches = keys.validate(&state, &STATE_PUBKEY);
+    assert_eq!(
+        *mismatches.reserve_pda(),
+        Some(KeyMismatch {
+            expected: None,
+            actual: RESERVE_PUBKEY,
+        })
+    );
+    assert_eq!(mismatches.0.iter().flatten().count(), 1);
+}
+
+#[test]
+fn create_ata_idempotent_keys_are_validated() {
+    let user = [1; 32];
+    let keys = CreateAtaIdempotentIxKeysOwned::default()
+        .with_consts()
+        .with_funding(user)
+        .with_ata_of(user, MSOL_MINT_ADDR);
+    assert_no_mismatches(keys.validate().0);
+
+    let other_ata = find_ata(&[2; 32], &MSOL_MINT_ADDR).0;
+    let mismatches = keys
+        .clone()
+        .with_ata(other_ata)
+        .with_token_program(SYSTEM_PROGRAM)
+        .validate();
+    assert_eq!(
+        *mismatches.ata(),
+        Some(KeyMismatch {
+            expected: Some(*keys.ata()),
+            actual: other_ata,
+        })
+    );
+    assert!(mismatches.token_program().is_some());
+    assert_eq!(mismatches.0.iter().flatten().count(), 2);
+}
@@ -0,0 +1,164 @@
+use const_crypto::bs58;
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, list::ListAccount, StakeStateV2, WithdrawStakeQuoteArgs,
+};
+
+use crate::common::{
+    any_quotable_state, any_stake_records, any_validators, mainnet_state, KeyedUiAccount,
+};
+
+#[test]
+fn withdraw_stake_candidates_mainnet() {
+    let state = mainnet_state();
+
+    let stake_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-stake_list").account_data();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let validator_list_data =
+        KeyedUiAccount::from_test_fixtures_file("marinade-validator_list").account_data();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    // only the voter of the fixture stake account is known
+    let stake_account = KeyedUiAccount::from_test_fixtures_file("withdraw_stake_account");
+    let stake_account_pubkey = bs58::decode_pubkey(&stake_account.pubkey);
+    let voter = StakeStateV2::borsh_de(stake_account.account_data().as_slice())
+        .unwrap()
+        .delegation()
+        .unwrap()
+        .voter_pubkey;
+    let voter_of = |pk: &[u8; 32]| (*pk == stake_account_pubkey).then_some(voter);
+
+    let candidates: Vec<_> = state
+        .withdraw_stake_candidates(&validator_list, &stake_list, voter_of, 2_000_000_000)
+        .collect();
+
+    assert_eq!(candidates.len(), 1);
+    let [c] = candidates.as_slice() else {
+        unreachable!()
+    };
+    assert_eq!(c.stake_index, 10);
+    assert_eq!(c.validator_index, 589);
+    assert_eq!(
+        c.quote,
+        state.quote_withdraw_stake_unchecked(2_000_000_000).unwrap()
+    );
+
+    // not enough delegated lamports in any record
+    assert_eq!(
+        state
+            .withdraw_stake_candidates(&validator_list, &stake_list, voter_of, u64::MAX / 4)
+            .count(),
+        0
+    );
+}
+
+const N_VALIDATORS: usize = 4;
+
+/// stake account `100 + i` is delegated to validator `i % (N_VALIDATORS + 1)`,
+/// which is not on the list for `i % (N_VALIDATORS + 1) == N_VALIDATORS`
+fn voter_of(stake_account: &[u8; 32]) -> Option<[u8; 32]> {
+    let i = usize::from(stake_account[0] - 100) % (N_VALIDATORS + 1);
+    Some([i as u8 + 1; 32])
+}
+
+proptest! {
+    #[test]
+    fn withdraw_stake_candidates_pass_quote(
+        state in any_quotable_state(),
+        validators in any_validators(N_VALIDATORS),
+        stakes in any_stake_records(0..=100_000_000_000_000u64, 0..=16),
+        pool_tokens in 0..=100_000_000_000_000u64,
+    ) {
+        let validator_list = ListAccount(validators.as_slice());
+        let stake_list = ListAccount(stakes.as_slice());
+
+        let candidates: Vec<_> = state
+            .withdraw_stake_candidates(&validator_list, &stake_list, voter_of, pool_tokens)
+            .collect();
+
+        let expected: Vec<_> = stakes
+            .iter()
+            .enumerate()
+            .filter(|(i, stake_record)| {
+                i % (N_VALIDATORS + 1) != N_VALIDATORS
+                    && state
+                        .quote_withdraw_stake(
+                            pool_tokens,
+                            WithdrawStakeQuoteArgs { stake_record: **stake_record },
+                        )
+                        .is_ok()
+            })
+            .map(|(i, _)| i as u32)
+            .collect();
+        prop_assert_eq!(
+            candidates.iter().map(|c| c.stake_index).collect::<Vec<_>>(),
+            expected
+        );
+
+        for c in candidates {
+            prop_assert_eq!(c.stake_record, stakes[c.stake_index as usize]);
+            prop_assert_eq!(c.validator_record, validators[c.validator_index as usize]);
+            prop_assert_eq!(
+                Some(*c.validator_record.validator_account()),
+                voter_of(c.stake_record.stake_account())
+            );
+        }
+    }
+}
+
+proptest! {
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn ranked_withdraw_stake_candidates_sorted(
+        state in any_quotable_state(),
+        validators in any_validators(N_VALIDATORS),
+        stakes in any_stake_records(0..=100_000_000_000_000u64, 0..=16),
+        pool_tokens in 0..=100_000_000_000_000u64,
+        preferred in 0..N_VALIDATORS,
+    ) {
+        let mut state = state;
+        state.validator_system.total_validator_score = validators.iter().map(|v| v.score()).sum();
+        let validator_list = ListAccount(validators.as_slice());
+        let stake_list = ListAccount(stakes.as_slice());
+        let preferred = *validators[preferred].validator_account();
+
+        let ranked = state.ranked_withdraw_stake_candidates(
+            &validator_list,
+            &stake_list,
+            voter_of,
+            pool_tokens,
+            |a, b| {
+                marinade_staking_sdk::WithdrawStakeCandidate::prefer_vote_account(preferred)(a, b)
+                    .then_with(|| {
+                        marinade_staking_sdk::WithdrawStakeCandidate::prefer_overstaked(&state)(a, b)
+                    })
+            },
+        );
+        prop_assert_eq!(
+            ranked.len(),
+            state
+                .withdraw_stake_candidates(&validator_list, &stake_list, voter_of, pool_tokens)
+                .count()
+        );
+
+        let key = |c: &marinade_staking_sdk::WithdrawStakeCandidate| {
+            (
+                *c.validator_record.validator_account() != preferred,
+                -state.validator_overstake(&c.validator_record),
+                c.stake_index,
+            )
+        };
+        for w in ranked.windows(2) {
+            prop_assert!(key(&w[0]) < key(&w[1]));
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use const_crypto::bs58;
+use mollusk_svm::{
+    program::{create_keyed_account_for_builtin_program, keyed_account_for_system_program},
+    result::InstructionResult,
+};
+use proptest::prelude::*;
+use sanctum_marinade_liquid_staking_core::{
+    self as marinade_staking_sdk, list::ListAccount, MarinadeError, StakeRecord, StakeStateV2,
+    State, ValidatorRecord, WithdrawStakeAccountIxKeysOwned, WithdrawStakeCandidate,
+    WithdrawStakeQuoteArgs, PACKET_DATA_SIZE,
+};
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+
+use crate::common::{
+    any_quotable_state, any_stake_records, ix_from_sdk, legacy_transaction_size,
+    marinade_mainnet_accounts, mollusk_marinade_prog, msol_token_acc, payer_account,
+    token_acc_balance, KeyedUiAccount,
+};
+
+const BLADE_VALIDATOR_INDEX: usize = 589;
+const FIXTURE_STAKE_INDEX: usize = 10;
+
+fn user_keys(
+    user: [u8; 32],
+    burn_msol_from: [u8; 32],
+    state: &State,
+) -> WithdrawStakeAccountIxKeysOwned {
+    WithdrawStakeAccountIxKeysOwned::default()
+        .with_consts()
+        .with_mainnet_consts()
+        .with_keys_from_stake_pool(state)
+        .with_burn_msol_from(burn_msol_from)
+        .with_burn_msol_authority(user)
+        .with_split_stake_rent_payer(user)
+}
+
+fn find_mut<'a>(accounts: &'a mut [(Pubkey, Account)], pubkey: &[u8; 32]) -> &'a mut Account {
+    &mut accounts
+        .iter_mut()
+        .find(|(pk, _)| pk.as_array() == pubkey)
+        .unwrap()
+        .1
+}
+
+fn find<'a>(accounts: &'a [(Pubkey, Account)], pubkey: &[u8; 32]) -> &'a Account {
+    &accounts
+        .iter()
+        .find(|(pk, _)| pk.as_array() == pubkey)
+        .unwrap()
+        .1
+}
+
+/// Adds a second stake account delegated to BLADE to the mainnet fixtures
+/// so that a withdrawal too large for the fixture stake account is split across both
+#[test]
+fn withdraw_stake_plan_mollusk() {
+    const ADDED_STAKE: u64 = 10_000_000_000_000;
+
+    let mut accounts: Vec<_> = marinade_mainnet_accounts().collect();
+
+    let fixture_stake_account = KeyedUiAccount::from_test_fixtures_file("withdraw_stake_account");
+    let fixture_stake_account_pubkey = bs58::decode_pubkey(&fixture_stake_account.pubkey);
+    let fixture_stake_state =
+        StakeStateV2::borsh_de(fixture_stake_account.account_data().as_slice()).unwrap();
+    let StakeStateV2::Stake(meta, mut stake, flags) = fixture_stake_state else {
+        panic!("fixture should be delegated");
+    };
+    let voter = stake.delegation.voter_pubkey;
+
+    // new stake account
+    let added_stake_account = Pubkey::new_unique();
+    stake.delegation.stake = ADDED_STAKE;
+    let mut data = find(&accounts, &fixture_stake_account_pubkey).data.clone();
+    StakeStateV2::Stake(meta, stake, flags)
+        .borsh_ser(data.as_mut_slice())
+        .unwrap();
+    accounts.push((
+        added_stake_account,
+        Account {
+            lamports: ADDED_STAKE + meta.rent_exempt_reserve,
+            data,
+            ..find(&accounts, &fixture_stake_account_pubkey).clone()
+        },
+    ));
+
+    // state, stake list and validator list accounting for the new stake account
+    let mut state = State::borsh_de(
+        find(&accounts, &marinade_staking_sdk::STATE_PUBKEY)
+            .data
+            .as_slice(),
+    )
+    .unwrap();
+    let added_stake_index = state.stake_system.stake_list.count as usize;
+    let fixture_record = {
+        let stake_list_data = &find(&accounts, &marinade_staking_sdk::STAKE_LIST_PUBKEY).data;
+        marinade_staking_sdk::StakeList::try_from_acc_data(stake_list_data, added_stake_index)
+            .unwrap()
+            .0[FIXTURE_STAKE_INDEX]
+    };
+    let added_record = StakeRecord::new(
+        added_stake_account.to_bytes(),
+        ADDED_STAKE,
+        fixture_record.last_update_epoch(),
+        false,
+    );
+    let record_size = state.stake_system.stake_list.item_size as usize;
+    let offset = 8 + added_stake_index * record_size;
+    added_record
+        .serialize(
+            &mut &mut find_mut(&mut accounts, &marinade_staking_sdk::STAKE_LIST_PUBKEY).data
+                [offset..offset + record_size],
+        )
+        .unwrap();
+
+    let validator_record_size = state.validator_system.validator_list.item_size as usize;
+    let offset = 8 + BLADE_VALIDATOR_INDEX * validator_record_size;
+    let validator_list_data =
+        &mut find_mut(&mut accounts, &marinade_staking_sdk::VALIDATOR_LIST_PUBKEY).data;
+    let blade = ValidatorRecord::deserialize(&mut &validator_list_data[offset..]).unwrap();
+    assert_eq!(*blade.validator_account(), voter);
+    blade
+        .with_active_balance(blade.active_balance() + ADDED_STAKE)
+        .serialize(&mut &mut validator_list_data[offset..offset + validator_record_size])
+        .unwrap();
+
+    state.stake_system.stake_list.count += 1;
+    state.validator_system.total_active_balance += ADDED_STAKE;
+    state
+        .borsh_ser(
+            find_mut(&mut accounts, &marinade_staking_sdk::STATE_PUBKEY)
+                .data
+                .as_mut_slice(),
+        )
+        .unwrap();
+
+    // plan
+    let stake_list_data = find(&accounts, &marinade_staking_sdk::STAKE_LIST_PUBKEY)
+        .data
+        .clone();
+    let stake_list = marinade_staking_sdk::StakeList::try_from_acc_data(
+        &stake_list_data,
+        state.stake_system.stake_list.count as usize,
+    )
+    .unwrap();
+    let validator_list_data = find(&accounts, &marinade_staking_sdk::VALIDATOR_LIST_PUBKEY)
+        .data
+        .clone();
+    let validator_list = marinade_staking_sdk::ValidatorList::try_from_acc_data(
+        &validator_list_data,
+        state.validator_system.validator_list.count as usize,
+    )
+    .unwrap();
+
+    let voter_of = |pk: &[u8; 32]| {
+        [fixture_stake_account_pubkey, added_stake_account.to_bytes()]
+            .contains(pk)
+            .then_some(voter)
+    };
+    let mut candidates: Vec<_> = state
+        .max_withdraw_stake_candidates(&validator_list, &stake_list, voter_of)
+        .collect();
+    candidates.sort_by(WithdrawStakeCandidate::prefer_largest);
+    assert_eq!(
+        candidates
+            .iter()
+            .map(|c| c.stake_index as usize)
+            .collect::<Vec<_>>(),
+        [FIXTURE_STAKE_INDEX, added_stake_index]
+    );
+
+    let pool_tokens = candidates[0].quote.tokens_in + 1_000_000_000_000;
+
+    let user = Pubkey::new_unique();
+    let burn_msol_from = Pubkey::new_unique();
+    let split_stake_accounts = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let plan = state
+        .plan_withdraw_stake(
+            candidates,
+            pool_tokens,
+            &user_keys(user.to_bytes(), burn_msol_from.to_bytes(), &state),
+            user.to_bytes(),
+            |i| split_stake_accounts[i].to_bytes(),
+        )
+        .unwrap();
+
+    assert_eq!(plan.steps.len(), 2);
+    assert_eq!(plan.transactions.len(), 1);
+    assert_eq!(plan.transactions[0], 0..2);
+    assert_eq!(plan.total.tokens_in, pool_tokens);
+
+    // execute
+    let mollusk = mollusk_marinade_prog();
+    let treasury_before = token_acc_balance(find(&accounts, &state.treasury_msol_account));
+    accounts.extend([
+        keyed_account_for_system_program(),
+        create_keyed_account_for_builtin_program(
+            &Pubkey::new_from_array(marinade_staking_sdk::STAKE_PROGRAM),
+            "solana_stake_program",
+        ),
+        mollusk.sysvars.keyed_account_for_clock_sysvar(),
+        mollusk_svm_programs_token::token::keyed_account(),
+        (user, payer_account(5_000_000_000_000)),
+        (burn_msol_from, msol_token_acc(pool_tokens, user)),
+        (split_stake_accounts[0], Account::default()),
+        (split_stake_accounts[1], Account::default()),
+    ]);
+
+    let ixs: Vec<_> = plan
+        .steps
+        .iter()
+        .map(|step| ix_from_sdk(&step.ix))
+        .collect();
+    let InstructionResult {
+        raw_result,
+        resulting_accounts,
+        ..
+    } = mollusk.process_instruction_chain(&ixs, &accounts);
+    raw_result.unwrap();
+
+    assert_eq!(
+        token_acc_balance(find(&resulting_accounts, &burn_msol_from.to_bytes())),
+        0
+    );
+    assert_eq!(
+        token_acc_balance(find(&resulting_accounts, &state.treasury_msol_account))
+            - treasury_before,
+        plan.total.fee_amount
+    );
+    for (split_stake_account, vote_account, lamports_staked) in plan.split_stake_accounts() {
+        let split = StakeStateV2::borsh_de(
+            find(&resulting_accounts, &split_stake_account)
+                .data
+                .as_slice(),
+        )
+        .unwrap();
+        let delegation = split.delegation().unwrap();
+        assert_eq!(delegation.voter_pubkey, vote_account);
+        assert_eq!(delegation.stake, lamports_staked);
+    }
+    assert_eq!(
+        State::borsh_de(
+            find(&resulting_accounts, &marinade_staking_sdk::STATE_PUBKEY)
+                .data
+                .as_slice()
+        )
+        .unwrap(),
+        plan.state_after
+    );
+}
+
+proptest! {
+    #[test]
+    fn withdraw_stake_plan_steps_pass_quote(
+        state in any_quotable_state(),
+        stakes in any_stake_records(1_000_000_000..=100_000_000_000_000u64, 1..=32),
+        pool_tokens in 0..=1_000_000_000_000_000u64,
+    ) {
+        let validators = [ValidatorRecord::new([1; 32], stakes.iter().map(|s| s.last_update_delegated_lamports()).sum(), 1, 0, 0)];
+        let mut state = state;
+        state.validator_system.total_active_balance =
+            state.validator_system.total_active_balance.max(validators[0].active_balance());
+        let validator_list = ListAccount(validators.as_slice());
+        let stake_list = ListAccount(stakes.as_slice());
+        let candidates: Vec<_> = state
+            .max_withdraw_stake_candidates(&validator_list, &stake_list, |_| Some([1; 32]))
+            .collect();
+
+        let user = [0xff; 32];
+        let Ok(plan) = state.plan_withdraw_stake(
+            candidates,
+            pool_tokens,
+            &user_keys(user, [0xfe; 32], &state),
+            user,
+            |i| [i as u8; 32],
+        ) else {
+            return Ok(());
+        };
+
+        let mut expected_state = state.clone();
+        let mut tokens_in = 0;
+        let mut fee_amount = 0;
+        for step in plan.steps.iter() {
+            let stake_record = step.candidate.stake_record;
+            let quote = expected_state
+                .quote_withdraw_stake(step.quote.tokens_in, WithdrawStakeQuoteArgs { stake_record })
+                .unwrap();
+            prop_assert_eq!(quote, step.quote);
+            expected_state = expected_state
+                .apply_withdraw_stake(&quote, &stake_record, &step.candidate.validator_record)
+                .unwrap()
+                .0;
+            tokens_in += quote.tokens_in;
+            fee_amount += quote.fee_amount;
+        }
+        prop_assert_eq!(tokens_in, pool_tokens);
+        prop_assert_eq!(plan.total.tokens_in, pool_tokens);
+        prop_assert_eq!(plan.total.fee_amount, fee_amount);
+        prop_assert_eq!(&plan.state_after, &expected_state);
+
+        // transactions partition the steps and fit in a packet
+        let mut next = 0;
+        for range in plan.transactions.iter() {
+            prop_assert_eq!(range.start, next);
+            next = range.end;
+            let ixs: Vec<_> = plan.steps[range.clone()]
+                .iter()
+                .map(|step| ix_from_sdk(&step.ix))
+                .collect();
+            let size = legacy_transaction_size(&ixs, user);
+            prop_assert!(size <= PACKET_DATA_SIZE || range.len() == 1);
+            // and greedily so
+            if let Some(step) = plan.steps.get(range.end) {
+                let mut ixs = ixs;
+                ixs.push(ix_from_sdk(&step.ix));
+                let size = legacy_transaction_size(&ixs, user);
+                prop_assert!(size > PACKET_DATA_SIZE);
+            }
+        }
+        prop_assert_eq!(next, plan.steps.len());
+    }
+}
+
+#[test]
+fn withdraw_stake_plan_returns_last_quote_error() {
+    let mut state = State::DEFAULT;
+    state.withdraw_stake_account_enabled = true;
+    state.validator_system.total_active_balance = 100_000_000_000;
+    state.msol_supply = 100_000_000_000;
+    let validators = [ValidatorRecord::new([1; 32], 100_000_000_000, 1, 0, 0)];
+    let stakes = [StakeRecord::new([2; 32], 100_000_000_000, 0, false)];
+    let candidates: Vec<_> = state
+        .max_withdraw_stake_candidates(
+            &ListAccount(validators.as_slice()),
+            &ListAccount(stakes.as_slice()),
+            |_| Some([1; 32]),
+        )
+        .collect();
+    assert_eq!(candidates.len(), 1);
+
+    let user = [0xff; 32];
+    let plan = |state: &State, pool_tokens| {
+        state
+            .plan_withdraw_stake(
+                candidates.iter().copied(),
+                pool_tokens,
+                &user_keys(user, [0xfe; 32], state),
+                user,
+                |i| [i as u8; 32],
+            )
+            .map(|plan| plan.total)
+    };
+
+    let mut paused = state.clone();
+    paused.paused = true;
+    assert_eq!(
+        plan(&paused, 1_000_000_000),
+        Err(MarinadeError::ProgramIsPaused)
+    );
+
+    let mut disabled = state.clone();
+    disabled.withdraw_stake_account_enabled = false;
+    assert_eq!(
+        plan(&disabled, 1_000_000_000),
+        Err(MarinadeError::WithdrawStakeAccountIsNotEnabled)
+    );
+
+    // every candidate is used up
+    assert_eq!(
+        plan(&state, 200_000_000_000),
+        Err(MarinadeError::SelectedStakeAccountHasNotEnoughFunds)
+    );
+
+    // too little to split off a stake account
+    let mut min_stake = state.clone();
+    min_stake.stake_system.min_stake = 1_000_000_000;
+    assert_eq!(
+        plan(&min_stake, 1_000),
+        Err(MarinadeError::WithdrawStakeLamportsIsTooLow)
+    );
+}